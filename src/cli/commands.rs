@@ -1,18 +1,27 @@
 use anyhow::{Context, Result};
+use chrono::Utc;
 use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::HashSet;
 use std::path::Path;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tracing::info;
 
-use super::output::JsonOutput;
+use super::output::{JsonIssueMentions, JsonOutput, JsonResult};
+use crate::config::{MAX_DIFF_SIZE_KEY, Settings};
 use crate::embedding::ModelManager;
-use crate::git::{GitError, RepositoryParser};
-use crate::index::{EXACT_SCAN_THRESHOLD, IndexBuilder, IndexError, IndexStorage, SemanticIndex};
-use crate::search::{SearchEngine, SearchOptions, SearchStrategy};
-use crate::text::Bm25Params;
-use crate::vector::HnswParams;
-
-use super::SearchRequest;
+use crate::git::{CommitInfo, DateField, IGNORE_FILE, IdentityMap, RepositoryParser, issues};
+use crate::index::{
+    EXACT_SCAN_THRESHOLD, EmbeddingCache, IndexBuilder, IndexError, IndexLock, IndexStorage,
+    SemanticIndex, cache, portable, pulls, share,
+};
+use crate::search::{
+    self, RetrievalMode, SearchEngine, SearchOptions, SearchOutcome, SearchResult, SearchStrategy,
+    Workspace,
+};
+use crate::text::Bm25Index;
+use crate::vector::HnswIndex;
+
+use super::{SearchFilters, SearchRequest};
 
 /// Where indexing progress is written.
 ///
@@ -84,13 +93,21 @@ fn download_model(manager: &ModelManager, progress: Progress) -> Result<()> {
     Ok(())
 }
 
-pub fn index(repo_path: &str, include_diffs: bool, force: bool) -> Result<()> {
+/// Index the repository in full mode (`Some(true)`), quick mode, or — given
+/// `None` — whichever `semantic.indexMode` names.
+pub fn index(repo_path: &str, include_diffs: Option<bool>, force: bool) -> Result<()> {
     let path = Path::new(repo_path);
+    let settings = Settings::load(path)?;
+    let include_diffs = include_diffs.unwrap_or(settings.include_diffs.value);
     let storage = IndexStorage::new(path)?;
+    let _lock = lock_index(&storage, Progress::Stdout)?;
 
     let existing_index = match storage.load() {
         Ok(idx) => Some(idx),
         Err(IndexError::IndexNotFound) => None,
+        // A forced rebuild discards whatever is there, so an index this version
+        // cannot read is no obstacle to it.
+        Err(_) if force => None,
         Err(e) => return Err(e).context("Failed to load existing index"),
     };
 
@@ -108,7 +125,14 @@ pub fn index(repo_path: &str, include_diffs: bool, force: bool) -> Result<()> {
                         existing.entries.len()
                     );
                 }
-                full_index(path, &storage, include_diffs, Progress::Stdout)?;
+                full_index(
+                    path,
+                    &storage,
+                    include_diffs,
+                    &settings,
+                    Some(&existing),
+                    Progress::Stdout,
+                )?;
                 return Ok(());
             }
 
@@ -121,7 +145,7 @@ pub fn index(repo_path: &str, include_diffs: bool, force: bool) -> Result<()> {
                          To downgrade to quick mode (smaller index), run with --force.\n\
                          Note: switching back to full mode later will require re-embedding all commits.\n"
                     );
-                    incremental_index(path, &storage, existing, existing_mode)?;
+                    incremental_index(path, &storage, existing, existing_mode, &settings)?;
                 } else {
                     // Quick index exists, full requested — requires re-embedding everything
                     println!(
@@ -134,20 +158,31 @@ pub fn index(repo_path: &str, include_diffs: bool, force: bool) -> Result<()> {
                 return Ok(());
             }
 
-            incremental_index(path, &storage, existing, include_diffs)?;
+            incremental_index(path, &storage, existing, include_diffs, &settings)?;
         }
         None => {
-            full_index(path, &storage, include_diffs, Progress::Stdout)?;
+            full_index(
+                path,
+                &storage,
+                include_diffs,
+                &settings,
+                None,
+                Progress::Stdout,
+            )?;
         }
     }
 
     Ok(())
 }
 
+/// Build the index from scratch. What `previous` holds that git does not —
+/// imported pull requests — is carried over to the same commits.
 fn full_index(
     path: &Path,
     storage: &IndexStorage,
     include_diffs: bool,
+    settings: &Settings,
+    previous: Option<&SemanticIndex>,
     progress: Progress,
 ) -> Result<SemanticIndex> {
     let mode = if include_diffs { "full" } else { "quick" };
@@ -158,101 +193,471 @@ fn full_index(
     ));
 
     info!("Parsing git repository...");
-    let parser = RepositoryParser::new(path)?;
-    let commits = parser.parse_commits(include_diffs)?;
+    let mut parser = RepositoryParser::new(path)?;
+    parser.set_max_diff_size(settings.max_diff_size.value);
+    // Read first, so a note added mid-walk moves the tip past what is recorded.
+    let notes_tips = parser.notes_tips()?;
+    let mut commits = parser.parse_commits(include_diffs)?;
+    let tags = parser.parse_tags()?;
+    if let Some(previous) = previous {
+        pulls::carry_over(previous, &mut commits);
+    }
 
-    progress.say(&format!("Found {} commits to index\n", commits.len()));
+    progress.say(&format!(
+        "Found {} commits{} to index\n",
+        commits.len(),
+        tag_count_note(tags.len())
+    ));
 
     let model_manager = ensure_model(progress)?;
     let mut builder = IndexBuilder::new(model_manager, include_diffs)?;
-
-    // Commits are in newest-first order from revwalk; track HEAD as last_commit
-    if let Some(first) = commits.first() {
-        builder.set_last_commit(first.hash.clone());
+    let cache = use_embedding_cache(path, &mut builder)?;
+    builder.set_ignore_fingerprint(parser.ignore_fingerprint().to_string());
+    builder.set_max_diff_size(settings.max_diff_size.value);
+    builder.set_notes_tips(notes_tips);
+
+    // HEAD itself may be an ignored commit, so ask for it rather than take
+    // the newest one parsed.
+    let head = parser.head().ok();
+    if let Some(head) = &head {
+        builder.set_last_commit(head.clone());
     }
 
-    let pb = make_progress_bar(commits.len() as u64);
+    let pb = make_progress_bar((commits.len() + tags.len()) as u64);
 
-    for commit in commits {
+    for commit in commits.into_iter().chain(tags) {
         builder.add_commit(commit)?;
         pb.inc(1);
     }
 
     pb.finish_with_message("✅ Commits indexed");
 
+    let cache_hits = builder.cache_hits();
     progress.say("\n💾 Saving index...");
     let index = builder.build();
     storage.save(&index)?;
+    if let Some(head) = &head {
+        storage.set_worktree_head(head)?;
+    }
+    settle_embedding_cache(cache.as_ref(), cache_hits, progress);
 
     print_index_stats(&index, storage, progress)?;
-    refresh_search_graph(&index, storage, progress);
+    refresh_search_graph(&index, storage, settings, progress);
 
     Ok(index)
 }
 
+/// Index what `existing` is missing, and drop what it should no longer hold.
+/// Returns whether the index changed.
 fn incremental_index(
     path: &Path,
     storage: &IndexStorage,
-    existing: SemanticIndex,
+    mut existing: SemanticIndex,
     include_diffs: bool,
-) -> Result<()> {
-    let parser = RepositoryParser::new(path)?;
+    settings: &Settings,
+) -> Result<bool> {
+    let mut parser = RepositoryParser::new(path)?;
+    parser.set_max_diff_size(settings.max_diff_size.value);
+    let head = parser.head()?;
+
+    // Which commits and paths the index holds depends on the rules, so new
+    // ones apply to all of history, not just what comes next.
+    if existing.metadata.ignore_fingerprint != parser.ignore_fingerprint() {
+        println!("ℹ️  {IGNORE_FILE} changed since the index was built — re-indexing.\n");
+        full_index(
+            path,
+            storage,
+            include_diffs,
+            settings,
+            Some(&existing),
+            Progress::Stdout,
+        )?;
+        return Ok(true);
+    }
+    // Likewise every stored diff was cut to the old budget. A quick index
+    // stores none, so the setting cannot have changed anything in it.
+    if include_diffs && existing.metadata.max_diff_size != settings.max_diff_size.value {
+        println!("ℹ️  {MAX_DIFF_SIZE_KEY} changed since the index was built — re-indexing.\n");
+        full_index(
+            path,
+            storage,
+            include_diffs,
+            settings,
+            Some(&existing),
+            Progress::Stdout,
+        )?;
+        return Ok(true);
+    }
 
-    let new_commits = match parser.parse_commits_since(&existing.last_commit, include_diffs) {
-        Ok(commits) => commits,
-        Err(GitError::CommitNotFound(_)) => {
+    // When what this worktree last indexed is no longer behind HEAD, a rebase
+    // or force-push rewrote history. The commits it left behind would still
+    // turn up in search, so drop whatever no ref or worktree HEAD reaches.
+    let previous = storage
+        .worktree_head()
+        .unwrap_or_else(|| existing.last_commit.clone());
+    let mut pruned = 0;
+    if !parser.head_descends_from(&previous)? {
+        let referenced = parser.referenced_commits()?;
+        let before = existing.entries.len();
+        existing
+            .entries
+            .retain(|entry| entry.commit.is_tag() || referenced.contains(&entry.commit.hash));
+        pruned = before - existing.entries.len();
+        if pruned > 0 {
             println!(
-                "⚠️  Previously indexed commit {} not found in history (was the branch rebased?).",
-                &existing.last_commit[..7.min(existing.last_commit.len())]
+                "⚠️  History was rewritten since {} was indexed (was the branch rebased?).",
+                previous.get(..7).unwrap_or(&previous)
             );
-            println!("Re-indexing from scratch...\n");
-            full_index(path, storage, include_diffs, Progress::Stdout)?;
-            return Ok(());
+            println!("Dropping {pruned} commits no branch reaches any more.\n");
         }
-        Err(err) => {
-            return Err(err.into());
+    }
+
+    // A deleted tag should stop turning up in search, and a moved one should
+    // not turn up at both the commit it left and the one it points at now.
+    let tags = parser.parse_tags()?;
+    let before = existing.entries.len();
+    existing.entries.retain(|entry| {
+        !entry.commit.is_tag()
+            || tags
+                .iter()
+                .any(|tag| tag.hash == entry.commit.hash && tag.tag == entry.commit.tag)
+    });
+    let stale_tags = before - existing.entries.len();
+    if stale_tags > 0 {
+        println!("Dropping {stale_tags} tags that were deleted or moved.\n");
+        pruned += stale_tags;
+    }
+
+    // Notes are mostly added after a commit was indexed, by review or CI. When
+    // a notes ref has moved, read every commit's notes again and re-embed the
+    // ones that changed.
+    let notes_tips = parser.notes_tips()?;
+    let notes_moved = notes_tips != existing.metadata.notes_tips;
+    let mut renoted = Vec::new();
+    if notes_moved {
+        for (position, entry) in existing.entries.iter_mut().enumerate() {
+            if entry.commit.is_tag() {
+                continue;
+            }
+            let notes = parser.notes_on(&entry.commit.hash)?;
+            if notes != entry.commit.notes {
+                entry.commit.notes = notes;
+                renoted.push(position);
+            }
         }
-    };
+    }
 
-    if new_commits.is_empty() {
-        println!(
-            "✅ Index is already up to date! ({} commits indexed)",
-            existing.entries.len()
-        );
-        return Ok(());
+    // The index may be shared with worktrees on other branches, so HEAD need
+    // not descend from `last_commit`: find what is missing instead. What this
+    // worktree and the last indexer had at HEAD was indexed with its whole
+    // history, so the walk can stop at either.
+    let indexed: HashSet<&str> = existing
+        .entries
+        .iter()
+        .filter(|entry| !entry.commit.is_tag())
+        .map(|entry| entry.commit.hash.as_str())
+        .collect();
+    let boundaries: Vec<String> = [storage.worktree_head(), Some(existing.last_commit.clone())]
+        .into_iter()
+        .flatten()
+        .filter(|hash| indexed.contains(hash.as_str()))
+        .collect();
+    let boundaries: Vec<&str> = boundaries.iter().map(String::as_str).collect();
+    let new_commits = parser.parse_unindexed_commits(&indexed, &boundaries, include_diffs)?;
+
+    // Tags are not reachable from `last_commit` the way commits are, so new
+    // ones are found by comparing against what is already indexed.
+    let new_tags: Vec<_> = tags
+        .into_iter()
+        .filter(|tag| {
+            !existing
+                .entries
+                .iter()
+                .any(|entry| entry.commit.hash == tag.hash && entry.commit.tag == tag.tag)
+        })
+        .collect();
+
+    if new_commits.is_empty() && new_tags.is_empty() && renoted.is_empty() {
+        storage.set_worktree_head(&head)?;
+        if pruned == 0 && !notes_moved {
+            println!(
+                "✅ Index is already up to date! ({} commits indexed)",
+                existing.entries.len()
+            );
+            return Ok(false);
+        }
+
+        // Only removals, so there is nothing to embed and no model to load.
+        existing.last_commit = head;
+        existing.metadata.total_commits = existing.entries.len();
+        existing.metadata.updated_at = Utc::now();
+        existing.metadata.notes_tips = notes_tips;
+        storage.save(&existing)?;
+        print_index_stats(&existing, storage, Progress::Stdout)?;
+        refresh_search_graph(&existing, storage, settings, Progress::Stdout);
+        return Ok(true);
     }
 
     let mode = if include_diffs { "full" } else { "quick" };
     println!(
-        "📚 Updating index ({} mode): {} ({} new commits)\n",
+        "📚 Updating index ({} mode): {} ({} new commits{})\n",
         mode,
         path.display(),
-        new_commits.len()
+        new_commits.len(),
+        tag_count_note(new_tags.len())
     );
+    if !renoted.is_empty() {
+        println!(
+            "📝 Notes changed on {} commits since they were indexed.\n",
+            renoted.len()
+        );
+    }
 
     let model_manager = ensure_model(Progress::Stdout)?;
     let mut builder = IndexBuilder::from_existing(existing, model_manager)?;
+    let cache = use_embedding_cache(path, &mut builder)?;
+    builder.set_last_commit(head.clone());
+    builder.set_notes_tips(notes_tips);
 
-    // New commits are newest-first; update last_commit to the newest
-    if let Some(first) = new_commits.first() {
-        builder.set_last_commit(first.hash.clone());
-    }
-
-    let pb = make_progress_bar(new_commits.len() as u64);
+    let pb = make_progress_bar((renoted.len() + new_commits.len() + new_tags.len()) as u64);
 
-    for commit in new_commits {
+    for position in renoted {
+        builder.reembed(position)?;
+        pb.inc(1);
+    }
+    for commit in new_commits.into_iter().chain(new_tags) {
         builder.add_commit(commit)?;
         pb.inc(1);
     }
 
     pb.finish_with_message("✅ New commits indexed");
 
+    let cache_hits = builder.cache_hits();
     println!("\n💾 Saving index...");
     let index = builder.build();
     storage.save(&index)?;
+    storage.set_worktree_head(&head)?;
+    settle_embedding_cache(cache.as_ref(), cache_hits, Progress::Stdout);
 
     print_index_stats(&index, storage, Progress::Stdout)?;
-    refresh_search_graph(&index, storage, Progress::Stdout);
+    refresh_search_graph(&index, storage, settings, Progress::Stdout);
+
+    Ok(true)
+}
+
+/// Publish this repository's index under `refs/semantic/index` and push it.
+pub fn index_push(repo_path: &str, remote: &str) -> Result<()> {
+    let path = Path::new(repo_path);
+    let index = IndexStorage::new(path)?.load()?;
+    let repo = git2::Repository::discover(path)?;
+
+    let head = index.last_commit.get(..7).unwrap_or(&index.last_commit);
+    println!(
+        "📦 Publishing index of {} commits (up to {head}) as {}",
+        index.entries.len(),
+        share::SHARED_INDEX_REF
+    );
+    share::publish(&repo, &index)?;
+
+    println!("⬆️  Pushing to {remote}...\n");
+    share::push(path, remote)?;
+    println!("\n✅ Shared index pushed to {remote}");
+    Ok(())
+}
+
+/// Fetch the index published on `remote`, adopt it, and index whatever local
+/// commits it does not have yet. An index already here is only replaced with
+/// `force`, since adopting drops whatever it holds that the shared one lacks.
+pub fn index_fetch(repo_path: &str, remote: &str, force: bool) -> Result<()> {
+    let path = Path::new(repo_path);
+    let settings = Settings::load(path)?;
+    let storage = IndexStorage::new(path)?;
+    let _lock = lock_index(&storage, Progress::Stdout)?;
+
+    match storage.load() {
+        Ok(_) if !force => return Err(IndexError::IndexExists.into()),
+        Ok(_) | Err(IndexError::IndexNotFound) => {}
+        Err(_) if force => {}
+        Err(e) => return Err(e).context("Failed to load existing index"),
+    }
+
+    println!(
+        "⬇️  Fetching {} from {remote}...\n",
+        share::SHARED_INDEX_REF
+    );
+    share::fetch(path, remote)?;
+    let shared = share::read(&git2::Repository::discover(path)?)?;
+    share::check_model(&shared, &ModelManager::new()?.model_version())?;
+
+    println!(
+        "\n📥 Adopting shared index of {} commits",
+        shared.entries.len()
+    );
+    storage.save(&shared)?;
+
+    let include_diffs = shared.metadata.include_diffs;
+    if !incremental_index(path, &storage, shared, include_diffs, &settings)? {
+        // Nothing new, so nothing rebuilt the sidecars for the adopted index.
+        refresh_search_graph(&storage.load()?, &storage, &settings, Progress::Stdout);
+    }
+    Ok(())
+}
+
+/// Take the repository's index for updating. Worktrees share it, so another
+/// one may be mid-update; say so rather than appear to hang.
+fn lock_index(storage: &IndexStorage, progress: Progress) -> Result<IndexLock> {
+    if let Some(lock) = storage.try_lock()? {
+        return Ok(lock);
+    }
+    progress.say("⏳ Another git-semantic is updating this repository's index; waiting for it...");
+    Ok(storage.lock()?)
+}
+
+/// Hand `builder` the machine-wide embedding cache when git config turns it
+/// on, returning it for [`settle_embedding_cache`].
+fn use_embedding_cache(path: &Path, builder: &mut IndexBuilder) -> Result<Option<EmbeddingCache>> {
+    let config = git2::Repository::discover(path)?.config()?;
+    let cache = EmbeddingCache::from_config(&config)?;
+    if let Some(cache) = &cache {
+        builder.set_cache(cache.clone());
+    }
+    Ok(cache)
+}
+
+/// Report what the cache saved, then hold it to its size cap. A prune that
+/// fails leaves the cache oversized, which the next run retries.
+fn settle_embedding_cache(cache: Option<&EmbeddingCache>, hits: usize, progress: Progress) {
+    let Some(cache) = cache else {
+        return;
+    };
+    if hits > 0 {
+        progress.say(&format!("♻️  Reused {hits} embeddings from the cache"));
+    }
+    if let Err(err) = cache.prune() {
+        tracing::debug!("could not prune the embedding cache: {err}");
+    }
+}
+
+/// `" and N tags"` for progress messages, or nothing when there are none.
+fn tag_count_note(tags: usize) -> String {
+    match tags {
+        0 => String::new(),
+        1 => " and 1 tag".to_string(),
+        n => format!(" and {n} tags"),
+    }
+}
+
+/// Attach pull request metadata from an export file and re-embed the commits
+/// it lands on.
+pub fn import_prs(repo_path: &str, file: &str) -> Result<()> {
+    let path = Path::new(repo_path);
+    let settings = Settings::load(path)?;
+    let storage = IndexStorage::new(path)?;
+    let _lock = lock_index(&storage, Progress::Stdout)?;
+    let mut index = storage.load()?;
+
+    let export = std::fs::read_to_string(file)
+        .with_context(|| format!("failed to read pull request export {file}"))?;
+    let pulls = pulls::parse_export(&export)?;
+    let total = pulls.len();
+
+    let changed = pulls::attach(&mut index, pulls);
+    let attached = index
+        .entries
+        .iter()
+        .filter(|entry| entry.commit.pull_request.is_some())
+        .count();
+    println!("🔗 Read {total} pull requests from {file}");
+
+    if changed.is_empty() {
+        println!("✅ Nothing new to attach ({attached} commits already have one)");
+        return Ok(());
+    }
+
+    println!(
+        "Re-embedding {} commits with their PR text\n",
+        changed.len()
+    );
+    let model_manager = ensure_model(Progress::Stdout)?;
+    let mut builder = IndexBuilder::from_existing(index, model_manager)?;
+    let cache = use_embedding_cache(path, &mut builder)?;
+
+    let pb = make_progress_bar(changed.len() as u64);
+    for position in changed {
+        builder.reembed(position)?;
+        pb.inc(1);
+    }
+    pb.finish_with_message("✅ Pull requests attached");
+
+    let cache_hits = builder.cache_hits();
+    let index = builder.build();
+    storage.save(&index)?;
+    println!(
+        "\n{attached} of {} commits now carry pull request metadata",
+        index.entries.len()
+    );
+    settle_embedding_cache(cache.as_ref(), cache_hits, Progress::Stdout);
+    refresh_search_graph(&index, &storage, &settings, Progress::Stdout);
+
+    Ok(())
+}
+
+/// Write the index as JSON Lines to `output`, or to stdout.
+pub fn export(repo_path: &str, output: Option<&str>) -> Result<()> {
+    let index = IndexStorage::new(Path::new(repo_path))?.load()?;
+
+    // With no file the records are on stdout, so the summary cannot be.
+    let (written, progress) = match output {
+        Some(file) => {
+            let out =
+                std::fs::File::create(file).with_context(|| format!("failed to create {file}"))?;
+            (
+                portable::write_jsonl(&index, std::io::BufWriter::new(out))?,
+                Progress::Stdout,
+            )
+        }
+        None => (
+            portable::write_jsonl(&index, std::io::BufWriter::new(std::io::stdout().lock()))?,
+            Progress::Stderr,
+        ),
+    };
+
+    progress.say(&format!(
+        "📤 Exported {written} commits ({}, {})",
+        index.model_version,
+        if index.metadata.include_diffs {
+            "full mode"
+        } else {
+            "quick mode"
+        }
+    ));
+    Ok(())
+}
+
+/// Rebuild the index, and its search sidecars, from a JSON Lines export.
+pub fn import(repo_path: &str, file: &str, force: bool) -> Result<()> {
+    let path = Path::new(repo_path);
+    let settings = Settings::load(path)?;
+    let storage = IndexStorage::new(path)?;
+    let _lock = lock_index(&storage, Progress::Stdout)?;
+
+    match storage.load() {
+        Ok(_) if !force => return Err(IndexError::IndexExists.into()),
+        Ok(_) | Err(IndexError::IndexNotFound) => {}
+        Err(_) if force => {}
+        Err(e) => return Err(e).context("Failed to load existing index"),
+    }
+
+    let input =
+        std::fs::File::open(file).with_context(|| format!("failed to read export {file}"))?;
+    let repo = git2::Repository::discover(path)?;
+    let index = portable::read_jsonl(std::io::BufReader::new(input), portable::resolve_in(&repo))?;
+    share::check_model(&index, &ModelManager::new()?.model_version())?;
+    println!("📥 Read {} commits from {file}\n", index.entries.len());
+
+    storage.save(&index)?;
+    print_index_stats(&index, &storage, Progress::Stdout)?;
+    refresh_search_graph(&index, &storage, &settings, Progress::Stdout);
 
     Ok(())
 }
@@ -262,10 +667,147 @@ pub fn update(repo_path: &str) -> Result<()> {
         "Note: `git-semantic index` now automatically handles incremental updates.\n\
          The `update` command will be removed in a future release.\n"
     );
-    index(repo_path, true, false)
+    index(repo_path, None, false)
 }
 
 pub fn search(repo_path: &str, request: SearchRequest) -> Result<()> {
+    let path = Path::new(repo_path);
+    let settings = Settings::load(path)?;
+    let storage = IndexStorage::new(path)?;
+    let index = load_for_search(path, &storage, &settings)?;
+    search_index(path, &storage, &index, &settings, request)
+}
+
+/// List every commit that references `reference`, newest first by
+/// `date_field` — or, given a query, ranked by relevance to it.
+pub fn issue(
+    repo_path: &str,
+    reference: &str,
+    query: Option<String>,
+    date_field: DateField,
+    json: bool,
+) -> Result<()> {
+    let path = Path::new(repo_path);
+    let settings = Settings::load(path)?;
+    let storage = IndexStorage::new(path)?;
+    let index = load_for_search(path, &storage, &settings)?;
+
+    let needle = issues::normalize(reference);
+    let mut mentions: Vec<&CommitInfo> = index
+        .entries
+        .iter()
+        .map(|entry| &entry.commit)
+        .filter(|commit| commit.mentions_issue(&needle))
+        .collect();
+
+    if let Some(query) = query
+        && !mentions.is_empty()
+    {
+        // Every mention is wanted, only the order changes, so ask for exactly
+        // as many results as there are mentions and score them all.
+        return search_index(
+            path,
+            &storage,
+            &index,
+            &settings,
+            SearchRequest {
+                query,
+                num_results: Some(mentions.len()),
+                filters: SearchFilters {
+                    issue: Some(needle),
+                    date_field,
+                    ..SearchFilters::default()
+                },
+                exact: true,
+                ef: None,
+                mode: None,
+                diverse: false,
+                lambda: None,
+                json,
+            },
+        );
+    }
+
+    mentions.sort_by_key(|commit| std::cmp::Reverse(commit.date_for(date_field)));
+
+    if json {
+        let document = JsonIssueMentions {
+            issue: needle,
+            results: mentions
+                .into_iter()
+                .enumerate()
+                .map(|(i, commit)| {
+                    JsonResult::from(&SearchResult {
+                        commit: commit.clone(),
+                        similarity: f32::NAN,
+                        rank: i + 1,
+                    })
+                })
+                .collect(),
+        };
+        println!("{}", serde_json::to_string_pretty(&document)?);
+        return Ok(());
+    }
+
+    if mentions.is_empty() {
+        println!("No commits reference {needle}");
+        return Ok(());
+    }
+
+    println!("🔗 Commits referencing {needle}\n");
+    for (i, commit) in mentions.iter().enumerate() {
+        println!(
+            "{}. {} - {}",
+            i + 1,
+            &commit.hash[..7],
+            commit.message.lines().next().unwrap_or("")
+        );
+        println!(
+            "   Author: {}, {}",
+            commit.author_name(),
+            commit.local_date()
+        );
+        print_committed(commit, date_field);
+        println!();
+    }
+    println!("{} commits", mentions.len());
+
+    Ok(())
+}
+
+/// When the results were filtered or ordered by committer date, show it: it
+/// is often days or zones away from the author date printed above.
+fn print_committed(commit: &CommitInfo, date_field: DateField) {
+    if date_field == DateField::Committer && !commit.committer.is_empty() {
+        println!(
+            "   Committed: {}, {}",
+            commit.committer,
+            commit.local_date_for(DateField::Committer)
+        );
+    }
+}
+
+/// The index `search` runs against, building it on first use and applying
+/// the current `.mailmap`.
+fn load_for_search(
+    path: &Path,
+    storage: &IndexStorage,
+    settings: &Settings,
+) -> Result<SemanticIndex> {
+    let mut index = match storage.load() {
+        Ok(index) => index,
+        // Nothing indexed yet. Everything needed to fix that is already known,
+        // so do it rather than send the user off to run a different command.
+        Err(IndexError::IndexNotFound) => bootstrap(path, storage, settings)?,
+        Err(err) => return Err(err.into()),
+    };
+    apply_mailmap(path, &mut index);
+    Ok(index)
+}
+
+/// Split `request` into its query, its options with whatever it leaves unset
+/// taken from `settings`, and whether to emit JSON.
+fn search_options(request: SearchRequest, settings: &Settings) -> (String, SearchOptions, bool) {
     let SearchRequest {
         query,
         num_results,
@@ -273,27 +815,128 @@ pub fn search(repo_path: &str, request: SearchRequest) -> Result<()> {
         exact,
         ef,
         mode,
-        diversity,
+        diverse,
+        lambda,
         json,
     } = request;
-    let query = query.as_str();
+    let options = SearchOptions {
+        num_results: num_results.unwrap_or(settings.results.value),
+        filters,
+        exact,
+        ef: ef.or(Some(settings.hnsw_ef.value)),
+        mode: mode.unwrap_or(settings.mode.value),
+        diversity: diverse.then(|| lambda.unwrap_or(settings.lambda.value)),
+        repository: None,
+    };
+    (query, options, json)
+}
 
-    let path = Path::new(repo_path);
+fn search_index(
+    path: &Path,
+    storage: &IndexStorage,
+    index: &SemanticIndex,
+    settings: &Settings,
+    request: SearchRequest,
+) -> Result<()> {
+    let (query, mut options, json) = search_options(request, settings);
+    options.repository = Some(path.to_path_buf());
+    let date_field = options.filters.date_field;
+    let (lexical, graph) =
+        load_search_sidecars(storage, index, settings, options.mode, options.exact, json);
 
-    let storage = IndexStorage::new(path)?;
-    let index = match storage.load() {
-        Ok(index) => index,
-        // Nothing indexed yet. Everything needed to fix that is already known,
-        // so do it rather than send the user off to run a different command.
-        Err(IndexError::IndexNotFound) => bootstrap(path, &storage)?,
-        Err(err) => return Err(err.into()),
+    let model_manager = ModelManager::new()?;
+    let mut engine = SearchEngine::new(model_manager)?;
+
+    let started = Instant::now();
+    let outcome = engine.search(index, graph.as_ref(), lexical.as_ref(), &query, options)?;
+
+    print_outcome(&query, &outcome, None, date_field, json, started.elapsed())
+}
+
+/// Search every repository in the `semantic.workspace` list and print one
+/// merged ranking, each result tagged with its repository.
+pub fn search_workspace(request: SearchRequest) -> Result<()> {
+    // The current repository's config includes the global one, and lets a
+    // checkout that ties the others together carry its own list.
+    let config = match git2::Repository::discover(".") {
+        Ok(repo) => repo.config()?,
+        Err(_) => git2::Config::open_default()?,
     };
+    let workspace = Workspace::from_config(&config)?;
+    // The query's options come from where the search was run; each
+    // repository's own settings decide how its sidecars are built.
+    let (query, options, json) = search_options(request, &Settings::load(Path::new("."))?);
+    let date_field = options.filters.date_field;
+
+    // Loaded up front: a repository searched for the first time is indexed,
+    // which may download the model the engine is about to load.
+    let mut loaded = Vec::with_capacity(workspace.repos.len());
+    for repo in &workspace.repos {
+        let context = || format!("workspace repository {}", repo.path.display());
+        let settings = Settings::load(&repo.path).with_context(context)?;
+        let storage = IndexStorage::new(&repo.path).with_context(context)?;
+        let index = load_for_search(&repo.path, &storage, &settings).with_context(context)?;
+        loaded.push((settings, storage, index));
+    }
+
+    let model_manager = ModelManager::new()?;
+    let mut engine = SearchEngine::new(model_manager)?;
 
+    let mut outcomes = Vec::with_capacity(loaded.len());
+    let mut elapsed = Duration::ZERO;
+    for ((settings, storage, index), repo) in loaded.iter().zip(&workspace.repos) {
+        let (lexical, graph) =
+            load_search_sidecars(storage, index, settings, options.mode, options.exact, json);
+        let started = Instant::now();
+        outcomes.push(engine.search(
+            index,
+            graph.as_ref(),
+            lexical.as_ref(),
+            &query,
+            // Revisions such as `--in main` are resolved in each repository.
+            SearchOptions {
+                repository: Some(repo.path.clone()),
+                ..options.clone()
+            },
+        )?);
+        elapsed += started.elapsed();
+    }
+
+    let merged = search::merge(outcomes, options.num_results);
+    let names: Vec<&str> = merged
+        .repos
+        .iter()
+        .map(|&repo| workspace.repos[repo].name.as_str())
+        .collect();
+    let provenance = Provenance {
+        names,
+        repositories: workspace.repos.len(),
+    };
+    print_outcome(
+        &query,
+        &merged.outcome,
+        Some(&provenance),
+        date_field,
+        json,
+        elapsed,
+    )
+}
+
+/// The BM25 index and ANN graph a search of `index` will use, loaded from
+/// their sidecars or built.
+fn load_search_sidecars(
+    storage: &IndexStorage,
+    index: &SemanticIndex,
+    settings: &Settings,
+    mode: RetrievalMode,
+    exact: bool,
+    json: bool,
+) -> (Option<Bm25Index>, Option<HnswIndex>) {
     // BM25 is cheap to build and independent of repository size, so it is
     // loaded whenever lexical retrieval is in play.
     let lexical = if mode.uses_lexical() && !index.entries.is_empty() {
         let build_started = Instant::now();
-        let (lexical, rebuilt) = storage.load_or_build_lexical(&index, Bm25Params::default());
+        let (lexical, rebuilt) = storage.load_or_build_lexical(index, settings.bm25_params());
         if rebuilt && !json {
             println!(
                 "🔧 Built keyword index for {} commits in {:.1}s (cached for next time)\n",
@@ -312,7 +955,7 @@ pub fn search(repo_path: &str, request: SearchRequest) -> Result<()> {
         None
     } else {
         let build_started = Instant::now();
-        let (graph, rebuilt) = storage.load_or_build_ann(&index, HnswParams::default());
+        let (graph, rebuilt) = storage.load_or_build_ann(index, settings.hnsw_params());
         if rebuilt && !json {
             println!(
                 "🔧 Built search graph for {} commits in {:.1}s (cached for next time)\n",
@@ -323,33 +966,34 @@ pub fn search(repo_path: &str, request: SearchRequest) -> Result<()> {
         Some(graph)
     };
 
-    let model_manager = ModelManager::new()?;
-    let mut engine = SearchEngine::new(model_manager)?;
+    (lexical, graph)
+}
 
-    let started = Instant::now();
-    let outcome = engine.search(
-        &index,
-        graph.as_ref(),
-        lexical.as_ref(),
-        query,
-        SearchOptions {
-            num_results,
-            filters,
-            exact,
-            ef,
-            mode,
-            diversity,
-        },
-    )?;
-    let elapsed = started.elapsed();
+/// Which repository each result of a workspace search came from.
+struct Provenance<'a> {
+    /// One per result, in result order.
+    names: Vec<&'a str>,
+    repositories: usize,
+}
 
+fn print_outcome(
+    query: &str,
+    outcome: &SearchOutcome,
+    provenance: Option<&Provenance>,
+    date_field: DateField,
+    json: bool,
+    elapsed: Duration,
+) -> Result<()> {
     if json {
-        let document = JsonOutput::new(
+        let mut document = JsonOutput::new(
             query,
-            &outcome,
+            outcome,
             outcome.diversified,
             elapsed.as_secs_f64() * 1000.0,
         );
+        if let Some(provenance) = provenance {
+            document = document.with_repos(provenance.names.iter().copied());
+        }
         println!("{}", serde_json::to_string_pretty(&document)?);
         return Ok(());
     }
@@ -361,7 +1005,7 @@ pub fn search(repo_path: &str, request: SearchRequest) -> Result<()> {
 
     println!("🎯 Most Relevant Commits for: \"{}\"\n", query);
 
-    for result in &outcome.results {
+    for (position, result) in outcome.results.iter().enumerate() {
         // A fused or lexical hit has no cosine to report; showing a BM25 score
         // in a column labelled "similarity" would be a lie.
         let score = if result.similarity.is_nan() {
@@ -369,29 +1013,56 @@ pub fn search(repo_path: &str, request: SearchRequest) -> Result<()> {
         } else {
             format!("{:.2} similarity", result.similarity)
         };
+        let repo = provenance
+            .and_then(|provenance| provenance.names.get(position))
+            .map(|name| format!("[{name}] "))
+            .unwrap_or_default();
         println!(
-            "{}. {} - {} ({})",
+            "{}. {}{}{} - {} ({})",
             result.rank,
+            repo,
             &result.commit.hash[..7],
+            result
+                .commit
+                .tag
+                .as_ref()
+                .map(|tag| format!(" 🏷  {tag}"))
+                .unwrap_or_default(),
             result.commit.message.lines().next().unwrap_or(""),
             score
         );
-        println!(
-            "   Author: {}, {}",
-            result.commit.author, result.commit.date
-        );
-
-        if !result.commit.diff_summary.is_empty() {
-            let preview: String = result
-                .commit
-                .diff_summary
-                .lines()
-                .take(2)
-                .collect::<Vec<_>>()
-                .join("\n   ");
-            if !preview.is_empty() {
-                println!("   {}", preview);
-            }
+        let co_authors: Vec<&str> = result
+            .commit
+            .co_authors
+            .iter()
+            .map(|person| person.name.as_str())
+            .filter(|name| !name.is_empty())
+            .collect();
+        if co_authors.is_empty() {
+            println!(
+                "   Author: {}, {}",
+                result.commit.author_name(),
+                result.commit.local_date()
+            );
+        } else {
+            println!(
+                "   Author: {} with {}, {}",
+                result.commit.author_name(),
+                co_authors.join(", "),
+                result.commit.local_date()
+            );
+        }
+        print_committed(&result.commit, date_field);
+
+        let files = result.commit.files_line();
+        let preview: String = std::iter::once(files.as_str())
+            .filter(|line| !line.is_empty())
+            .chain(result.commit.diff_summary.lines())
+            .take(2)
+            .collect::<Vec<_>>()
+            .join("\n   ");
+        if !preview.is_empty() {
+            println!("   {}", preview);
         }
 
         println!();
@@ -407,9 +1078,13 @@ pub fn search(repo_path: &str, request: SearchRequest) -> Result<()> {
     } else {
         ""
     };
+    let across = provenance
+        .map(|provenance| format!(" in {} repositories", provenance.repositories))
+        .unwrap_or_default();
     println!(
-        "Searched {} commits via {} {}{} in {:.0}ms",
+        "Searched {} commits{} via {} {}{} in {:.0}ms",
         outcome.candidate_count,
+        across,
         outcome.mode.as_str(),
         how,
         diversified,
@@ -419,6 +1094,22 @@ pub fn search(repo_path: &str, request: SearchRequest) -> Result<()> {
     Ok(())
 }
 
+/// Re-resolve every stored identity through the repository's current
+/// `.mailmap`, so edits to it apply to history that is already indexed.
+///
+/// Best effort: a mailmap that cannot be read leaves the identities recorded
+/// at index time in place, which is never worse than before.
+fn apply_mailmap(path: &Path, index: &mut SemanticIndex) {
+    match IdentityMap::discover(path) {
+        Ok(identities) => {
+            for entry in &mut index.entries {
+                identities.canonicalize(&mut entry.commit);
+            }
+        }
+        Err(err) => info!("Keeping indexed identities: {err}"),
+    }
+}
+
 /// Index a repository the first time it is searched.
 ///
 /// Onboarding used to be three commands, and the first one a new user ran was
@@ -427,14 +1118,75 @@ pub fn search(repo_path: &str, request: SearchRequest) -> Result<()> {
 /// sequence needed a human: the model location is fixed and the index mode has
 /// a default. So `search` does it.
 ///
-/// Full mode, matching `git-semantic index`. Quick mode is faster but its index
-/// is not a superset, so bootstrapping into it would quietly commit the user to
-/// the weaker option and a full re-embed to leave it.
-fn bootstrap(path: &Path, storage: &IndexStorage) -> Result<SemanticIndex> {
+/// Full mode unless `semantic.indexMode` says quick, matching `git-semantic
+/// index`. Quick mode is faster but its index is not a superset, so
+/// bootstrapping into it unasked would quietly commit the user to the weaker
+/// option and a full re-embed to leave it.
+fn bootstrap(path: &Path, storage: &IndexStorage, settings: &Settings) -> Result<SemanticIndex> {
+    let _lock = lock_index(storage, Progress::Stderr)?;
+    // Another worktree may have built it while this one waited.
+    match storage.load() {
+        Ok(index) => return Ok(index),
+        Err(IndexError::IndexNotFound) => {}
+        Err(err) => return Err(err.into()),
+    }
+
     eprintln!("No index for this repository yet — building one (one-time).");
-    eprintln!("For a faster, message-only index instead: git-semantic index --quick\n");
+    let include_diffs = settings.include_diffs.value;
+    if include_diffs {
+        eprintln!("For a faster, message-only index instead: git-semantic index --quick\n");
+    }
+
+    full_index(
+        path,
+        storage,
+        include_diffs,
+        settings,
+        None,
+        Progress::Stderr,
+    )
+}
+
+/// Shrink the machine-wide embedding cache to `max_size`, or to the cap in
+/// the user's git config.
+pub fn cache_prune(max_size: Option<&str>) -> Result<()> {
+    let max_bytes = match max_size {
+        Some(raw) => cache::parse_size(raw)?,
+        None => cache::configured_size(&git2::Config::open_default()?),
+    };
+    let cache = EmbeddingCache::in_data_dir(max_bytes)?;
+    let report = cache.prune()?;
+
+    let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    println!(
+        "🧹 Removed {} cached embeddings ({:.1} MB)",
+        report.removed,
+        mb(report.freed_bytes)
+    );
+    println!(
+        "{} kept ({:.1} MB of a {:.1} MB cap)",
+        report.kept,
+        mb(report.kept_bytes),
+        mb(cache.max_bytes())
+    );
 
-    full_index(path, storage, true, Progress::Stderr)
+    Ok(())
+}
+
+/// Print every tuning knob's effective value and where it was set.
+pub fn config(repo_path: &str) -> Result<()> {
+    let settings = Settings::load(Path::new(repo_path))?;
+    let entries = settings.entries();
+    let width = entries.iter().map(|(key, ..)| key.len()).max().unwrap_or(0);
+
+    println!(
+        "⚙️  Effective settings (flag > local git config > .git-semantic.toml > global git config)\n"
+    );
+    for (key, value, source) in entries {
+        println!("  {key:<width$}  {value:<8}  {}", source.as_str());
+    }
+
+    Ok(())
 }
 
 pub fn stats(repo_path: &str) -> Result<()> {
@@ -445,9 +1197,19 @@ pub fn stats(repo_path: &str) -> Result<()> {
 
     println!("📊 Index Statistics\n");
     println!("Repository: {}", path.display());
-    println!("Total commits indexed: {}", index.entries.len());
+    let tags = index.entries.iter().filter(|e| e.commit.is_tag()).count();
+    println!("Total commits indexed: {}", index.entries.len() - tags);
+    if tags > 0 {
+        println!("Annotated tags indexed: {tags}");
+    }
     println!("Model version: {}", index.model_version);
-    println!("Last indexed commit: {}", index.last_commit);
+    // Worktrees share the index, each with its own HEAD.
+    println!(
+        "Last indexed commit: {}",
+        storage
+            .worktree_head()
+            .unwrap_or_else(|| index.last_commit.clone())
+    );
     println!(
         "Index mode: {}",
         if index.metadata.include_diffs {
@@ -457,6 +1219,14 @@ pub fn stats(repo_path: &str) -> Result<()> {
         }
     );
     println!("Index size: ~{:.2} MB", storage.index_size_mb()?);
+    if index.metadata.include_diffs {
+        let (secrets, commits) = redaction_totals(&index);
+        println!("Secrets redacted: {secrets} (in {commits} commits)");
+    }
+    let non_utf8 = index.entries.iter().filter(|e| e.commit.non_utf8).count();
+    if non_utf8 > 0 {
+        println!("Non-UTF-8 text decoded: {non_utf8} commits");
+    }
     println!(
         "Keyword index: {}",
         if storage.lexical_path().exists() {
@@ -487,6 +1257,16 @@ pub fn stats(repo_path: &str) -> Result<()> {
     Ok(())
 }
 
+/// Secrets replaced in stored diffs, and how many commits they came from.
+fn redaction_totals(index: &SemanticIndex) -> (usize, usize) {
+    index
+        .entries
+        .iter()
+        .map(|entry| entry.commit.redactions)
+        .filter(|&n| n > 0)
+        .fold((0, 0), |(secrets, commits), n| (secrets + n, commits + 1))
+}
+
 fn make_progress_bar(total: u64) -> ProgressBar {
     let pb = ProgressBar::new(total);
     pb.set_style(
@@ -527,9 +1307,14 @@ fn print_index_stats(
 /// Skipped for small repositories, which never consult the graph. A failure here
 /// is reported, not propagated — the index itself saved fine and search falls
 /// back to an exhaustive scan.
-fn refresh_search_graph(index: &SemanticIndex, storage: &IndexStorage, progress: Progress) {
+fn refresh_search_graph(
+    index: &SemanticIndex,
+    storage: &IndexStorage,
+    settings: &Settings,
+    progress: Progress,
+) {
     if !index.entries.is_empty()
-        && let Err(err) = storage.refresh_lexical(index, Bm25Params::default())
+        && let Err(err) = storage.refresh_lexical(index, settings.bm25_params())
     {
         info!("keyword index will be rebuilt on first search: {err}");
     }
@@ -544,7 +1329,7 @@ fn refresh_search_graph(index: &SemanticIndex, storage: &IndexStorage, progress:
         index.entries.len()
     ));
 
-    match storage.refresh_ann(index, HnswParams::default()) {
+    match storage.refresh_ann(index, settings.hnsw_params()) {
         Ok(()) => progress.say(&format!(
             "  done in {:.1}s",
             started.elapsed().as_secs_f64()
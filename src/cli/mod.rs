@@ -1,33 +1,89 @@
 pub mod commands;
 pub mod output;
 
+pub use crate::git::DateField;
 pub use crate::search::RetrievalMode;
 pub use output::{JsonOutput, JsonResult};
 
 /// Everything one `search` invocation needs.
 ///
 /// Bundled rather than passed positionally — the flag list has grown past the
-/// point where eight bare parameters are readable at the call site.
+/// point where eight bare parameters are readable at the call site. A `None`
+/// falls back to the configured [`Settings`](crate::config::Settings).
 #[derive(Debug)]
 pub struct SearchRequest {
     pub query: String,
-    pub num_results: usize,
+    pub num_results: Option<usize>,
     pub filters: SearchFilters,
     /// Score every commit instead of using the approximate graph.
     pub exact: bool,
     /// Override the graph's candidate-list width.
     pub ef: Option<usize>,
-    pub mode: RetrievalMode,
+    pub mode: Option<RetrievalMode>,
+    /// Rerank the results for diversity with MMR.
+    pub diverse: bool,
     /// MMR lambda when diversification is on.
-    pub diversity: Option<f32>,
+    pub lambda: Option<f32>,
     /// Emit JSON instead of formatted text.
     pub json: bool,
 }
 
-#[derive(Debug)]
+/// Metadata filters for one search.
+///
+/// Several values of one filter are alternatives, any of which passes; the
+/// `exclude_*` lists drop every commit one of their values matches.
+#[derive(Debug, Clone, Default)]
 pub struct SearchFilters {
-    pub author: Option<String>,
+    /// Match the author or any co-author by name.
+    pub authors: Vec<String>,
+    pub exclude_authors: Vec<String>,
+    /// Match the author or any co-author by email.
+    pub emails: Vec<String>,
+    pub exclude_emails: Vec<String>,
+    /// Match whoever applied the commit, by name or email.
+    pub committers: Vec<String>,
+    pub exclude_committers: Vec<String>,
     pub after: Option<String>,
     pub before: Option<String>,
-    pub file: Option<String>,
+    /// Which timestamp `after` and `before` bound.
+    pub date_field: DateField,
+    /// Substrings of a changed path, or globs once they use glob syntax.
+    pub files: Vec<String>,
+    /// Paths to leave out: a commit passes only if it changed some file
+    /// matching none of them, as with git's `:(exclude)` pathspecs.
+    pub exclude_files: Vec<String>,
+    /// Regexes over the whole message, matched ignoring case.
+    pub messages: Vec<String>,
+    pub exclude_messages: Vec<String>,
+    /// Only commits that added a file — one matching `files`, when given.
+    pub added: bool,
+    /// Only commits that deleted a file — one matching `files`, when given.
+    pub deleted: bool,
+    /// Only commits changing at least this many lines, counted over the files
+    /// matching `files` when given.
+    pub min_lines: Option<usize>,
+    /// Only commits whose diff changed a function or type with this name.
+    pub symbol: Option<String>,
+    /// `key` or `key=value` trailers the commit must all carry. Keys match
+    /// case-insensitively, values as case-insensitive substrings.
+    pub trailer: Vec<String>,
+    /// Conventional Commits types, any of which passes.
+    pub types: Vec<String>,
+    /// Conventional Commits types to leave out; commits without a type pass.
+    pub exclude_types: Vec<String>,
+    /// Conventional Commits scope, matched exactly but ignoring case.
+    pub scope: Option<String>,
+    /// Only commits flagged as breaking changes.
+    pub breaking: bool,
+    /// An issue reference the message must contain, in any spelling
+    /// [`crate::git::issues::normalize`] understands.
+    pub issue: Option<String>,
+    /// A label the commit's imported pull request must carry, ignoring case.
+    pub label: Option<String>,
+    /// The imported pull request number, as `123`, `#123` or `!123`.
+    pub pr: Option<String>,
+    /// Only commits these revisions reach, combined the way `git log`
+    /// combines its arguments: refs, `A..B` and `A...B` ranges, and `^A` to
+    /// leave out what A reaches. See [`crate::git::reachable`].
+    pub revisions: Vec<String>,
 }
@@ -7,29 +7,121 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::git::{DateField, FileChange, Identity, Note, PullRequest, Trailer};
 use crate::search::{SearchOutcome, SearchStrategy};
 
 /// A single ranked commit.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonResult {
     pub rank: usize,
+    /// The workspace repository the commit belongs to, for `--workspace`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
     /// Full 40-character hash — the human view truncates, machines should not.
     pub hash: String,
+    /// Set when the hit is an annotated tag; `hash` is then the commit it
+    /// points at, and the people and message fields are the tag's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
     pub author: String,
-    /// RFC 3339, so it parses everywhere without a format string.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub author_email: String,
+    /// The author as `.mailmap` names them, when that differs from the raw
+    /// `author` and `author_email`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_author: Option<Identity>,
+    /// RFC 3339, so it parses everywhere without a format string, with the
+    /// author's own UTC offset.
     pub date: String,
+    /// Who applied the commit, when the index recorded it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub committer: Option<JsonCommitter>,
+    /// People credited with `Co-authored-by:` trailers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub co_authors: Vec<Identity>,
     /// First line of the commit message.
     pub subject: String,
     /// Full commit message, trailing whitespace trimmed.
     pub message: String,
+    /// Conventional Commits type, when the subject follows the convention.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub commit_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Omitted unless the commit is marked as a breaking change.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub breaking: bool,
+    /// `git notes` from the configured notes refs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<Note>,
+    /// The pull request the commit landed through, once imported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_request: Option<PullRequest>,
+    /// Issue and ticket references in the message, normalized.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<String>,
+    /// `Key: value` trailers from the end of the message, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trailers: Vec<Trailer>,
     /// Paths the commit touched, when the index recorded them.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub files: Option<Vec<String>>,
+    /// What happened to each file, when the index recorded it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<JsonChange>,
+    /// Functions and types the diff changed, when the index recorded them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<String>,
     /// Cosine similarity, omitted when the ranking did not come from embeddings.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub similarity: Option<f32>,
 }
 
+/// The person who applied a commit, and when.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonCommitter {
+    pub name: String,
+    pub email: String,
+    /// RFC 3339.
+    pub date: String,
+}
+
+/// One file's change within a result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonChange {
+    pub path: String,
+    /// The path before a rename, or the source of a copy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// `added`, `modified`, `deleted`, `renamed`, `copied`, or `unknown` for
+    /// an index migrated from before statuses were recorded.
+    pub status: String,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl From<&FileChange> for JsonChange {
+    fn from(change: &FileChange) -> Self {
+        Self {
+            path: change.path.clone(),
+            old_path: change.old_path.clone(),
+            status: change.status.as_str().to_string(),
+            insertions: change.insertions,
+            deletions: change.deletions,
+        }
+    }
+}
+
+/// Every commit referencing one issue, for `git-semantic issue` without a
+/// query. With one, the command answers in [`JsonOutput`] instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonIssueMentions {
+    /// The reference as normalized for matching.
+    pub issue: String,
+    /// Newest first.
+    pub results: Vec<JsonResult>,
+}
+
 /// One query's full response.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonOutput {
@@ -58,6 +150,14 @@ impl JsonOutput {
             results: outcome.results.iter().map(JsonResult::from).collect(),
         }
     }
+
+    /// Tag each result, in order, with the workspace repository it came from.
+    pub fn with_repos<'a>(mut self, repos: impl IntoIterator<Item = &'a str>) -> Self {
+        for (result, repo) in self.results.iter_mut().zip(repos) {
+            result.repo = Some(repo.to_string());
+        }
+        self
+    }
 }
 
 impl From<&crate::search::SearchResult> for JsonResult {
@@ -66,14 +166,40 @@ impl From<&crate::search::SearchResult> for JsonResult {
 
         Self {
             rank: result.rank,
+            repo: None,
             hash: commit.hash.clone(),
+            tag: commit.tag.clone(),
             author: commit.author.clone(),
-            date: commit.date.to_rfc3339(),
+            author_email: commit.author_email.clone(),
+            canonical_author: Some(&commit.canonical_author)
+                .filter(|canonical| {
+                    !canonical.name.is_empty()
+                        && (canonical.name != commit.author
+                            || canonical.email != commit.author_email)
+                })
+                .cloned(),
+            date: commit.local_date().to_rfc3339(),
+            // An index migrated from 1.5.0 never recorded a committer.
+            committer: (!commit.committer.is_empty()).then(|| JsonCommitter {
+                name: commit.committer.clone(),
+                email: commit.committer_email.clone(),
+                date: commit.local_date_for(DateField::Committer).to_rfc3339(),
+            }),
+            co_authors: commit.co_authors.clone(),
             subject: commit.message.lines().next().unwrap_or("").to_string(),
             message: commit.message.trim_end().to_string(),
+            commit_type: commit.conventional.as_ref().map(|c| c.kind.clone()),
+            scope: commit.conventional.as_ref().and_then(|c| c.scope.clone()),
+            breaking: commit.conventional.as_ref().is_some_and(|c| c.breaking),
+            notes: commit.notes.clone(),
+            pull_request: commit.pull_request.clone(),
+            issues: commit.issues.clone(),
+            trailers: commit.trailers.clone(),
             files: commit
                 .changed_files()
                 .map(|paths| paths.into_iter().map(str::to_string).collect()),
+            changes: commit.files.iter().map(JsonChange::from).collect(),
+            symbols: commit.symbols.clone(),
             // NaN marks "no embedding produced this ranking"; JSON has no NaN,
             // so the field is omitted rather than emitted as null or 0.
             similarity: result.similarity.is_finite().then_some(result.similarity),
@@ -92,11 +218,11 @@ fn strategy_name(strategy: SearchStrategy) -> &'static str {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::git::CommitInfo;
+    use crate::git::{ChangeStatus, CommitInfo, ConventionalCommit};
     use crate::search::{RetrievalMode, SearchResult};
 
     fn commit(hash: &str, message: &str, diff_summary: &str) -> CommitInfo {
-        CommitInfo {
+        let mut commit = CommitInfo {
             hash: hash.to_string(),
             author: "Alice Chen".to_string(),
             date: chrono::DateTime::parse_from_rfc3339("2024-06-15T12:00:00Z")
@@ -104,7 +230,10 @@ mod tests {
                 .with_timezone(&chrono::Utc),
             message: message.to_string(),
             diff_summary: diff_summary.to_string(),
-        }
+            ..Default::default()
+        };
+        commit.lift_files_line();
+        commit
     }
 
     fn result(similarity: f32) -> SearchResult {
@@ -135,6 +264,17 @@ mod tests {
         assert_eq!(json.results[0].hash.len(), 40);
     }
 
+    #[test]
+    fn the_repo_field_appears_only_for_a_workspace_search() {
+        let single = JsonOutput::new("race", &outcome(vec![result(0.83)]), false, 1.5);
+        let value = serde_json::to_value(&single).unwrap();
+        assert!(value["results"][0].get("repo").is_none());
+
+        let workspace = single.with_repos(["api"]);
+        let value = serde_json::to_value(&workspace).unwrap();
+        assert_eq!(value["results"][0]["repo"], "api");
+    }
+
     #[test]
     fn splits_subject_from_full_message() {
         let json = JsonOutput::new("race", &outcome(vec![result(0.83)]), false, 1.5);
@@ -156,12 +296,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dates_keep_the_offset_they_were_recorded_with() {
+        let mut r = result(0.83);
+        r.commit.date_offset = -4 * 60;
+        r.commit.committer = "Bob".to_string();
+        r.commit.committer_date = r.commit.date;
+        r.commit.committer_date_offset = 9 * 60;
+
+        let json = JsonOutput::new("race", &outcome(vec![r]), false, 1.5);
+        assert_eq!(json.results[0].date, "2024-06-15T08:00:00-04:00");
+        assert_eq!(
+            json.results[0].committer.as_ref().unwrap().date,
+            "2024-06-15T21:00:00+09:00"
+        );
+    }
+
     #[test]
     fn changed_files_are_exposed_as_a_list() {
         let json = JsonOutput::new("race", &outcome(vec![result(0.83)]), false, 1.5);
         assert_eq!(
             json.results[0].files,
-            Some(vec!["src/auth.rs".to_string(), "Cargo.toml".to_string()])
+            Some(vec!["Cargo.toml".to_string(), "src/auth.rs".to_string()])
         );
     }
 
@@ -169,6 +325,7 @@ mod tests {
     fn files_is_omitted_for_a_legacy_index() {
         let mut r = result(0.83);
         r.commit.diff_summary = "+no paths recorded".to_string();
+        r.commit.files.clear();
         let json = JsonOutput::new("race", &outcome(vec![r]), false, 1.5);
 
         assert!(json.results[0].files.is_none());
@@ -179,6 +336,239 @@ mod tests {
         );
     }
 
+    #[test]
+    fn changes_carry_status_and_line_counts() {
+        let mut r = result(0.83);
+        r.commit.files = vec![
+            FileChange {
+                insertions: 12,
+                deletions: 3,
+                ..FileChange::new("src/auth.rs", ChangeStatus::Modified)
+            },
+            FileChange {
+                old_path: Some("src/login.rs".to_string()),
+                ..FileChange::new("src/session.rs", ChangeStatus::Renamed)
+            },
+        ];
+        let json = JsonOutput::new("race", &outcome(vec![r]), false, 1.5);
+        let value = serde_json::to_value(&json).unwrap();
+
+        assert_eq!(
+            value["results"][0]["changes"],
+            serde_json::json!([
+                {"path": "src/auth.rs", "status": "modified", "insertions": 12, "deletions": 3},
+                {
+                    "path": "src/session.rs",
+                    "old_path": "src/login.rs",
+                    "status": "renamed",
+                    "insertions": 0,
+                    "deletions": 0
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn changes_is_omitted_when_nothing_was_recorded() {
+        let mut r = result(0.83);
+        r.commit.files.clear();
+        let json = JsonOutput::new("race", &outcome(vec![r]), false, 1.5);
+        assert!(
+            !serde_json::to_string(&json).unwrap().contains("changes"),
+            "no recorded files, no field"
+        );
+    }
+
+    #[test]
+    fn symbols_are_listed_and_omitted_when_empty() {
+        let json = JsonOutput::new("race", &outcome(vec![result(0.83)]), false, 1.5);
+        assert!(!serde_json::to_string(&json).unwrap().contains("symbols"));
+
+        let mut r = result(0.83);
+        r.commit.symbols = vec!["encode_text".to_string()];
+        let json = JsonOutput::new("race", &outcome(vec![r]), false, 1.5);
+        assert_eq!(json.results[0].symbols, vec!["encode_text"]);
+    }
+
+    #[test]
+    fn people_fields_are_emitted_when_recorded() {
+        let mut r = result(0.83);
+        r.commit.author_email = "alice@example.com".to_string();
+        r.commit.committer = "GitHub".to_string();
+        r.commit.committer_email = "noreply@github.com".to_string();
+        r.commit.committer_date = r.commit.date;
+        r.commit.co_authors = vec![Identity {
+            name: "Bob".to_string(),
+            email: "bob@example.com".to_string(),
+        }];
+        let value =
+            serde_json::to_value(JsonOutput::new("q", &outcome(vec![r]), false, 1.0)).unwrap();
+        let hit = &value["results"][0];
+
+        assert_eq!(hit["author_email"], "alice@example.com");
+        assert_eq!(
+            hit["committer"],
+            serde_json::json!({
+                "name": "GitHub",
+                "email": "noreply@github.com",
+                "date": "2024-06-15T12:00:00+00:00"
+            })
+        );
+        assert_eq!(
+            hit["co_authors"],
+            serde_json::json!([{"name": "Bob", "email": "bob@example.com"}])
+        );
+    }
+
+    #[test]
+    fn canonical_author_is_emitted_only_when_the_mailmap_changed_it() {
+        let mut r = result(0.83);
+        r.commit.author_email = "alice@old.example".to_string();
+        r.commit.canonical_author = Identity {
+            name: "Alice Chen".to_string(),
+            email: "alice@old.example".to_string(),
+        };
+        let json = JsonOutput::new("q", &outcome(vec![r.clone()]), false, 1.0);
+        assert_eq!(json.results[0].canonical_author, None);
+
+        r.commit.canonical_author.email = "alice@example.com".to_string();
+        let json = JsonOutput::new("q", &outcome(vec![r]), false, 1.0);
+        assert_eq!(
+            json.results[0]
+                .canonical_author
+                .as_ref()
+                .map(|c| c.email.as_str()),
+            Some("alice@example.com")
+        );
+    }
+
+    #[test]
+    fn conventional_fields_are_emitted_when_parsed() {
+        let text = serde_json::to_string(&JsonOutput::new(
+            "q",
+            &outcome(vec![result(0.83)]),
+            false,
+            1.0,
+        ))
+        .unwrap();
+        assert!(!text.contains("\"breaking\""), "{text}");
+
+        let mut r = result(0.83);
+        r.commit.conventional = Some(ConventionalCommit {
+            kind: "feat".to_string(),
+            scope: Some("api".to_string()),
+            breaking: true,
+        });
+        let value =
+            serde_json::to_value(JsonOutput::new("q", &outcome(vec![r]), false, 1.0)).unwrap();
+        let hit = &value["results"][0];
+        assert_eq!(hit["type"], "feat");
+        assert_eq!(hit["scope"], "api");
+        assert_eq!(hit["breaking"], true);
+    }
+
+    #[test]
+    fn tags_and_notes_are_emitted_when_present() {
+        let text = serde_json::to_string(&JsonOutput::new(
+            "q",
+            &outcome(vec![result(0.83)]),
+            false,
+            1.0,
+        ))
+        .unwrap();
+        assert!(
+            !text.contains("\"tag\"") && !text.contains("notes"),
+            "{text}"
+        );
+
+        let mut r = result(0.83);
+        r.commit.tag = Some("v1.2.0".to_string());
+        r.commit.notes = vec![Note {
+            notes_ref: "refs/notes/review".to_string(),
+            message: "LGTM".to_string(),
+        }];
+        let value =
+            serde_json::to_value(JsonOutput::new("q", &outcome(vec![r]), false, 1.0)).unwrap();
+        let hit = &value["results"][0];
+        assert_eq!(hit["tag"], "v1.2.0");
+        assert_eq!(
+            hit["notes"],
+            serde_json::json!([{"notes_ref": "refs/notes/review", "message": "LGTM"}])
+        );
+    }
+
+    #[test]
+    fn an_imported_pull_request_is_emitted() {
+        let json = JsonOutput::new("q", &outcome(vec![result(0.83)]), false, 1.0);
+        assert!(
+            !serde_json::to_string(&json)
+                .unwrap()
+                .contains("pull_request")
+        );
+
+        let mut r = result(0.83);
+        r.commit.pull_request = Some(PullRequest {
+            number: 12,
+            title: "Retry uploads".to_string(),
+            body: "Because S3 flakes.".to_string(),
+            labels: vec!["bug".to_string()],
+        });
+        let value =
+            serde_json::to_value(JsonOutput::new("q", &outcome(vec![r]), false, 1.0)).unwrap();
+        assert_eq!(
+            value["results"][0]["pull_request"],
+            serde_json::json!({
+                "number": 12,
+                "title": "Retry uploads",
+                "body": "Because S3 flakes.",
+                "labels": ["bug"]
+            })
+        );
+    }
+
+    #[test]
+    fn issues_are_listed_and_omitted_when_empty() {
+        let json = JsonOutput::new("q", &outcome(vec![result(0.83)]), false, 1.0);
+        assert!(!serde_json::to_string(&json).unwrap().contains("issues"));
+
+        let mut r = result(0.83);
+        r.commit.issues = vec!["#12".to_string(), "JIRA-456".to_string()];
+        let json = JsonOutput::new("q", &outcome(vec![r]), false, 1.0);
+        assert_eq!(json.results[0].issues, vec!["#12", "JIRA-456"]);
+    }
+
+    #[test]
+    fn trailers_are_listed_as_key_value_pairs() {
+        let json = JsonOutput::new("q", &outcome(vec![result(0.83)]), false, 1.0);
+        assert!(!serde_json::to_string(&json).unwrap().contains("trailers"));
+
+        let mut r = result(0.83);
+        r.commit.trailers = vec![Trailer {
+            key: "Fixes".to_string(),
+            value: "#123".to_string(),
+        }];
+        let value =
+            serde_json::to_value(JsonOutput::new("q", &outcome(vec![r]), false, 1.0)).unwrap();
+        assert_eq!(
+            value["results"][0]["trailers"],
+            serde_json::json!([{"key": "Fixes", "value": "#123"}])
+        );
+    }
+
+    #[test]
+    fn people_fields_are_omitted_for_a_legacy_commit() {
+        let json = JsonOutput::new("q", &outcome(vec![result(0.83)]), false, 1.0);
+        let text = serde_json::to_string(&json).unwrap();
+        for field in [
+            "author_email",
+            "canonical_author",
+            "committer",
+            "co_authors",
+        ] {
+            assert!(!text.contains(field), "{field} should be absent: {text}");
+        }
+    }
+
     #[test]
     fn similarity_is_omitted_for_a_keyword_only_hit() {
         let json = JsonOutput::new("race", &outcome(vec![result(f32::NAN)]), false, 1.5);
@@ -0,0 +1,599 @@
+//! Tuning knobs, read from git config and a checked-in `.git-semantic.toml`.
+//!
+//! Every knob has a built-in default and can be set in three places besides
+//! its command-line flag. From strongest to weakest:
+//!
+//! 1. the repository's own git config (`git config semantic.results 20`),
+//! 2. `.git-semantic.toml` at the root of the worktree, which a team can
+//!    commit so everyone searches the repository the same way,
+//! 3. the user's global git config, which covers every repository.
+//!
+//! A flag beats all of them. The file uses git's key names inside a
+//! `[semantic]` table:
+//!
+//! ```toml
+//! [semantic]
+//! results = 20
+//! mode = "lexical"
+//! maxDiffSize = "20k"
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use git2::{Config, ConfigLevel, Repository};
+use thiserror::Error;
+
+use crate::index::cache;
+use crate::search::{DEFAULT_LAMBDA, RetrievalMode};
+use crate::text::Bm25Params;
+use crate::vector::HnswParams;
+
+/// The checked-in settings file, at the root of the worktree.
+pub const CONFIG_FILE: &str = ".git-semantic.toml";
+
+/// Results a search returns: `-n`.
+pub const RESULTS_KEY: &str = "semantic.results";
+/// Retrievers a search consults: `--mode`.
+pub const MODE_KEY: &str = "semantic.mode";
+/// Diversity balance for `--diverse`: `--lambda`.
+pub const LAMBDA_KEY: &str = "semantic.lambda";
+/// `full` or `quick`, for an index built without `--full` or `--quick`.
+pub const INDEX_MODE_KEY: &str = "semantic.indexMode";
+/// Diff text kept per commit in full mode, with git's `k` and `m` suffixes.
+pub const MAX_DIFF_SIZE_KEY: &str = "semantic.maxDiffSize";
+/// Search graph out-degree.
+pub const HNSW_M_KEY: &str = "semantic.hnswM";
+/// Search graph candidate-list width while building.
+pub const HNSW_EF_CONSTRUCTION_KEY: &str = "semantic.hnswEfConstruction";
+/// Search graph candidate-list width while searching: `--ef`.
+pub const HNSW_EF_KEY: &str = "semantic.hnswEf";
+/// BM25 term-frequency saturation.
+pub const BM25_K1_KEY: &str = "semantic.bm25K1";
+/// BM25 document-length normalization.
+pub const BM25_B_KEY: &str = "semantic.bm25B";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("invalid value '{value}' for {key} in {origin} — expected {expected}")]
+    InvalidValue {
+        key: &'static str,
+        value: String,
+        origin: String,
+        expected: &'static str,
+    },
+
+    #[error("could not read {}: {reason}", path.display())]
+    InvalidFile { path: PathBuf, reason: String },
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+impl ConfigError {
+    /// Returns a user-facing hint for how to resolve this error.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::InvalidValue { .. } => {
+                Some("Fix or remove the setting; git-semantic config shows where each comes from")
+            }
+            Self::InvalidFile { .. } => {
+                Some("Settings go in a [semantic] table, e.g. results = 20 or mode = \"lexical\"")
+            }
+            Self::Git(_) => None,
+        }
+    }
+
+    /// Returns an error code for programmatic identification.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidValue { .. } => "E5001",
+            Self::InvalidFile { .. } => "E5002",
+            Self::Git(_) => "E5003",
+        }
+    }
+}
+
+/// Where a setting's effective value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    Global,
+    RepoFile,
+    Local,
+}
+
+impl Source {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Global => "global git config",
+            Self::RepoFile => CONFIG_FILE,
+            Self::Local => "local git config",
+        }
+    }
+}
+
+/// One knob's effective value and where it was set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    fn default(value: T) -> Self {
+        Self {
+            value,
+            source: Source::Default,
+        }
+    }
+}
+
+/// Every tuning knob, resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub results: Setting<usize>,
+    pub mode: Setting<RetrievalMode>,
+    pub lambda: Setting<f32>,
+    /// Full mode embeds diffs; quick mode only messages.
+    pub include_diffs: Setting<bool>,
+    pub max_diff_size: Setting<usize>,
+    pub hnsw_m: Setting<usize>,
+    pub hnsw_ef_construction: Setting<usize>,
+    pub hnsw_ef: Setting<usize>,
+    pub bm25_k1: Setting<f32>,
+    pub bm25_b: Setting<f32>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let hnsw = HnswParams::default();
+        let bm25 = Bm25Params::default();
+        Self {
+            results: Setting::default(10),
+            mode: Setting::default(RetrievalMode::default()),
+            lambda: Setting::default(DEFAULT_LAMBDA),
+            include_diffs: Setting::default(true),
+            max_diff_size: Setting::default(crate::git::MAX_DIFF_SIZE),
+            hnsw_m: Setting::default(hnsw.m),
+            hnsw_ef_construction: Setting::default(hnsw.ef_construction),
+            hnsw_ef: Setting::default(hnsw.ef_search),
+            bm25_k1: Setting::default(bm25.k1),
+            bm25_b: Setting::default(bm25.b),
+        }
+    }
+}
+
+impl Settings {
+    /// The settings for the repository at `path`, or the global ones when it
+    /// is not in a repository.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        match Repository::discover(path) {
+            Ok(repo) => Self::from_repo(&repo),
+            Err(_) => Self::global(),
+        }
+    }
+
+    /// `repo`'s git config, its worktree's settings file, and the global
+    /// config beneath them.
+    pub fn from_repo(repo: &Repository) -> Result<Self, ConfigError> {
+        let file = match repo.workdir() {
+            Some(workdir) => SettingsFile::read(&workdir.join(CONFIG_FILE))?,
+            None => None,
+        };
+        Self::resolve(&Layers::new(&repo.config()?, file))
+    }
+
+    /// The global git config alone.
+    pub fn global() -> Result<Self, ConfigError> {
+        Self::resolve(&Layers::new(&Config::open_default()?, None))
+    }
+
+    fn resolve(layers: &Layers) -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        Ok(Self {
+            results: layers.get(RESULTS_KEY, defaults.results, "a positive count", positive)?,
+            mode: layers.get(
+                MODE_KEY,
+                defaults.mode,
+                "hybrid, semantic or lexical",
+                |raw| match raw.to_ascii_lowercase().as_str() {
+                    "hybrid" => Some(RetrievalMode::Hybrid),
+                    "semantic" => Some(RetrievalMode::Semantic),
+                    "lexical" => Some(RetrievalMode::Lexical),
+                    _ => None,
+                },
+            )?,
+            lambda: layers.get(
+                LAMBDA_KEY,
+                defaults.lambda,
+                "a number from 0 to 1",
+                fraction,
+            )?,
+            include_diffs: layers.get(
+                INDEX_MODE_KEY,
+                defaults.include_diffs,
+                "full or quick",
+                |raw| match raw.to_ascii_lowercase().as_str() {
+                    "full" => Some(true),
+                    "quick" => Some(false),
+                    _ => None,
+                },
+            )?,
+            max_diff_size: layers.get(
+                MAX_DIFF_SIZE_KEY,
+                defaults.max_diff_size,
+                "a size in bytes, e.g. 20000 or 20k",
+                |raw| {
+                    cache::parse_size(raw)
+                        .ok()
+                        .and_then(|bytes| usize::try_from(bytes).ok())
+                        .filter(|&bytes| bytes > 0)
+                },
+            )?,
+            hnsw_m: layers.get(
+                HNSW_M_KEY,
+                defaults.hnsw_m,
+                "a count of at least 2",
+                |raw| raw.parse().ok().filter(|&m: &usize| m >= 2),
+            )?,
+            hnsw_ef_construction: layers.get(
+                HNSW_EF_CONSTRUCTION_KEY,
+                defaults.hnsw_ef_construction,
+                "a positive count",
+                positive,
+            )?,
+            hnsw_ef: layers.get(HNSW_EF_KEY, defaults.hnsw_ef, "a positive count", positive)?,
+            bm25_k1: layers.get(
+                BM25_K1_KEY,
+                defaults.bm25_k1,
+                "a non-negative number",
+                |raw| {
+                    raw.parse()
+                        .ok()
+                        .filter(|&k1: &f32| k1.is_finite() && k1 >= 0.0)
+                },
+            )?,
+            bm25_b: layers.get(
+                BM25_B_KEY,
+                defaults.bm25_b,
+                "a number from 0 to 1",
+                fraction,
+            )?,
+        })
+    }
+
+    /// Graph parameters, with the seed every build shares.
+    pub fn hnsw_params(&self) -> HnswParams {
+        HnswParams {
+            m: self.hnsw_m.value,
+            ef_construction: self.hnsw_ef_construction.value,
+            ef_search: self.hnsw_ef.value,
+            ..HnswParams::default()
+        }
+    }
+
+    pub fn bm25_params(&self) -> Bm25Params {
+        Bm25Params {
+            k1: self.bm25_k1.value,
+            b: self.bm25_b.value,
+        }
+    }
+
+    /// Each key, its effective value as it would be written, and its source,
+    /// for `git-semantic config`.
+    pub fn entries(&self) -> Vec<(&'static str, String, Source)> {
+        fn row<T: fmt::Display>(
+            key: &'static str,
+            setting: &Setting<T>,
+        ) -> (&'static str, String, Source) {
+            (key, setting.value.to_string(), setting.source)
+        }
+        vec![
+            row(RESULTS_KEY, &self.results),
+            (
+                MODE_KEY,
+                self.mode.value.as_str().to_string(),
+                self.mode.source,
+            ),
+            row(LAMBDA_KEY, &self.lambda),
+            (
+                INDEX_MODE_KEY,
+                if self.include_diffs.value {
+                    "full"
+                } else {
+                    "quick"
+                }
+                .to_string(),
+                self.include_diffs.source,
+            ),
+            row(MAX_DIFF_SIZE_KEY, &self.max_diff_size),
+            row(HNSW_M_KEY, &self.hnsw_m),
+            row(HNSW_EF_CONSTRUCTION_KEY, &self.hnsw_ef_construction),
+            row(HNSW_EF_KEY, &self.hnsw_ef),
+            row(BM25_K1_KEY, &self.bm25_k1),
+            row(BM25_B_KEY, &self.bm25_b),
+        ]
+    }
+}
+
+fn positive(raw: &str) -> Option<usize> {
+    raw.parse().ok().filter(|&n| n > 0)
+}
+
+fn fraction(raw: &str) -> Option<f32> {
+    raw.parse().ok().filter(|x: &f32| (0.0..=1.0).contains(x))
+}
+
+/// The `[semantic]` table of a `.git-semantic.toml`.
+struct SettingsFile {
+    path: PathBuf,
+    table: toml::Table,
+}
+
+impl SettingsFile {
+    /// `None` when there is no file.
+    fn read(path: &Path) -> Result<Option<Self>, ConfigError> {
+        let invalid = |reason: String| ConfigError::InvalidFile {
+            path: path.to_path_buf(),
+            reason,
+        };
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(invalid(err.to_string())),
+        };
+        let mut document: toml::Table = text
+            .parse()
+            .map_err(|err: toml::de::Error| invalid(err.message().to_string()))?;
+
+        let table = match document.remove("semantic") {
+            Some(toml::Value::Table(table)) => table,
+            Some(_) => return Err(invalid("`semantic` is not a table".to_string())),
+            None => toml::Table::new(),
+        };
+        for name in table.keys() {
+            let known = KEYS.iter().any(|key| {
+                key.strip_prefix("semantic.")
+                    .unwrap_or(key)
+                    .eq_ignore_ascii_case(name)
+            });
+            if !known {
+                tracing::debug!(
+                    "ignoring unknown setting semantic.{name} in {}",
+                    path.display()
+                );
+            }
+        }
+
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            table,
+        }))
+    }
+
+    /// `key`'s value as text, its name matched without regard to case as git
+    /// matches its own.
+    fn get(&self, key: &str) -> Option<String> {
+        let name = key.strip_prefix("semantic.").unwrap_or(key);
+        let (_, value) = self
+            .table
+            .iter()
+            .find(|(candidate, _)| candidate.eq_ignore_ascii_case(name))?;
+        Some(match value {
+            toml::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        })
+    }
+}
+
+const KEYS: [&str; 10] = [
+    RESULTS_KEY,
+    MODE_KEY,
+    LAMBDA_KEY,
+    INDEX_MODE_KEY,
+    MAX_DIFF_SIZE_KEY,
+    HNSW_M_KEY,
+    HNSW_EF_CONSTRUCTION_KEY,
+    HNSW_EF_KEY,
+    BM25_K1_KEY,
+    BM25_B_KEY,
+];
+
+/// Where raw values are looked up, strongest first.
+struct Layers {
+    local: Vec<Config>,
+    file: Option<SettingsFile>,
+    global: Vec<Config>,
+}
+
+impl Layers {
+    /// Split `config` at the settings file: levels belonging to the repository
+    /// above it, the user's and the machine's below.
+    fn new(config: &Config, file: Option<SettingsFile>) -> Self {
+        let levels = |levels: &[ConfigLevel]| {
+            levels
+                .iter()
+                .filter_map(|&level| config.open_level(level).ok())
+                .collect()
+        };
+        Self {
+            local: levels(&[ConfigLevel::App, ConfigLevel::Worktree, ConfigLevel::Local]),
+            file,
+            global: levels(&[
+                ConfigLevel::Global,
+                ConfigLevel::XDG,
+                ConfigLevel::System,
+                ConfigLevel::ProgramData,
+            ]),
+        }
+    }
+
+    /// `key` from the strongest layer that sets it, parsed, or `default`.
+    fn get<T>(
+        &self,
+        key: &'static str,
+        default: Setting<T>,
+        expected: &'static str,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> Result<Setting<T>, ConfigError> {
+        let Some((raw, source, origin)) = self.lookup(key) else {
+            return Ok(default);
+        };
+        match parse(raw.trim()) {
+            Some(value) => Ok(Setting { value, source }),
+            None => Err(ConfigError::InvalidValue {
+                key,
+                value: raw,
+                origin,
+                expected,
+            }),
+        }
+    }
+
+    fn lookup(&self, key: &str) -> Option<(String, Source, String)> {
+        let from_git = |configs: &[Config], source: Source| {
+            configs.iter().find_map(|config| {
+                config
+                    .get_string(key)
+                    .ok()
+                    .map(|value| (value, source, source.as_str().to_string()))
+            })
+        };
+        from_git(&self.local, Source::Local)
+            .or_else(|| {
+                self.file.as_ref().and_then(|file| {
+                    file.get(key)
+                        .map(|value| (value, Source::RepoFile, file.path.display().to_string()))
+                })
+            })
+            .or_else(|| from_git(&self.global, Source::Global))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A config with a local and a global level, each in its own file.
+    fn config(dir: &TempDir, local: &[(&str, &str)], global: &[(&str, &str)]) -> Config {
+        let mut config = Config::new().unwrap();
+        for (name, level, values) in [
+            ("global", ConfigLevel::Global, global),
+            ("local", ConfigLevel::Local, local),
+        ] {
+            let path = dir.path().join(name);
+            let mut file = Config::open(&path).unwrap();
+            for (key, value) in values {
+                file.set_str(key, value).unwrap();
+            }
+            config.add_file(&path, level, false).unwrap();
+        }
+        config
+    }
+
+    fn settings_file(dir: &TempDir, text: &str) -> Option<SettingsFile> {
+        let path = dir.path().join(CONFIG_FILE);
+        std::fs::write(&path, text).unwrap();
+        SettingsFile::read(&path).unwrap()
+    }
+
+    #[test]
+    fn nothing_set_is_the_defaults() {
+        let dir = TempDir::new().unwrap();
+        let settings = Settings::resolve(&Layers::new(&config(&dir, &[], &[]), None)).unwrap();
+        assert_eq!(settings, Settings::default());
+        assert_eq!(settings.hnsw_params(), HnswParams::default());
+        assert_eq!(settings.bm25_params(), Bm25Params::default());
+    }
+
+    #[test]
+    fn local_config_beats_the_file_which_beats_global_config() {
+        let dir = TempDir::new().unwrap();
+        let config = config(
+            &dir,
+            &[(RESULTS_KEY, "30")],
+            &[
+                (RESULTS_KEY, "5"),
+                (MODE_KEY, "semantic"),
+                (LAMBDA_KEY, "0.4"),
+            ],
+        );
+        let file = settings_file(
+            &dir,
+            "[semantic]\nresults = 20\nmode = \"lexical\"\nindexMode = \"quick\"\n",
+        );
+
+        let settings = Settings::resolve(&Layers::new(&config, file)).unwrap();
+        assert_eq!(settings.results.value, 30);
+        assert_eq!(settings.results.source, Source::Local);
+        assert_eq!(settings.mode.value, RetrievalMode::Lexical);
+        assert_eq!(settings.mode.source, Source::RepoFile);
+        assert!(!settings.include_diffs.value);
+        assert_eq!(settings.lambda.value, 0.4);
+        assert_eq!(settings.lambda.source, Source::Global);
+        assert_eq!(settings.hnsw_m.source, Source::Default);
+    }
+
+    #[test]
+    fn keys_match_without_regard_to_case() {
+        let dir = TempDir::new().unwrap();
+        let config = config(&dir, &[("semantic.hnswm", "24")], &[]);
+        let file = settings_file(&dir, "[semantic]\nMAXDIFFSIZE = \"20k\"\nbm25k1 = 1.5\n");
+
+        let settings = Settings::resolve(&Layers::new(&config, file)).unwrap();
+        assert_eq!(settings.hnsw_m.value, 24);
+        assert_eq!(settings.hnsw_params().m, 24);
+        assert_eq!(settings.max_diff_size.value, 20 * 1024);
+        assert_eq!(settings.bm25_params().k1, 1.5);
+    }
+
+    #[test]
+    fn an_invalid_value_names_its_key_and_where_it_was_set() {
+        let dir = TempDir::new().unwrap();
+        let config = config(&dir, &[], &[(MODE_KEY, "fuzzy")]);
+        let err = Settings::resolve(&Layers::new(&config, None)).unwrap_err();
+        assert!(matches!(
+            &err,
+            ConfigError::InvalidValue { key: MODE_KEY, value, origin, .. }
+                if value == "fuzzy" && origin == "global git config"
+        ));
+
+        let dir = TempDir::new().unwrap();
+        let file = settings_file(&dir, "[semantic]\nresults = 0\n");
+        let config = self::config(&dir, &[], &[]);
+        let err = Settings::resolve(&Layers::new(&config, file)).unwrap_err();
+        assert!(matches!(
+            &err,
+            ConfigError::InvalidValue { key: RESULTS_KEY, origin, .. }
+                if origin.ends_with(CONFIG_FILE)
+        ));
+    }
+
+    #[test]
+    fn an_unparseable_file_is_an_error_and_unknown_keys_are_not() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        std::fs::write(&path, "[semantic\nresults = 20").unwrap();
+        assert!(matches!(
+            SettingsFile::read(&path),
+            Err(ConfigError::InvalidFile { .. })
+        ));
+
+        let file = settings_file(&dir, "[semantic]\nfuture = true\n[other]\nresults = 3\n");
+        let settings = Settings::resolve(&Layers::new(&config(&dir, &[], &[]), file)).unwrap();
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn a_missing_file_is_no_file() {
+        let dir = TempDir::new().unwrap();
+        assert!(
+            SettingsFile::read(&dir.path().join(CONFIG_FILE))
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Conventional Commits subjects: `type(scope)!: description`.
+//!
+//! See <https://www.conventionalcommits.org/en/v1.0.0/>. Only the header is
+//! structural; the description is left in the message, where search already
+//! reads it.
+
+use super::ConventionalCommit;
+
+/// Footers that flag a breaking change. The spec allows either spelling.
+const BREAKING_FOOTERS: &[&str] = &["BREAKING CHANGE:", "BREAKING-CHANGE:"];
+
+/// The type, scope and breaking marker of `message`, or `None` when its
+/// subject does not follow the convention.
+///
+/// The type is lowercased — `Fix:` and `fix:` are the same kind of change.
+/// A commit is breaking when the header carries `!` or any line after the
+/// subject starts with a `BREAKING CHANGE:` footer.
+pub fn parse(message: &str) -> Option<ConventionalCommit> {
+    let mut lines = message.lines();
+    let subject = lines.next()?;
+    let (header, description) = subject.split_once(':')?;
+    if !description.starts_with(' ') || description.trim().is_empty() {
+        return None;
+    }
+
+    let (header, bang) = match header.strip_suffix('!') {
+        Some(rest) => (rest, true),
+        None => (header, false),
+    };
+    let (kind, scope) = match header.split_once('(') {
+        Some((kind, rest)) => {
+            let scope = rest.strip_suffix(')')?.trim();
+            if scope.is_empty() || scope.contains(['(', ')']) {
+                return None;
+            }
+            (kind, Some(scope.to_string()))
+        }
+        None => (header, None),
+    };
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+
+    let footer = lines.any(|line| {
+        BREAKING_FOOTERS
+            .iter()
+            .any(|prefix| line.starts_with(prefix))
+    });
+
+    Some(ConventionalCommit {
+        kind: kind.to_ascii_lowercase(),
+        scope,
+        breaking: bang || footer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conventional(kind: &str, scope: Option<&str>, breaking: bool) -> ConventionalCommit {
+        ConventionalCommit {
+            kind: kind.to_string(),
+            scope: scope.map(str::to_string),
+            breaking,
+        }
+    }
+
+    #[test]
+    fn type_and_scope_are_read_from_the_subject() {
+        assert_eq!(
+            parse("fix(auth): reject expired tokens"),
+            Some(conventional("fix", Some("auth"), false))
+        );
+        assert_eq!(
+            parse("Perf: faster scan\n\nBody."),
+            Some(conventional("perf", None, false))
+        );
+    }
+
+    #[test]
+    fn a_bang_or_a_footer_marks_a_breaking_change() {
+        assert_eq!(
+            parse("feat(api)!: drop v1 endpoints"),
+            Some(conventional("feat", Some("api"), true))
+        );
+        assert_eq!(
+            parse("refactor: new index layout\n\nBREAKING CHANGE: re-index required"),
+            Some(conventional("refactor", None, true))
+        );
+        assert_eq!(
+            parse("chore!: bump MSRV"),
+            Some(conventional("chore", None, true))
+        );
+    }
+
+    #[test]
+    fn other_subjects_are_not_conventional() {
+        for subject in [
+            "Merge branch 'main' into feature",
+            "Revert \"feat: add cache\"",
+            "update readme",
+            "fix:missing space",
+            "fix: ",
+            "fix(): empty scope",
+            "fix(auth: unclosed scope",
+            "big fix: words in the type",
+            "",
+        ] {
+            assert_eq!(parse(subject), None, "{subject:?}");
+        }
+    }
+}
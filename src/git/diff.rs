@@ -1,19 +1,38 @@
-use git2::{Commit, Diff, DiffOptions, Repository};
+use git2::{Commit, Delta, Diff, DiffFindOptions, DiffOptions, Patch, Repository};
 use std::collections::BTreeSet;
+use std::path::Path;
 
-use super::GitError;
+use super::encoding::decode_line;
+use super::{ChangeStatus, DiffPolicy, FileChange, GitError, IgnoreRules, Redactor, symbols};
 
-/// Total diff text kept per commit, in bytes.
-const MAX_DIFF_SIZE: usize = 10_000;
+/// Total diff text kept per commit, in bytes, unless `semantic.maxDiffSize`
+/// says otherwise.
+pub const MAX_DIFF_SIZE: usize = 10_000;
 
-/// Share of the budget reserved for the changed-file list, so a commit that
-/// touches hundreds of files still leaves room for actual diff content.
-const MAX_PATH_SECTION: usize = 2_000;
+const TRUNCATION_MARKER: &str = "\n... (truncated)";
+
+/// What one commit's diff contributes to its [`CommitInfo`](super::CommitInfo).
+#[derive(Debug, Default)]
+pub struct ExtractedDiff {
+    pub summary: String,
+    pub files: Vec<FileChange>,
+    pub symbols: Vec<String>,
+    pub redactions: usize,
+    /// Some added or removed line was not valid UTF-8.
+    pub non_utf8: bool,
+}
 
 pub struct DiffExtractor;
 
 impl DiffExtractor {
-    pub fn extract_diff(repo: &Repository, commit: &Commit) -> Result<String, GitError> {
+    pub fn extract_diff(
+        repo: &Repository,
+        commit: &Commit,
+        policy: &DiffPolicy,
+        ignore: &IgnoreRules,
+        redactor: &Redactor,
+        max_size: usize,
+    ) -> Result<ExtractedDiff, GitError> {
         let tree = commit.tree()?;
 
         let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
@@ -21,122 +40,298 @@ impl DiffExtractor {
         let mut diff_opts = DiffOptions::new();
         diff_opts.context_lines(0);
 
-        let diff =
+        let mut diff =
             repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_opts))?;
 
-        let paths = Self::changed_paths(&diff)?;
-        let body = Self::format_diff(&diff)?;
+        // Without similarity detection a moved file is a full delete plus a
+        // full add, which spends most of the budget restating content that did
+        // not change. Paired up, a pure rename costs no content lines at all.
+        let mut find_opts = DiffFindOptions::new();
+        find_opts.renames(true).copies(true);
+        diff.find_similar(Some(&mut find_opts))?;
+
+        let mut files = Vec::with_capacity(diff.deltas().len());
+        let mut sections = Vec::new();
+        let mut symbols = BTreeSet::new();
+        let mut redactions = 0;
+        let mut non_utf8 = false;
+
+        for position in 0..diff.deltas().len() {
+            // Binary files have no lines to count, and come back as `None`.
+            let patch = Patch::from_diff(&diff, position)?;
+            let Some(change) = Self::file_change(&diff, position, patch.as_ref())? else {
+                continue;
+            };
+            if ignore.ignores_path(&change.path) {
+                continue;
+            }
+
+            if let Some(mut patch) = patch
+                && !policy.excludes(repo, Path::new(&change.path))?
+            {
+                // Redacted before budgeting, so a secret can never survive by
+                // being cut in half at a section boundary.
+                let (text, lossy) = Self::format_patch(&mut patch)?;
+                non_utf8 |= lossy;
+                let (body, redacted) = redactor.redact(&text);
+                redactions += redacted;
+                if !body.is_empty() {
+                    sections.push(body);
+                }
+                symbols.extend(Self::hunk_symbols(&patch)?);
+            }
 
-        let mut summary = String::with_capacity(paths.len() + body.len() + 1);
-        summary.push_str(&paths);
-        if !paths.is_empty() && !body.is_empty() {
-            summary.push('\n');
+            files.push(change);
         }
-        summary.push_str(&body);
 
-        Ok(truncate_on_char_boundary(summary, MAX_DIFF_SIZE))
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(ExtractedDiff {
+            summary: spread_budget(&sections, max_size),
+            files,
+            symbols: symbols.into_iter().collect(),
+            redactions,
+            non_utf8,
+        })
     }
 
-    /// A `Files:` line naming every path the commit touched.
-    ///
-    /// libgit2 reports paths in the file-header line class, which
-    /// [`Self::format_diff`] deliberately drops — it keeps only `+`/`-` content.
-    /// So paths never reached the stored summary, which is what `--file`
-    /// searches. Collecting them from the deltas directly puts them back.
-    ///
-    /// Both sides of a rename are recorded, so either name finds the commit.
-    fn changed_paths(diff: &Diff) -> Result<String, GitError> {
-        let mut paths: BTreeSet<String> = BTreeSet::new();
-
-        diff.foreach(
-            &mut |delta, _progress| {
-                for file in [delta.old_file(), delta.new_file()] {
-                    if let Some(path) = file.path().and_then(|p| p.to_str()) {
-                        paths.insert(path.to_string());
-                    }
+    /// Every path `commit` changes against its first parent, old and new
+    /// sides both. Cheap: no content is compared.
+    pub fn changed_paths(repo: &Repository, commit: &Commit) -> Result<Vec<String>, GitError> {
+        let tree = commit.tree()?;
+        let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+        let mut paths = BTreeSet::new();
+        for delta in diff.deltas() {
+            for file in [delta.old_file(), delta.new_file()] {
+                if let Some(path) = file.path().and_then(|p| p.to_str()) {
+                    paths.insert(path.to_string());
                 }
-                true
-            },
-            None,
-            None,
-            None,
-        )?;
-
-        if paths.is_empty() {
-            return Ok(String::new());
+            }
         }
+        Ok(paths.into_iter().collect())
+    }
+
+    /// The record for one touched file, with its status and line counts.
+    ///
+    /// Kept outside the size budget, so a commit that touches hundreds of
+    /// files still records all of them — `--file` and the change filters
+    /// read this list, not the truncated diff text.
+    fn file_change(
+        diff: &Diff,
+        position: usize,
+        patch: Option<&Patch>,
+    ) -> Result<Option<FileChange>, GitError> {
+        let Some(delta) = diff.get_delta(position) else {
+            return Ok(None);
+        };
+        let status = match delta.status() {
+            Delta::Added => ChangeStatus::Added,
+            Delta::Deleted => ChangeStatus::Deleted,
+            Delta::Renamed => ChangeStatus::Renamed,
+            Delta::Copied => ChangeStatus::Copied,
+            _ => ChangeStatus::Modified,
+        };
 
-        let mut line = String::from("Files: ");
-        for (i, path) in paths.iter().enumerate() {
-            let separator = if i == 0 { "" } else { ", " };
-            if line.len() + separator.len() + path.len() > MAX_PATH_SECTION {
-                line.push_str(", ...");
-                break;
+        // A deletion has no new side worth naming; everything else is
+        // known by where it ended up.
+        let (side, other) = match status {
+            ChangeStatus::Deleted => (delta.old_file(), None),
+            ChangeStatus::Renamed | ChangeStatus::Copied => {
+                (delta.new_file(), Some(delta.old_file()))
             }
-            line.push_str(separator);
-            line.push_str(path);
-        }
+            _ => (delta.new_file(), None),
+        };
+        let Some(path) = side.path().and_then(|p| p.to_str()) else {
+            return Ok(None);
+        };
+        let old_path = other
+            .and_then(|file| file.path())
+            .and_then(|p| p.to_str())
+            .map(str::to_string);
+
+        let (insertions, deletions) = match patch {
+            Some(patch) => {
+                let (_, insertions, deletions) = patch.line_stats()?;
+                (insertions, deletions)
+            }
+            None => (0, 0),
+        };
+
+        Ok(Some(FileChange {
+            path: path.to_string(),
+            old_path,
+            status,
+            insertions,
+            deletions,
+        }))
+    }
 
-        Ok(line)
+    /// The enclosing definitions named by a patch's hunk headers. Like the
+    /// content, these come only from files the policy keeps — a regenerated
+    /// binding touches every symbol in it and means none of them.
+    fn hunk_symbols(patch: &Patch) -> Result<Vec<String>, GitError> {
+        let mut names = Vec::new();
+        for index in 0..patch.num_hunks() {
+            let (hunk, _) = patch.hunk(index)?;
+            if let Some(name) = std::str::from_utf8(hunk.header())
+                .ok()
+                .and_then(symbols::from_hunk_header)
+            {
+                names.push(name);
+            }
+        }
+        Ok(names)
     }
 
-    fn format_diff(diff: &Diff) -> Result<String, GitError> {
+    /// The added and removed lines, and whether any had to be decoded
+    /// lossily.
+    fn format_patch(patch: &mut Patch) -> Result<(String, bool), GitError> {
         let mut result = String::new();
+        let mut non_utf8 = false;
 
-        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+        patch.print(&mut |_delta, _hunk, line| {
             match line.origin() {
                 '+' | '-' => {
-                    if let Ok(content) = std::str::from_utf8(line.content()) {
-                        result.push(line.origin());
-                        result.push_str(content);
-                    }
+                    let decoded = decode_line(line.content());
+                    non_utf8 |= decoded.non_utf8;
+                    result.push(line.origin());
+                    result.push_str(&decoded.text);
                 }
                 _ => {}
             }
             true
         })?;
 
-        Ok(result)
+        Ok((result, non_utf8))
+    }
+}
+
+/// Join per-file diff sections within `budget` bytes, giving every file a
+/// share instead of letting the first large one take everything.
+///
+/// Shares are handed out smallest-first: a file that needs less than an even
+/// split keeps what it needs and leaves the rest to the larger files. Each
+/// section is cut on a line boundary where one fits, and a single marker at
+/// the end says something was dropped.
+fn spread_budget(sections: &[String], budget: usize) -> String {
+    let total: usize = sections.iter().map(String::len).sum();
+    if total <= budget {
+        return sections.concat();
+    }
+
+    let mut by_size: Vec<usize> = (0..sections.len()).collect();
+    by_size.sort_by_key(|&i| sections[i].len());
+
+    let mut shares = vec![0; sections.len()];
+    let mut remaining = budget;
+    for (taken, &i) in by_size.iter().enumerate() {
+        let even = remaining / (sections.len() - taken);
+        shares[i] = sections[i].len().min(even);
+        remaining -= shares[i];
+    }
+
+    let mut result = String::with_capacity(budget + TRUNCATION_MARKER.len());
+    for (section, &share) in sections.iter().zip(&shares) {
+        let kept = cut_to_fit(section, share);
+        result.push_str(kept);
+        if kept.len() < section.len() && !kept.ends_with('\n') && !kept.is_empty() {
+            result.push('\n');
+        }
+    }
+
+    while result.ends_with('\n') {
+        result.pop();
     }
+    result + TRUNCATION_MARKER
 }
 
-/// Truncate to at most `limit` bytes without splitting a UTF-8 character.
-fn truncate_on_char_boundary(text: String, limit: usize) -> String {
+/// The longest prefix of `text` within `limit` bytes, ending on a line
+/// boundary when one exists and never splitting a UTF-8 character.
+pub(super) fn cut_to_fit(text: &str, limit: usize) -> &str {
     if text.len() <= limit {
         return text;
     }
 
     // Find valid UTF-8 boundary to handle multi-byte chars (e.g. emojis)
-    let truncate_at = text
-        .char_indices()
-        .take_while(|(i, _)| *i < limit)
-        .last()
-        .map(|(i, c)| i + c.len_utf8())
-        .unwrap_or(limit);
-
-    text[..truncate_at].to_string() + "\n... (truncated)"
+    let mut end = limit;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    match text[..end].rfind('\n') {
+        Some(newline) => &text[..=newline],
+        None => &text[..end],
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn sections(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_cut_at_utf8_char_boundary() {
+        let s = "1234567890🔍abc"; // emoji at byte 10-13
+        assert_eq!(cut_to_fit(s, 12), "1234567890");
+    }
+
     #[test]
-    fn test_truncate_at_utf8_char_boundary() {
-        let s = "1234567890🔍abc".to_string(); // emoji at byte 10-13
-        let out = truncate_on_char_boundary(s, 10);
-        assert_eq!(out, "1234567890\n... (truncated)");
+    fn test_cut_prefers_a_line_boundary() {
+        assert_eq!(cut_to_fit("+one\n+two\n+three\n", 12), "+one\n+two\n");
     }
 
     #[test]
-    fn test_truncate_leaves_short_text_alone() {
-        let s = "short".to_string();
-        assert_eq!(truncate_on_char_boundary(s.clone(), 100), s);
+    fn test_cut_leaves_short_text_alone() {
+        assert_eq!(cut_to_fit("short", 100), "short");
+    }
+
+    #[test]
+    fn test_cut_at_exact_limit_is_untouched() {
+        assert_eq!(cut_to_fit("1234567890", 10), "1234567890");
+    }
+
+    #[test]
+    fn test_sections_within_budget_are_joined_whole() {
+        let parts = sections(&["+a\n", "-b\n"]);
+        assert_eq!(spread_budget(&parts, 100), "+a\n-b\n");
+    }
+
+    #[test]
+    fn test_a_large_section_cannot_starve_the_others() {
+        let big = "+noise\n".repeat(1_000);
+        let parts = sections(&[&big, "+fn login() {}\n", "-old_call();\n"]);
+        let out = spread_budget(&parts, 1_000);
+
+        assert!(out.contains("+fn login() {}"), "{out}");
+        assert!(out.contains("-old_call();"), "{out}");
+        assert!(out.contains("+noise"), "the large file keeps a share too");
+        assert!(out.ends_with(TRUNCATION_MARKER));
+        assert!(out.len() <= 1_000 + TRUNCATION_MARKER.len());
+    }
+
+    #[test]
+    fn test_unused_share_flows_to_larger_sections() {
+        let a = "+a\n".repeat(400); // 1,200 bytes
+        let b = "+b\n".repeat(400);
+        let parts = sections(&["+tiny\n", &a, &b]);
+        let out = spread_budget(&parts, 1_206);
+
+        // 6 bytes for the tiny file, 600 each for the other two — not 402.
+        assert_eq!(out.matches("+a\n").count(), 200);
+        assert_eq!(out.matches("+b").count(), 200);
     }
 
     #[test]
-    fn test_truncate_at_exact_limit_is_untouched() {
-        let s = "1234567890".to_string();
-        assert_eq!(truncate_on_char_boundary(s.clone(), 10), s);
+    fn test_budget_is_respected_with_many_sections() {
+        let parts: Vec<String> = (0..300)
+            .map(|i| format!("+line {i}\n").repeat(20))
+            .collect();
+        let out = spread_budget(&parts, MAX_DIFF_SIZE);
+        assert!(out.len() <= MAX_DIFF_SIZE + TRUNCATION_MARKER.len());
     }
 }
@@ -0,0 +1,78 @@
+//! Text that is not UTF-8.
+//!
+//! Git stores bytes. A commit made with `i18n.commitEncoding` set carries an
+//! `encoding` header naming its charset, and a Latin-1 source file diffs as
+//! Latin-1 lines. Read as UTF-8 the first comes back as no message at all and
+//! the second as no lines — the commit is indexed, just with nothing in it.
+
+use std::borrow::Cow;
+
+use encoding_rs::{Encoding, UTF_8};
+
+/// Decoded text, and whether the bytes were anything other than UTF-8.
+pub(super) struct Decoded<'a> {
+    pub text: Cow<'a, str>,
+    pub non_utf8: bool,
+}
+
+/// A commit message, decoded with the charset its `encoding` header declares.
+///
+/// No header means UTF-8, as it does to git. An unrecognised label is treated
+/// the same way. Bytes invalid in the chosen charset become U+FFFD.
+pub(super) fn decode_message<'a>(raw: &'a [u8], declared: Option<&str>) -> Decoded<'a> {
+    let encoding = declared
+        .and_then(|label| Encoding::for_label(label.trim().as_bytes()))
+        .unwrap_or(UTF_8);
+    let (text, _) = encoding.decode_without_bom_handling(raw);
+    Decoded {
+        text,
+        non_utf8: std::str::from_utf8(raw).is_err(),
+    }
+}
+
+/// One diff line. Blobs declare no charset, so invalid sequences are replaced
+/// rather than guessed at — the ASCII around them stays searchable.
+pub(super) fn decode_line(raw: &[u8]) -> Decoded<'_> {
+    let text = String::from_utf8_lossy(raw);
+    let non_utf8 = matches!(text, Cow::Owned(_));
+    Decoded { text, non_utf8 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_declared_encoding_is_honoured() {
+        let decoded = decode_message(b"fix: caf\xe9 cr\xe8me\n", Some("ISO-8859-1"));
+        assert_eq!(decoded.text, "fix: café crème\n");
+        assert!(decoded.non_utf8);
+    }
+
+    #[test]
+    fn utf8_needs_no_header() {
+        let decoded = decode_message("fix: café\n".as_bytes(), None);
+        assert_eq!(decoded.text, "fix: café\n");
+        assert!(!decoded.non_utf8);
+
+        let labelled = decode_message("fix: café\n".as_bytes(), Some("utf-8"));
+        assert!(!labelled.non_utf8);
+    }
+
+    #[test]
+    fn undeclared_or_unknown_encodings_are_decoded_lossily() {
+        for declared in [None, Some("no-such-charset")] {
+            let decoded = decode_message(b"fix: caf\xe9\n", declared);
+            assert_eq!(decoded.text, "fix: caf\u{fffd}\n");
+            assert!(decoded.non_utf8);
+        }
+    }
+
+    #[test]
+    fn diff_lines_keep_their_ascii() {
+        let decoded = decode_line(b"let name = \"Jos\xe9\";\n");
+        assert_eq!(decoded.text, "let name = \"Jos\u{fffd}\";\n");
+        assert!(decoded.non_utf8);
+        assert!(!decode_line(b"plain\n").non_utf8);
+    }
+}
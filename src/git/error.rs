@@ -16,6 +16,25 @@ pub enum GitError {
 
     #[error("git operation failed")]
     Git(#[from] git2::Error),
+
+    #[error("invalid diff ignore pattern '{0}'")]
+    InvalidIgnorePattern(String, #[source] globset::Error),
+
+    #[error("invalid redaction pattern '{0}'")]
+    InvalidRedactPattern(String, #[source] regex::Error),
+
+    #[error("invalid issue pattern '{0}'")]
+    InvalidIssuePattern(String, #[source] regex::Error),
+
+    #[error("unknown revision '{0}'")]
+    InvalidRevision(String, #[source] git2::Error),
+
+    #[error("invalid .semanticignore rule '{rule}' on line {line}: {reason}")]
+    InvalidSemanticIgnore {
+        line: usize,
+        rule: String,
+        reason: String,
+    },
 }
 
 impl GitError {
@@ -35,6 +54,21 @@ impl GitError {
                 Some("The git history may be corrupted. Try running: git fsck")
             }
             Self::Git(_) => None,
+            Self::InvalidIgnorePattern(..) => {
+                Some("Fix or remove the entry with: git config --unset-all semantic.diffIgnore")
+            }
+            Self::InvalidRedactPattern(..) => Some(
+                "Patterns use Rust regex syntax. Fix or remove them with: git config --unset-all semantic.redact",
+            ),
+            Self::InvalidIssuePattern(..) => Some(
+                "Patterns use Rust regex syntax. Fix or remove them with: git config --unset-all semantic.issuePattern",
+            ),
+            Self::InvalidRevision(..) => Some(
+                "Name a branch, tag or commit, or a range such as main..feature or main...feature",
+            ),
+            Self::InvalidSemanticIgnore { .. } => Some(
+                "Paths use .gitignore syntax; message: and author: rules use Rust regex syntax",
+            ),
         }
     }
 
@@ -46,6 +80,11 @@ impl GitError {
             Self::NoHead => "E2003",
             Self::RevwalkFailed(_) => "E2004",
             Self::Git(_) => "E2005",
+            Self::InvalidIgnorePattern(..) => "E2006",
+            Self::InvalidRedactPattern(..) => "E2007",
+            Self::InvalidIssuePattern(..) => "E2008",
+            Self::InvalidSemanticIgnore { .. } => "E2009",
+            Self::InvalidRevision(..) => "E2010",
         }
     }
 }
@@ -0,0 +1,260 @@
+//! `.semanticignore`: paths and commits kept out of the index entirely.
+//!
+//! `semantic.diffIgnore` keeps a file's lines out of the diff budget but still
+//! records the file. Vendored trees, generated code and bot commits are not
+//! worth searching at all, so this file drops them outright:
+//!
+//! ```text
+//! # Paths, as in .gitignore; the last matching line wins
+//! vendor/
+//! *.pb.go
+//! !vendor/our-fork/**
+//!
+//! # Commits, by a regex over the message or the author's "Name <email>",
+//! # as recorded or as .mailmap maps it
+//! author: \[bot\]
+//! message: ^chore\(deps\)
+//! ```
+//!
+//! A commit is skipped when a commit rule matches it, or when it changes
+//! files and every one of them is ignored. A commit that is kept loses its
+//! ignored files from `files` and from the diff text.
+
+use git2::Repository;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+use super::GitError;
+use super::policy::anchored;
+
+/// The ignore file, at the root of the main worktree.
+pub const IGNORE_FILE: &str = ".semanticignore";
+
+const MESSAGE_RULE: &str = "message:";
+const AUTHOR_RULE: &str = "author:";
+
+/// The parsed rules of a `.semanticignore`.
+#[derive(Debug)]
+pub struct IgnoreRules {
+    /// Every path glob, in file order.
+    paths: GlobSet,
+    /// Per glob in `paths`: whether its line began with `!`.
+    negated: Vec<bool>,
+    messages: Vec<Regex>,
+    authors: Vec<Regex>,
+    fingerprint: String,
+}
+
+impl Default for IgnoreRules {
+    fn default() -> Self {
+        Self {
+            paths: GlobSet::empty(),
+            negated: Vec::new(),
+            messages: Vec::new(),
+            authors: Vec::new(),
+            fingerprint: String::new(),
+        }
+    }
+}
+
+impl IgnoreRules {
+    /// Rules from the text of an ignore file. Blank lines and `#` comments
+    /// are skipped; a leading `\` escapes a `#`, `!` or rule prefix.
+    pub fn parse(text: &str) -> Result<Self, GitError> {
+        let mut globs = GlobSetBuilder::new();
+        let mut negated = Vec::new();
+        let mut messages = Vec::new();
+        let mut authors = Vec::new();
+        let mut rules = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let rule = line.trim();
+            if rule.is_empty() || rule.starts_with('#') {
+                continue;
+            }
+            rules.push(rule);
+            let invalid = |reason: String| GitError::InvalidSemanticIgnore {
+                line: number + 1,
+                rule: rule.to_string(),
+                reason,
+            };
+
+            let commit_rule = [(MESSAGE_RULE, &mut messages), (AUTHOR_RULE, &mut authors)]
+                .into_iter()
+                .find_map(|(prefix, list)| Some((rule.strip_prefix(prefix)?, list)));
+            if let Some((pattern, list)) = commit_rule {
+                list.push(Regex::new(pattern.trim()).map_err(|e| invalid(e.to_string()))?);
+                continue;
+            }
+
+            let (is_negated, pattern) = match rule.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, rule),
+            };
+            let pattern = pattern.strip_prefix('\\').unwrap_or(pattern);
+            // As in .gitignore, a pattern also covers everything beneath a
+            // directory it names, and a trailing `/` matches only that.
+            let (pattern, directory_only) = match pattern.strip_suffix('/') {
+                Some(directory) => (directory, true),
+                None => (pattern, false),
+            };
+            let glob = anchored(pattern);
+            let mut variants = vec![format!("{glob}/**")];
+            if !directory_only {
+                variants.push(glob);
+            }
+            for variant in variants {
+                let glob = GlobBuilder::new(&variant)
+                    .literal_separator(true)
+                    .build()
+                    .map_err(|e| invalid(e.kind().to_string()))?;
+                globs.add(glob);
+                negated.push(is_negated);
+            }
+        }
+
+        let paths = globs.build().map_err(|e| GitError::InvalidSemanticIgnore {
+            line: 0,
+            rule: String::new(),
+            reason: e.to_string(),
+        })?;
+        let fingerprint = if rules.is_empty() {
+            String::new()
+        } else {
+            let digest = Sha256::digest(rules.join("\n").as_bytes());
+            digest.iter().map(|byte| format!("{byte:02x}")).collect()
+        };
+
+        Ok(Self {
+            paths,
+            negated,
+            messages,
+            authors,
+            fingerprint,
+        })
+    }
+
+    /// The rules in the main worktree of `repo`, or none when it has no
+    /// ignore file.
+    ///
+    /// Linked worktrees share one index, so they share one set of rules too:
+    /// were each to read its own checkout's file, worktrees on branches with
+    /// different files would each find the index built under the wrong rules
+    /// and rebuild it in turn. A bare repository's worktrees have no main
+    /// checkout and read their own.
+    pub fn from_repo(repo: &Repository) -> Result<Self, GitError> {
+        let main = Repository::open(repo.commondir())?;
+        let Some(workdir) = main.workdir().or(repo.workdir()) else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(workdir.join(IGNORE_FILE)) {
+            Ok(text) => Self::parse(&text),
+            Err(err) => {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!("could not read {IGNORE_FILE}: {err}");
+                }
+                Ok(Self::default())
+            }
+        }
+    }
+
+    /// Identifies the rules, so an index can tell it was built under
+    /// different ones. Empty when there are none, so indexes built before the
+    /// file existed still match a repository without one.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    pub fn has_path_rules(&self) -> bool {
+        !self.negated.is_empty()
+    }
+
+    /// Whether `path`, relative to the repository root, is ignored: the last
+    /// line matching it decides.
+    pub fn ignores_path(&self, path: &str) -> bool {
+        self.paths
+            .matches(path)
+            .into_iter()
+            .max()
+            .is_some_and(|last| !self.negated[last])
+    }
+
+    /// Whether a commit rule matches the commit's message or its author,
+    /// written `Name <email>`.
+    pub fn ignores_commit(&self, message: &str, author: &str) -> bool {
+        self.messages.iter().any(|rule| rule.is_match(message))
+            || self.authors.iter().any(|rule| rule.is_match(author))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_rules_ignore_nothing_and_have_no_fingerprint() {
+        let rules = IgnoreRules::parse("# nothing yet\n\n").unwrap();
+        assert!(!rules.has_path_rules());
+        assert!(!rules.ignores_path("src/main.rs"));
+        assert!(!rules.ignores_commit("fix: crash", "Alice <alice@example.com>"));
+        assert_eq!(rules.fingerprint(), "");
+    }
+
+    #[test]
+    fn path_rules_follow_gitignore_anchoring() {
+        let rules = IgnoreRules::parse("vendor/\n*.pb.go\n/generated\n").unwrap();
+        assert!(rules.ignores_path("vendor/lib/a.c"));
+        assert!(rules.ignores_path("third_party/vendor/b.c"));
+        assert!(rules.ignores_path("api/v1/service.pb.go"));
+        assert!(rules.ignores_path("generated/schema.rs"));
+        assert!(!rules.ignores_path("src/generated/schema.rs"));
+        assert!(
+            !rules.ignores_path("vendor"),
+            "a trailing slash names a directory"
+        );
+        assert!(!rules.ignores_path("src/vendor.rs"));
+    }
+
+    #[test]
+    fn a_later_negation_takes_a_path_back() {
+        let rules = IgnoreRules::parse("vendor/\n!vendor/our-fork/**\n").unwrap();
+        assert!(rules.ignores_path("vendor/zlib/inflate.c"));
+        assert!(!rules.ignores_path("vendor/our-fork/patch.c"));
+    }
+
+    #[test]
+    fn commit_rules_match_the_message_or_the_author() {
+        let rules = IgnoreRules::parse("author: \\[bot\\]\nmessage: ^chore\\(deps\\)\n").unwrap();
+        assert!(rules.ignores_commit("bump serde", "dependabot[bot] <bot@github.com>"));
+        assert!(rules.ignores_commit("chore(deps): bump serde", "Alice <a@example.com>"));
+        assert!(!rules.ignores_commit("fix(deps): pin serde", "Alice <a@example.com>"));
+        assert!(!rules.has_path_rules());
+    }
+
+    #[test]
+    fn a_backslash_escapes_a_leading_hash() {
+        let rules = IgnoreRules::parse("\\#notes.md\n").unwrap();
+        assert!(rules.ignores_path("#notes.md"));
+    }
+
+    #[test]
+    fn the_fingerprint_follows_the_rules_not_the_comments() {
+        let a = IgnoreRules::parse("vendor/\n").unwrap();
+        let b = IgnoreRules::parse("# vendored code\nvendor/\n\n").unwrap();
+        let c = IgnoreRules::parse("vendor/\n*.pb.go\n").unwrap();
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), c.fingerprint());
+        assert_eq!(a.fingerprint().len(), 64);
+    }
+
+    #[test]
+    fn an_invalid_rule_names_its_line() {
+        let err = IgnoreRules::parse("vendor/\nmessage: (unclosed\n").unwrap_err();
+        assert!(matches!(
+            err,
+            GitError::InvalidSemanticIgnore { line: 2, ref rule, .. } if rule == "message: (unclosed"
+        ));
+        assert_eq!(err.code(), "E2009");
+    }
+}
@@ -0,0 +1,236 @@
+//! Issue and ticket references in commit messages.
+//!
+//! `Fixes #1234`, `GH-1234`, `JIRA-456` and pasted tracker URLs all name a
+//! ticket, but as text they only reach BM25 — and `#1234` tokenizes to a bare
+//! number that matches every commit mentioning 1234 of anything. Pulling them
+//! into a normalized list makes "every commit for JIRA-456" an exact lookup.
+
+use git2::Repository;
+use regex::Regex;
+
+use super::GitError;
+
+/// Git config key holding extra reference patterns. Multi-valued; a pattern
+/// with a capture group records the first group, otherwise the whole match —
+/// as does one whose group is only a number, which would collide with `#N`.
+pub const ISSUE_PATTERN_KEY: &str = "semantic.issuePattern";
+
+/// GitHub, GitLab and Gitea links to an issue, pull or merge request:
+/// `owner/repo` (or a GitLab group path) and the number.
+const TRACKER_URL: &str =
+    r"https?://[^\s/]+/([\w.\-]+(?:/[\w.\-]+)*?)/(?:-/)?(issues|pulls?|merge_requests)/(\d+)";
+
+/// `#1234`, unless it is part of a word, an HTML entity or a URL fragment.
+const HASH_NUMBER: &str = r"(?:^|[^\w&/#])#(\d+)\b";
+
+/// GitHub's own `GH-1234` spelling of `#1234`.
+const GH_NUMBER: &str = r"(?i)\bGH-(\d+)\b";
+
+/// Jira-style project keys: `JIRA-456`, `PROJ2-17`.
+const PROJECT_KEY: &str = r"\b([A-Z][A-Z0-9]{1,9}-[1-9][0-9]*)\b";
+
+/// Prefixes shaped like a project key that are almost never one.
+const NOT_PROJECT_KEYS: &[&str] = &["CVE", "GH", "ISO", "RFC", "SHA", "UTF"];
+
+/// Finds issue references and reduces them to one spelling each.
+pub struct IssueExtractor {
+    tracker_url: Regex,
+    hash_number: Regex,
+    gh_number: Regex,
+    project_key: Regex,
+    custom: Vec<Regex>,
+}
+
+impl IssueExtractor {
+    /// The built-in patterns plus `extra`.
+    pub fn new(extra: &[String]) -> Result<Self, GitError> {
+        let builtin = |pattern| Regex::new(pattern).expect("built-in issue patterns are valid");
+        let custom = extra
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| GitError::InvalidIssuePattern(pattern.clone(), e))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            tracker_url: builtin(TRACKER_URL),
+            hash_number: builtin(HASH_NUMBER),
+            gh_number: builtin(GH_NUMBER),
+            project_key: builtin(PROJECT_KEY),
+            custom,
+        })
+    }
+
+    /// The built-ins plus every `semantic.issuePattern` in the repository's
+    /// git config.
+    pub fn from_repo(repo: &Repository) -> Result<Self, GitError> {
+        let mut extra = Vec::new();
+        let config = repo.config()?;
+        if let Ok(entries) = config.multivar(ISSUE_PATTERN_KEY, None) {
+            entries.for_each(|entry| {
+                if let Ok(value) = entry.value() {
+                    extra.push(value.to_string());
+                }
+            })?;
+        }
+        Self::new(&extra)
+    }
+
+    /// Every reference in `message`, normalized, sorted and deduplicated.
+    ///
+    /// Tracker URLs become `owner/repo#N` (`!N` for a GitLab merge request),
+    /// so they stay distinct from same-numbered issues in other repositories
+    /// while `--issue '#N'` still finds them.
+    pub fn extract(&self, message: &str) -> Vec<String> {
+        let mut found = Vec::new();
+
+        for caps in self.tracker_url.captures_iter(message) {
+            let sigil = if &caps[2] == "merge_requests" {
+                '!'
+            } else {
+                '#'
+            };
+            found.push(format!("{}{sigil}{}", &caps[1], &caps[3]));
+        }
+        // URLs are removed before the shorter patterns run, so the issue
+        // number in `…/issues/12#issuecomment-3` is not read twice.
+        let rest = self.tracker_url.replace_all(message, " ");
+
+        for caps in self.hash_number.captures_iter(&rest) {
+            found.push(format!("#{}", &caps[1]));
+        }
+        for caps in self.gh_number.captures_iter(&rest) {
+            found.push(format!("#{}", &caps[1]));
+        }
+        for caps in self.project_key.captures_iter(&rest) {
+            let key = &caps[1];
+            let project = key.split('-').next().unwrap_or(key);
+            if !NOT_PROJECT_KEYS.contains(&project) {
+                found.push(key.to_string());
+            }
+        }
+        for pattern in &self.custom {
+            for caps in pattern.captures_iter(message) {
+                // A group that caught only the number would read as a GitHub
+                // issue, so `SR1234` keeps the prefix the pattern matched.
+                let reference = match caps.get(1) {
+                    Some(group) if !group.as_str().trim().bytes().all(|b| b.is_ascii_digit()) => {
+                        group.as_str()
+                    }
+                    _ => &caps[0],
+                };
+                found.push(normalize(reference));
+            }
+        }
+
+        found.retain(|reference| !reference.is_empty());
+        found.sort();
+        found.dedup();
+        found
+    }
+}
+
+impl Default for IssueExtractor {
+    fn default() -> Self {
+        Self::new(&[]).expect("built-in issue patterns are valid")
+    }
+}
+
+/// One spelling per reference: `GH-12`, `gh-12` and `12` become `#12`, and
+/// project keys are upper-cased. Anything else is kept as written.
+pub fn normalize(reference: &str) -> String {
+    let reference = reference.trim();
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    if digits(reference) {
+        return format!("#{reference}");
+    }
+    if let Some((prefix, number)) = reference.split_once('-')
+        && digits(number)
+        && !prefix.is_empty()
+        && prefix.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        if prefix.eq_ignore_ascii_case("gh") {
+            return format!("#{number}");
+        }
+        return reference.to_ascii_uppercase();
+    }
+    reference.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extracted(message: &str) -> Vec<String> {
+        IssueExtractor::default().extract(message)
+    }
+
+    #[test]
+    fn the_common_spellings_are_found_and_normalized() {
+        assert_eq!(
+            extracted("fix: retry on timeout (#1234)\n\nFixes GH-77, relates to JIRA-456."),
+            vec!["#1234", "#77", "JIRA-456"]
+        );
+    }
+
+    #[test]
+    fn tracker_urls_keep_their_repository() {
+        assert_eq!(
+            extracted(
+                "See https://github.com/acme/api/issues/12#issuecomment-3 and\n\
+                 https://gitlab.com/group/sub/proj/-/merge_requests/9"
+            ),
+            vec!["acme/api#12", "group/sub/proj!9"]
+        );
+    }
+
+    #[test]
+    fn lookalikes_are_not_references() {
+        for message in [
+            "docs: switch to UTF-8 everywhere",
+            "fix: use SHA-256 for digests",
+            "chore: patch CVE-2024-1234",
+            "style: colour &#123; entity",
+            "refactor: issue#12 is not a reference",
+            "feat: add step 2",
+        ] {
+            assert!(extracted(message).is_empty(), "{message:?}");
+        }
+    }
+
+    #[test]
+    fn custom_patterns_record_their_first_group() {
+        let extractor = IssueExtractor::new(&[r"\bticket (T[0-9]{5})\b".to_string()]).unwrap();
+        assert_eq!(
+            extractor.extract("fix: ticket T40213 again"),
+            vec!["T40213"]
+        );
+    }
+
+    #[test]
+    fn a_custom_number_keeps_its_prefix() {
+        let extractor = IssueExtractor::new(&["SR([0-9]{4})".to_string()]).unwrap();
+        assert_eq!(
+            extractor.extract("fix: SR1234, not #1234"),
+            vec!["#1234", "SR1234"]
+        );
+    }
+
+    #[test]
+    fn an_invalid_custom_pattern_is_an_error() {
+        let err = IssueExtractor::new(&["(unclosed".to_string()])
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), "E2008");
+    }
+
+    #[test]
+    fn normalize_agrees_with_extraction() {
+        assert_eq!(normalize("GH-12"), "#12");
+        assert_eq!(normalize("12"), "#12");
+        assert_eq!(normalize("#12"), "#12");
+        assert_eq!(normalize(" jira-456 "), "JIRA-456");
+        assert_eq!(normalize("acme/api#12"), "acme/api#12");
+    }
+}
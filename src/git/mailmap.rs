@@ -0,0 +1,189 @@
+//! Canonical identities from the repository's `.mailmap`.
+//!
+//! One person committing as `yan`, `Yan Xue` and a work address shows up as
+//! three authors unless something folds them together. Git's answer is
+//! `.mailmap`, which `git log` and `git shortlog` already honour; this applies
+//! the same mapping so `--author` and anything counting authors agree with them.
+
+use git2::{Mailmap, Repository, Signature};
+use std::path::Path;
+
+use super::{CommitInfo, GitError, Identity, trailers};
+
+/// Looked up in place of an empty name, which libgit2 will not accept.
+const NAMELESS: &str = "(unknown)";
+
+/// Resolves raw names and emails to the identities `.mailmap` says they are.
+pub struct IdentityMap {
+    mailmap: Mailmap,
+}
+
+impl IdentityMap {
+    /// The mapping git itself would use for `repo`: the `.mailmap` in the
+    /// working tree plus whatever `mailmap.file` and `mailmap.blob` point at.
+    pub fn from_repo(repo: &Repository) -> Result<Self, GitError> {
+        Ok(Self {
+            mailmap: repo.mailmap()?,
+        })
+    }
+
+    /// The mapping for the repository containing `path`.
+    pub fn discover(path: &Path) -> Result<Self, GitError> {
+        let repo = Repository::discover(path).map_err(GitError::RepositoryNotFound)?;
+        Self::from_repo(&repo)
+    }
+
+    /// A mapping read from `.mailmap`-formatted text.
+    pub fn from_buffer(text: &str) -> Result<Self, GitError> {
+        Ok(Self {
+            mailmap: Mailmap::from_buffer(text)?,
+        })
+    }
+
+    /// The canonical form of `name <email>`, or the input unchanged when no
+    /// entry matches.
+    pub fn resolve(&self, name: &str, email: &str) -> Identity {
+        let raw = Identity {
+            name: name.to_string(),
+            email: email.to_string(),
+        };
+        // libgit2 refuses to build a signature with an empty name, which a
+        // `Co-authored-by:` trailer can easily have. Its email can still be
+        // mapped, so stand a placeholder in for the name.
+        let lookup = if name.trim().is_empty() {
+            NAMELESS
+        } else {
+            name
+        };
+        let Ok(signature) = Signature::now(lookup, email) else {
+            return raw;
+        };
+        match self.mailmap.resolve_signature(&signature) {
+            Ok(resolved) => Identity {
+                // An entry that only maps the email leaves the name as it was
+                // looked up, placeholder and libgit2's trimming included.
+                name: match resolved.name() {
+                    Ok(mapped) if mapped != signature.name().unwrap_or(lookup) => {
+                        mapped.to_string()
+                    }
+                    _ => raw.name,
+                },
+                email: resolved.email().unwrap_or(email).to_string(),
+            },
+            Err(_) => raw,
+        }
+    }
+
+    /// Fill in `commit`'s canonical author and committer and re-resolve its
+    /// co-authors, all from the raw values it recorded.
+    ///
+    /// Idempotent, and cheap enough to run over a whole loaded index, which is
+    /// how an edited `.mailmap` takes effect without re-indexing.
+    pub fn canonicalize(&self, commit: &mut CommitInfo) {
+        commit.canonical_author = self.resolve(&commit.author, &commit.author_email);
+        commit.canonical_committer = self.resolve(&commit.committer, &commit.committer_email);
+        // The trailers in the message are the raw form; `co_authors` holds
+        // them resolved, so re-reading the message undoes any earlier mapping.
+        commit.co_authors = trailers::co_authors(&commit.message)
+            .into_iter()
+            .map(|person| self.resolve(&person.name, &person.email))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAILMAP: &str = "\
+Yan Xue <yan@example.com> <yan@work.example>
+Yan Xue <yan@example.com> yan <yan@laptop.local>
+<bob@example.com> <bob@old.example>
+";
+
+    fn identity(name: &str, email: &str) -> Identity {
+        Identity {
+            name: name.to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    #[test]
+    fn aliases_resolve_to_the_canonical_identity() {
+        let map = IdentityMap::from_buffer(MAILMAP).unwrap();
+        assert_eq!(
+            map.resolve("Yan", "yan@work.example"),
+            identity("Yan Xue", "yan@example.com")
+        );
+        assert_eq!(
+            map.resolve("yan", "yan@laptop.local"),
+            identity("Yan Xue", "yan@example.com")
+        );
+    }
+
+    #[test]
+    fn an_email_only_entry_keeps_the_recorded_name() {
+        let map = IdentityMap::from_buffer(MAILMAP).unwrap();
+        assert_eq!(
+            map.resolve("Bob", "bob@old.example"),
+            identity("Bob", "bob@example.com")
+        );
+    }
+
+    #[test]
+    fn unknown_identities_pass_through() {
+        let map = IdentityMap::from_buffer(MAILMAP).unwrap();
+        assert_eq!(
+            map.resolve("Carol", "carol@example.com"),
+            identity("Carol", "carol@example.com")
+        );
+        assert_eq!(
+            map.resolve("", "carol@example.com"),
+            identity("", "carol@example.com")
+        );
+    }
+
+    #[test]
+    fn nameless_identities_are_resolved_by_email() {
+        let map = IdentityMap::from_buffer(MAILMAP).unwrap();
+        assert_eq!(
+            map.resolve("", "yan@work.example"),
+            identity("Yan Xue", "yan@example.com")
+        );
+        assert_eq!(
+            map.resolve("", "bob@old.example"),
+            identity("", "bob@example.com"),
+            "an email-only entry does not invent a name"
+        );
+    }
+
+    #[test]
+    fn canonicalize_resolves_co_authors_from_the_message() {
+        let map = IdentityMap::from_buffer(MAILMAP).unwrap();
+        let mut commit = CommitInfo {
+            author: "yan".to_string(),
+            author_email: "yan@laptop.local".to_string(),
+            committer: "Bob".to_string(),
+            committer_email: "bob@old.example".to_string(),
+            message: "fix: pair\n\nCo-authored-by: Yan <yan@work.example>\n".to_string(),
+            ..Default::default()
+        };
+
+        map.canonicalize(&mut commit);
+        map.canonicalize(&mut commit);
+
+        assert_eq!(commit.author, "yan", "the raw value is kept");
+        assert_eq!(
+            commit.canonical_author,
+            identity("Yan Xue", "yan@example.com")
+        );
+        assert_eq!(
+            commit.canonical_committer,
+            identity("Bob", "bob@example.com")
+        );
+        assert_eq!(
+            commit.co_authors,
+            vec![identity("Yan Xue", "yan@example.com")]
+        );
+    }
+}
@@ -1,55 +1,449 @@
+pub mod conventional;
 mod diff;
+mod encoding;
 mod error;
+mod ignore;
+pub mod issues;
+mod mailmap;
+mod notes;
 mod parser;
+mod policy;
+mod redact;
+mod revisions;
+pub mod symbols;
+pub mod trailers;
 
+pub use diff::MAX_DIFF_SIZE;
 pub use error::GitError;
+pub use ignore::{IGNORE_FILE, IgnoreRules};
+pub use issues::{ISSUE_PATTERN_KEY, IssueExtractor};
+pub use mailmap::IdentityMap;
+pub use notes::{NOTES_REF_KEY, NotesReader};
 pub use parser::RepositoryParser;
+pub use policy::{DEFAULT_DIFF_IGNORE, DIFF_IGNORE_KEY, DiffPolicy, anchored};
+pub use redact::{REDACT_ENTROPY_KEY, REDACT_KEY, Redactor};
+pub use revisions::reachable;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// One indexed commit or annotated tag.
+///
+/// Every field defaults when absent, so a JSONL record for `import` written by
+/// another tool need only carry what it knows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CommitInfo {
     pub hash: String,
+    /// Author name. Kept as a bare string, not an [`Identity`], because every
+    /// filter and display path reads it directly.
     pub author: String,
+    pub author_email: String,
+    /// The author as `.mailmap` names them. Equal to the raw values when no
+    /// entry applies; empty only in indexes written before it was recorded.
+    pub canonical_author: Identity,
+    /// Author date — when the change was written, not when it landed.
     pub date: DateTime<Utc>,
+    /// Minutes east of UTC on the author's clock, as git records it. `date`
+    /// is the instant; this is how to show it the way the author saw it.
+    pub date_offset: i32,
+    /// Who applied the commit: the merger of a PR, the rebaser, the
+    /// maintainer who took a patch. Often not the author.
+    pub committer: String,
+    pub committer_email: String,
+    pub canonical_committer: Identity,
+    pub committer_date: DateTime<Utc>,
+    pub committer_date_offset: i32,
+    /// People credited with `Co-authored-by:` trailers, resolved through
+    /// `.mailmap`. The raw form stays in the message.
+    pub co_authors: Vec<Identity>,
     pub message: String,
+    /// `Key: value` lines from the message's closing trailer block, in order.
+    pub trailers: Vec<Trailer>,
+    /// Type, scope and breaking marker, when the subject follows
+    /// Conventional Commits.
+    pub conventional: Option<ConventionalCommit>,
+    /// Issue and ticket references in the message, normalized by
+    /// [`issues::normalize`], sorted and deduplicated.
+    pub issues: Vec<String>,
+    /// `git notes` from the configured notes refs, read when the commit was
+    /// indexed and again whenever one of those refs moves.
+    pub notes: Vec<Note>,
+    /// Set when this entry is an annotated tag rather than a commit: the tag
+    /// name, with `hash` the commit it points at and `message`, `author` and
+    /// `date` the tag's own.
+    pub tag: Option<String>,
+    /// The pull or merge request this commit landed through, once imported
+    /// with `import-prs`.
+    pub pull_request: Option<PullRequest>,
+    /// Added and removed lines, within the extractor's size budget.
     pub diff_summary: String,
+    /// Every file the commit touched, sorted by path. Empty in quick mode,
+    /// which never diffs.
+    pub files: Vec<FileChange>,
+    /// Functions and types whose bodies the diff changed, sorted and
+    /// deduplicated, as named by git's hunk headers.
+    pub symbols: Vec<String>,
+    /// Secrets replaced by placeholders in `diff_summary`.
+    pub redactions: usize,
+    /// The message or some diff line was not UTF-8 and had to be decoded —
+    /// from the charset the commit declares, or lossily.
+    pub non_utf8: bool,
 }
 
-/// Prefix of the line in `diff_summary` that lists the commit's changed paths.
+/// Which of a commit's two timestamps a filter or ordering reads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateField {
+    /// When the change was written — what `git log` shows.
+    #[default]
+    Author,
+    /// When it was applied. Moves on every rebase, cherry-pick and amend, so
+    /// it is the one that says when a change reached the branch.
+    Committer,
+}
+
+/// A name and email as git records them, either of which may be empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identity {
+    pub name: String,
+    pub email: String,
+}
+
+/// One `Key: value` line from a commit's trailer block, such as
+/// `Reviewed-by: Bob <bob@example.com>` or `Fixes: #123`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trailer {
+    /// As written; compared case-insensitively, as git does.
+    pub key: String,
+    pub value: String,
+}
+
+/// A note attached to a commit under one notes ref.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note {
+    /// Full ref name, e.g. `refs/notes/review`.
+    pub notes_ref: String,
+    pub message: String,
+}
+
+/// A pull or merge request, as imported from a forge export.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PullRequest {
+    /// `#N` on GitHub, `!N` on GitLab.
+    pub number: u64,
+    pub title: String,
+    pub body: String,
+    pub labels: Vec<String>,
+}
+
+/// The structured header of a Conventional Commits subject.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConventionalCommit {
+    /// `fix`, `feat`, `perf`…, lowercased.
+    pub kind: String,
+    /// The parenthesised scope, as written.
+    pub scope: Option<String>,
+    /// Marked with `!` or a `BREAKING CHANGE:` footer.
+    pub breaking: bool,
+}
+
+impl ConventionalCommit {
+    /// Whether the scope is `name`, ignoring case. A list scope such as
+    /// `api,auth` matches each of its parts.
+    pub fn in_scope(&self, name: &str) -> bool {
+        self.scope.as_deref().is_some_and(|scope| {
+            scope
+                .split(',')
+                .any(|part| part.trim().eq_ignore_ascii_case(name))
+        })
+    }
+}
+
+/// What happened to one file in one commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    /// The path before a rename, or the source of a copy.
+    pub old_path: Option<String>,
+    pub status: ChangeStatus,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeStatus {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Copied,
+    /// Migrated from an index that recorded paths but not what happened to
+    /// them. Matches path filters and nothing else.
+    Unknown,
+}
+
+impl ChangeStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Modified => "modified",
+            Self::Deleted => "deleted",
+            Self::Renamed => "renamed",
+            Self::Copied => "copied",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl FileChange {
+    /// A change with no line counts and no previous path.
+    pub fn new(path: impl Into<String>, status: ChangeStatus) -> Self {
+        Self {
+            path: path.into(),
+            old_path: None,
+            status,
+            insertions: 0,
+            deletions: 0,
+        }
+    }
+
+    /// Whether this change's path, or its previous path, contains `needle`.
+    pub fn touches(&self, needle: &str) -> bool {
+        self.path.contains(needle)
+            || (self.status == ChangeStatus::Renamed
+                && self
+                    .old_path
+                    .as_deref()
+                    .is_some_and(|old| old.contains(needle)))
+    }
+
+    /// Lines added plus lines removed.
+    pub fn lines_changed(&self) -> usize {
+        self.insertions + self.deletions
+    }
+}
+
+/// Prefix of the line listing a commit's changed paths in its embedded text.
 pub const FILES_PREFIX: &str = "Files: ";
 
+/// Prefix of the line listing the symbols a commit changed.
+pub const SYMBOLS_PREFIX: &str = "Symbols: ";
+
+/// Cap on the pull request description folded into the embedded text. PR
+/// templates run long; the model only reads the first few hundred tokens.
+const MAX_PR_BODY: usize = 4_000;
+
+/// Cap on each rendered list, so a commit that touches hundreds of files
+/// still leaves the model room for the diff itself.
+const MAX_PATH_SECTION: usize = 2_000;
+
 impl CommitInfo {
     pub fn to_text(&self, include_diff: bool) -> String {
-        let mut text = format!("{}\n{}", self.message, self.author);
+        let mut text = match &self.tag {
+            Some(tag) => format!("Tag {tag}\n{}\n{}", self.message, self.author),
+            None => format!("{}\n{}", self.message, self.author),
+        };
+
+        // The PR description is usually the fuller explanation of a squash
+        // merge, so it sits next to the message rather than after the diff.
+        if let Some(pr) = &self.pull_request {
+            text.push_str(&format!("\nPull request #{}: {}", pr.number, pr.title));
+            if !pr.body.trim().is_empty() {
+                text.push('\n');
+                text.push_str(diff::cut_to_fit(pr.body.trim(), MAX_PR_BODY).trim_end());
+            }
+        }
 
-        if include_diff && !self.diff_summary.is_empty() {
+        // Notes are prose about the change, like the message, so they are
+        // embedded in quick mode too.
+        for note in &self.notes {
             text.push('\n');
-            text.push_str(&self.diff_summary);
+            text.push_str(&note.message);
+        }
+
+        if include_diff {
+            for section in [
+                self.files_line(),
+                self.symbols_line(),
+                self.diff_summary.clone(),
+            ] {
+                if !section.is_empty() {
+                    text.push('\n');
+                    text.push_str(&section);
+                }
+            }
         }
 
         text
     }
 
-    /// Paths this commit touched, as recorded by the diff extractor.
+    /// Whether this entry is an annotated tag rather than a commit.
+    pub fn is_tag(&self) -> bool {
+        self.tag.is_some()
+    }
+
+    /// The `Files:` line fed to the model and to BM25, so paths are searchable
+    /// by name. Both sides of a rename appear, so either name finds the commit.
+    ///
+    /// Empty when no paths are recorded.
+    pub fn files_line(&self) -> String {
+        capped_line(FILES_PREFIX, self.changed_files().unwrap_or_default())
+    }
+
+    /// The `Symbols:` line, so "commits that changed `encode_text`" finds them
+    /// by name in both retrievers. Empty when none were recorded.
+    pub fn symbols_line(&self) -> String {
+        capped_line(SYMBOLS_PREFIX, self.symbols.iter().map(String::as_str))
+    }
+
+    /// The timestamp `field` names, as an instant.
+    pub fn date_for(&self, field: DateField) -> DateTime<Utc> {
+        match field {
+            DateField::Author => self.date,
+            DateField::Committer => self.committer_date,
+        }
+    }
+
+    /// The timestamp `field` names, in the time zone it was recorded in.
+    pub fn local_date_for(&self, field: DateField) -> DateTime<FixedOffset> {
+        let (date, offset) = match field {
+            DateField::Author => (self.date, self.date_offset),
+            DateField::Committer => (self.committer_date, self.committer_date_offset),
+        };
+        local_time(date, offset)
+    }
+
+    /// The author date in the author's own time zone.
+    pub fn local_date(&self) -> DateTime<FixedOffset> {
+        self.local_date_for(DateField::Author)
+    }
+
+    /// The author's name as `.mailmap` gives it, falling back to the raw name
+    /// for indexes that predate canonical identities.
+    pub fn author_name(&self) -> &str {
+        if self.canonical_author.name.is_empty() {
+            &self.author
+        } else {
+            &self.canonical_author.name
+        }
+    }
+
+    /// Whether `needle` (lowercase) appears in the author's name, raw or
+    /// canonical, or any co-author's.
+    pub fn credits_name(&self, needle: &str) -> bool {
+        self.author.to_lowercase().contains(needle)
+            || self.canonical_author.name.to_lowercase().contains(needle)
+            || self
+                .co_authors
+                .iter()
+                .any(|person| person.name.to_lowercase().contains(needle))
+    }
+
+    /// Whether `needle` (lowercase) appears in the author's email, raw or
+    /// canonical, or any co-author's.
+    pub fn credits_email(&self, needle: &str) -> bool {
+        self.author_email.to_lowercase().contains(needle)
+            || self.canonical_author.email.to_lowercase().contains(needle)
+            || self
+                .co_authors
+                .iter()
+                .any(|person| person.email.to_lowercase().contains(needle))
+    }
+
+    /// Whether a trailer has key `key`, compared case-insensitively, and — when
+    /// `value` (lowercase) is given — a value containing it.
+    pub fn has_trailer(&self, key: &str, value: Option<&str>) -> bool {
+        self.trailers.iter().any(|trailer| {
+            trailer.key.eq_ignore_ascii_case(key)
+                && value.is_none_or(|needle| trailer.value.to_lowercase().contains(needle))
+        })
+    }
+
+    /// Whether the message references `issue`, already normalized. A bare
+    /// `#N` also matches `owner/repo#N`, since which repository a short
+    /// reference meant is rarely in doubt.
+    pub fn mentions_issue(&self, issue: &str) -> bool {
+        self.issues.iter().any(|reference| {
+            reference.eq_ignore_ascii_case(issue)
+                || (issue.starts_with('#') && reference.ends_with(issue))
+        })
+    }
+
+    /// Whether the diff changed a symbol named exactly `name`.
+    pub fn touches_symbol(&self, name: &str) -> bool {
+        self.symbols.iter().any(|symbol| symbol == name)
+    }
+
+    /// Paths this commit touched, sorted and deduplicated, including the old
+    /// side of every rename.
     ///
-    /// Returns `None` for indexes built before paths were recorded, letting
-    /// callers fall back rather than silently treating every commit as touching
-    /// nothing.
+    /// Returns `None` when no paths are recorded — quick mode, or an index
+    /// built before paths were — letting callers fall back rather than
+    /// silently treating every commit as touching nothing.
     pub fn changed_files(&self) -> Option<Vec<&str>> {
-        let line = self
+        if self.files.is_empty() {
+            return None;
+        }
+
+        let mut paths: Vec<&str> = self
+            .files
+            .iter()
+            .flat_map(|change| {
+                let old = match change.status {
+                    ChangeStatus::Renamed => change.old_path.as_deref(),
+                    _ => None,
+                };
+                std::iter::once(change.path.as_str()).chain(old)
+            })
+            .collect();
+        paths.sort_unstable();
+        paths.dedup();
+        Some(paths)
+    }
+
+    /// Files this commit moved, as `(from, to)` pairs.
+    pub fn renames(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.files
+            .iter()
+            .filter_map(|change| match (change.status, change.old_path.as_deref()) {
+                (ChangeStatus::Renamed, Some(from)) => Some((from, change.path.as_str())),
+                _ => None,
+            })
+    }
+
+    /// Move a `Files:` line at the head of `diff_summary` into [`Self::files`].
+    ///
+    /// Until 1.5.0 that line was the only record of the changed paths. What
+    /// happened to each path was never stored, so lifted paths are
+    /// [`ChangeStatus::Unknown`] with no line counts. Does nothing when paths
+    /// are already recorded or the summary has no such line.
+    pub fn lift_files_line(&mut self) {
+        if !self.files.is_empty() {
+            return;
+        }
+        let Some(line) = self
             .diff_summary
             .lines()
-            .next()?
-            .strip_prefix(FILES_PREFIX)?;
+            .next()
+            .and_then(|first| first.strip_prefix(FILES_PREFIX))
+        else {
+            return;
+        };
 
-        Some(
-            line.split(", ")
-                .map(str::trim)
-                .filter(|p| !p.is_empty() && *p != "...")
-                .collect(),
-        )
+        self.files = line
+            .split(", ")
+            .map(str::trim)
+            .filter(|p| !p.is_empty() && *p != "...")
+            .map(|path| FileChange::new(path, ChangeStatus::Unknown))
+            .collect();
+
+        let body = self
+            .diff_summary
+            .split_once('\n')
+            .map(|(_, body)| body)
+            .unwrap_or("");
+        self.diff_summary = body.to_string();
     }
 
     /// Whether this commit touched a path containing `needle`.
@@ -58,11 +452,41 @@ impl CommitInfo {
     /// degrades to the old behaviour — substring search over the whole diff —
     /// which is imprecise but better than matching nothing.
     pub fn touches_path(&self, needle: &str) -> bool {
-        match self.changed_files() {
-            Some(paths) => paths.iter().any(|path| path.contains(needle)),
-            None => self.diff_summary.contains(needle),
+        if self.files.is_empty() {
+            return self.diff_summary.contains(needle);
+        }
+        self.files.iter().any(|change| change.touches(needle))
+    }
+}
+
+/// `date` shifted to `offset_minutes` east of UTC. An offset chrono rejects —
+/// only a corrupt commit header can produce one — falls back to UTC.
+pub(crate) fn local_time(date: DateTime<Utc>, offset_minutes: i32) -> DateTime<FixedOffset> {
+    let offset = offset_minutes
+        .checked_mul(60)
+        .and_then(FixedOffset::east_opt)
+        .unwrap_or_else(|| FixedOffset::east_opt(0).expect("zero is a valid offset"));
+    date.with_timezone(&offset)
+}
+
+/// `prefix` followed by `items` joined with commas, capped at
+/// [`MAX_PATH_SECTION`] with a trailing `, ...` when something was cut.
+fn capped_line<'a>(prefix: &str, items: impl IntoIterator<Item = &'a str>) -> String {
+    let mut line = String::from(prefix);
+    for (i, item) in items.into_iter().enumerate() {
+        let separator = if i == 0 { "" } else { ", " };
+        if line.len() + separator.len() + item.len() > MAX_PATH_SECTION {
+            line.push_str(", ...");
+            break;
         }
+        line.push_str(separator);
+        line.push_str(item);
+    }
+
+    if line.len() == prefix.len() {
+        return String::new();
     }
+    line
 }
 
 #[cfg(test)]
@@ -78,6 +502,7 @@ mod tests {
                 .with_timezone(&Utc),
             message: "fix: resolve race condition in auth".to_string(),
             diff_summary: "+mutex.lock()\n-unsafe_access()".to_string(),
+            ..Default::default()
         }
     }
 
@@ -116,15 +541,94 @@ mod tests {
         assert!(text.ends_with(&commit.author));
     }
 
+    #[test]
+    fn test_to_text_includes_notes_even_without_diff() {
+        let mut commit = sample_commit();
+        commit.notes = vec![Note {
+            notes_ref: "refs/notes/review".to_string(),
+            message: "Approved after load test".to_string(),
+        }];
+        assert_eq!(
+            commit.to_text(false),
+            "fix: resolve race condition in auth\nAlice Chen\nApproved after load test"
+        );
+    }
+
+    #[test]
+    fn test_dates_render_in_the_zone_they_were_recorded_in() {
+        let mut commit = sample_commit();
+        commit.date_offset = -4 * 60;
+        commit.committer_date = commit.date + chrono::Duration::hours(3);
+        commit.committer_date_offset = 330;
+
+        assert_eq!(
+            commit.local_date().to_rfc3339(),
+            "2024-06-15T08:00:00-04:00"
+        );
+        assert_eq!(
+            commit.local_date_for(DateField::Committer).to_rfc3339(),
+            "2024-06-15T20:30:00+05:30"
+        );
+        assert_eq!(commit.date_for(DateField::Committer), commit.committer_date);
+        assert_eq!(
+            local_time(commit.date, i32::MAX).to_rfc3339(),
+            "2024-06-15T12:00:00+00:00",
+            "a nonsense offset falls back to UTC"
+        );
+    }
+
+    #[test]
+    fn test_to_text_follows_the_message_with_the_pull_request() {
+        let mut commit = sample_commit();
+        commit.pull_request = Some(PullRequest {
+            number: 12,
+            title: "Fix auth race".to_string(),
+            body: "The token cache was read unlocked.\n".to_string(),
+            labels: vec!["bug".to_string()],
+        });
+        assert_eq!(
+            commit.to_text(false),
+            "fix: resolve race condition in auth\nAlice Chen\n\
+             Pull request #12: Fix auth race\nThe token cache was read unlocked."
+        );
+    }
+
+    #[test]
+    fn test_to_text_names_a_tag() {
+        let mut commit = sample_commit();
+        commit.tag = Some("v1.2.0".to_string());
+        commit.message = "Release 1.2.0: faster indexing".to_string();
+        assert_eq!(
+            commit.to_text(false),
+            "Tag v1.2.0\nRelease 1.2.0: faster indexing\nAlice Chen"
+        );
+    }
+
     fn commit_with_summary(diff_summary: &str) -> CommitInfo {
         let mut commit = sample_commit();
         commit.diff_summary = diff_summary.to_string();
         commit
     }
 
+    fn commit_with_files(paths: &[&str], diff_summary: &str) -> CommitInfo {
+        let mut commit = commit_with_summary(diff_summary);
+        commit.files = paths
+            .iter()
+            .map(|path| FileChange::new(*path, ChangeStatus::Modified))
+            .collect();
+        commit
+    }
+
+    fn renamed(from: &str, to: &str) -> FileChange {
+        FileChange {
+            old_path: Some(from.to_string()),
+            ..FileChange::new(to, ChangeStatus::Renamed)
+        }
+    }
+
     #[test]
-    fn test_changed_files_parses_the_files_line() {
-        let commit = commit_with_summary("Files: Cargo.toml, src/auth.rs\n+token");
+    fn test_changed_files_lists_the_recorded_paths() {
+        let commit = commit_with_files(&["src/auth.rs", "Cargo.toml"], "+token");
         assert_eq!(
             commit.changed_files(),
             Some(vec!["Cargo.toml", "src/auth.rs"])
@@ -132,19 +636,31 @@ mod tests {
     }
 
     #[test]
-    fn test_changed_files_handles_a_single_path() {
-        let commit = commit_with_summary("Files: src/main.rs\n+fn main() {}");
-        assert_eq!(commit.changed_files(), Some(vec!["src/main.rs"]));
+    fn test_changed_files_includes_both_sides_of_a_rename() {
+        let mut commit = commit_with_files(&[], "");
+        commit.files.push(renamed("src/login.rs", "src/auth.rs"));
+        assert_eq!(
+            commit.changed_files(),
+            Some(vec!["src/auth.rs", "src/login.rs"])
+        );
     }
 
     #[test]
-    fn test_changed_files_drops_the_truncation_marker() {
-        let commit = commit_with_summary("Files: a.rs, b.rs, ...\n+x");
-        assert_eq!(commit.changed_files(), Some(vec!["a.rs", "b.rs"]));
+    fn test_changed_files_ignores_the_source_of_a_copy() {
+        let mut commit = commit_with_files(&[], "");
+        commit.files.push(FileChange {
+            old_path: Some("template.rs".to_string()),
+            ..FileChange::new("copy.rs", ChangeStatus::Copied)
+        });
+        assert_eq!(commit.changed_files(), Some(vec!["copy.rs"]));
+        assert!(
+            !commit.touches_path("template.rs"),
+            "a copy source was only read"
+        );
     }
 
     #[test]
-    fn test_changed_files_is_none_without_the_prefix() {
+    fn test_changed_files_is_none_without_recorded_paths() {
         let commit = commit_with_summary("+modified src/auth.rs");
         assert_eq!(
             commit.changed_files(),
@@ -158,9 +674,19 @@ mod tests {
         assert_eq!(commit_with_summary("").changed_files(), None);
     }
 
+    #[test]
+    fn test_renames_yields_old_to_new_pairs() {
+        let mut commit = commit_with_files(&["src/main.rs"], "");
+        commit.files.push(renamed("src/login.rs", "src/auth.rs"));
+        assert_eq!(
+            commit.renames().collect::<Vec<_>>(),
+            vec![("src/login.rs", "src/auth.rs")]
+        );
+    }
+
     #[test]
     fn test_touches_path_matches_recorded_paths() {
-        let commit = commit_with_summary("Files: src/auth.rs, src/main.rs\n+x");
+        let commit = commit_with_files(&["src/auth.rs", "src/main.rs"], "+x");
         assert!(commit.touches_path("src/auth.rs"));
         assert!(commit.touches_path("auth"));
         assert!(commit.touches_path("src/"));
@@ -169,7 +695,7 @@ mod tests {
 
     #[test]
     fn test_touches_path_ignores_diff_body_mentions() {
-        let commit = commit_with_summary("Files: src/main.rs\n-use src/auth.rs::Token;");
+        let commit = commit_with_files(&["src/main.rs"], "-use src/auth.rs::Token;");
         assert!(
             !commit.touches_path("src/auth.rs"),
             "a path mentioned in the diff body is not a changed file"
@@ -187,10 +713,95 @@ mod tests {
 
     #[test]
     fn test_to_text_includes_changed_paths_when_diffs_are_indexed() {
-        let commit = commit_with_summary("Files: src/auth.rs\n+token");
+        let commit = commit_with_files(&["src/auth.rs"], "+token");
+        let text = commit.to_text(true);
+        assert!(
+            text.contains("Files: src/auth.rs\n+token"),
+            "paths should reach the embedded text too, ahead of the diff: {text}"
+        );
+        assert!(!commit.to_text(false).contains("src/auth.rs"));
+    }
+
+    #[test]
+    fn test_to_text_lists_symbols_between_paths_and_diff() {
+        let mut commit = commit_with_files(&["src/embed.rs"], "+token");
+        commit.symbols = vec!["TextEncoder".to_string(), "encode_text".to_string()];
+
+        assert!(
+            commit
+                .to_text(true)
+                .ends_with("Files: src/embed.rs\nSymbols: TextEncoder, encode_text\n+token")
+        );
+        assert!(!commit.to_text(false).contains("encode_text"));
+    }
+
+    #[test]
+    fn test_symbols_line_is_empty_without_symbols() {
+        assert_eq!(sample_commit().symbols_line(), "");
+    }
+
+    #[test]
+    fn test_touches_symbol_matches_whole_names_only() {
+        let mut commit = sample_commit();
+        commit.symbols = vec!["encode_text".to_string()];
+
+        assert!(commit.touches_symbol("encode_text"));
+        assert!(!commit.touches_symbol("encode"));
+        assert!(!commit.touches_symbol("Encode_Text"));
+    }
+
+    #[test]
+    fn test_files_line_is_capped_with_a_marker() {
+        let paths: Vec<String> = (0..500).map(|i| format!("src/module_{i:03}.rs")).collect();
+        let refs: Vec<&str> = paths.iter().map(String::as_str).collect();
+        let line = commit_with_files(&refs, "").files_line();
+
+        assert!(line.len() <= MAX_PATH_SECTION + ", ...".len());
+        assert!(line.ends_with(", ..."));
+    }
+
+    #[test]
+    fn test_lift_files_line_moves_paths_out_of_the_summary() {
+        let mut commit = commit_with_summary("Files: Cargo.toml, src/auth.rs, ...\n+token");
+        commit.lift_files_line();
+
+        assert_eq!(
+            commit.changed_files(),
+            Some(vec!["Cargo.toml", "src/auth.rs"]),
+            "the truncation marker is not a path"
+        );
         assert!(
-            commit.to_text(true).contains("src/auth.rs"),
-            "paths should reach the embedded text too"
+            commit
+                .files
+                .iter()
+                .all(|f| f.status == ChangeStatus::Unknown)
         );
+        assert_eq!(commit.diff_summary, "+token");
+    }
+
+    #[test]
+    fn test_lift_files_line_leaves_a_summary_without_one_alone() {
+        let mut commit = commit_with_summary("+modified src/auth.rs");
+        commit.lift_files_line();
+        assert!(commit.files.is_empty());
+        assert_eq!(commit.diff_summary, "+modified src/auth.rs");
+    }
+
+    #[test]
+    fn test_lift_files_line_handles_a_paths_only_summary() {
+        let mut commit = commit_with_summary("Files: src/main.rs");
+        commit.lift_files_line();
+        assert_eq!(commit.changed_files(), Some(vec!["src/main.rs"]));
+        assert!(commit.diff_summary.is_empty());
+    }
+
+    #[test]
+    fn test_lines_changed_sums_both_directions() {
+        let change = FileChange {
+            insertions: 7,
+            deletions: 3,
+            ..FileChange::new("a.rs", ChangeStatus::Modified)
+        };
+        assert_eq!(change.lines_changed(), 10);
     }
 }
@@ -0,0 +1,107 @@
+//! `git notes` attached to commits.
+//!
+//! Review outcomes, deploy records and CI verdicts often live in notes rather
+//! than in the message, because they are only known after the commit exists.
+//! Nothing is read unless a notes ref is configured: notes can be large,
+//! tool-generated, or private to one team.
+
+use git2::{ErrorCode, Oid, Repository};
+use std::collections::BTreeMap;
+
+use super::{GitError, Note};
+
+/// Git config key naming the notes refs to index. Multi-valued; a bare name
+/// such as `review` means `refs/notes/review`.
+pub const NOTES_REF_KEY: &str = "semantic.notesRef";
+
+/// Reads the notes a commit carries under the configured refs.
+#[derive(Default)]
+pub struct NotesReader {
+    refs: Vec<String>,
+}
+
+impl NotesReader {
+    /// A reader for `refs`, each expanded to its full `refs/notes/` name.
+    pub fn new(refs: &[String]) -> Self {
+        let mut refs: Vec<String> = refs.iter().map(|name| full_ref(name)).collect();
+        refs.dedup();
+        Self { refs }
+    }
+
+    /// A reader for every `semantic.notesRef` in the repository's git config.
+    pub fn from_repo(repo: &Repository) -> Result<Self, GitError> {
+        let mut refs = Vec::new();
+        let config = repo.config()?;
+        if let Ok(entries) = config.multivar(NOTES_REF_KEY, None) {
+            entries.for_each(|entry| {
+                if let Ok(value) = entry.value() {
+                    refs.push(value.to_string());
+                }
+            })?;
+        }
+        Ok(Self::new(&refs))
+    }
+
+    /// The notes on `commit`, in configured-ref order. A ref that does not
+    /// exist yet, or has no note for this commit, contributes nothing.
+    pub fn read(&self, repo: &Repository, commit: Oid) -> Result<Vec<Note>, GitError> {
+        let mut notes = Vec::new();
+        for notes_ref in &self.refs {
+            match repo.find_note(Some(notes_ref), commit) {
+                Ok(note) => {
+                    let message = String::from_utf8_lossy(note.message_bytes());
+                    let message = message.trim();
+                    if !message.is_empty() {
+                        notes.push(Note {
+                            notes_ref: notes_ref.clone(),
+                            message: message.to_string(),
+                        });
+                    }
+                }
+                Err(err) if err.code() == ErrorCode::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(notes)
+    }
+
+    /// The commit each configured ref that exists points at, by ref name.
+    /// Adding, editing or removing any note moves its ref's tip.
+    pub fn tips(&self, repo: &Repository) -> Result<BTreeMap<String, String>, GitError> {
+        let mut tips = BTreeMap::new();
+        for notes_ref in &self.refs {
+            match repo.refname_to_id(notes_ref) {
+                Ok(oid) => {
+                    tips.insert(notes_ref.clone(), oid.to_string());
+                }
+                Err(err) if err.code() == ErrorCode::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(tips)
+    }
+}
+
+fn full_ref(name: &str) -> String {
+    let name = name.trim();
+    if name.starts_with("refs/") {
+        name.to_string()
+    } else {
+        format!("refs/notes/{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_names_live_under_refs_notes() {
+        let reader = NotesReader::new(&[
+            "review".to_string(),
+            "refs/notes/deploy".to_string(),
+            "refs/notes/deploy".to_string(),
+        ]);
+        assert_eq!(reader.refs, vec!["refs/notes/review", "refs/notes/deploy"]);
+    }
+}
@@ -1,19 +1,71 @@
-use git2::Repository;
+use git2::{Commit, Oid, Repository};
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 use tracing::debug;
 
-use super::diff::DiffExtractor;
-use super::{CommitInfo, GitError};
+use super::diff::{DiffExtractor, ExtractedDiff, MAX_DIFF_SIZE};
+use super::encoding::decode_message;
+use super::{
+    CommitInfo, DiffPolicy, GitError, IdentityMap, IgnoreRules, IssueExtractor, Note, NotesReader,
+    Redactor, conventional, trailers,
+};
 
 pub struct RepositoryParser {
     repo: Repository,
+    policy: DiffPolicy,
+    ignore: IgnoreRules,
+    redactor: Redactor,
+    identities: IdentityMap,
+    issues: IssueExtractor,
+    notes: NotesReader,
+    max_diff_size: usize,
 }
 
 impl RepositoryParser {
     pub fn new(path: &Path) -> Result<Self, GitError> {
         let repo = Repository::discover(path).map_err(GitError::RepositoryNotFound)?;
+        let policy = DiffPolicy::from_repo(&repo)?;
+        let ignore = IgnoreRules::from_repo(&repo)?;
+        let redactor = Redactor::from_repo(&repo)?;
+        let identities = IdentityMap::from_repo(&repo)?;
+        let issues = IssueExtractor::from_repo(&repo)?;
+        let notes = NotesReader::from_repo(&repo)?;
 
-        Ok(Self { repo })
+        Ok(Self {
+            repo,
+            policy,
+            ignore,
+            redactor,
+            identities,
+            issues,
+            notes,
+            max_diff_size: MAX_DIFF_SIZE,
+        })
+    }
+
+    /// Identifies the `.semanticignore` rules commits are parsed under.
+    pub fn ignore_fingerprint(&self) -> &str {
+        self.ignore.fingerprint()
+    }
+
+    /// Keep up to `bytes` of diff text per commit instead of [`MAX_DIFF_SIZE`].
+    pub fn set_max_diff_size(&mut self, bytes: usize) {
+        self.max_diff_size = bytes;
+    }
+
+    /// Where each configured notes ref points, to tell later whether any
+    /// note has changed since.
+    pub fn notes_tips(&self) -> Result<BTreeMap<String, String>, GitError> {
+        self.notes.tips(&self.repo)
+    }
+
+    /// The notes `hash` carries now, as [`Self::parse_commits`] would read
+    /// them. None for a hash that is not a commit id.
+    pub fn notes_on(&self, hash: &str) -> Result<Vec<Note>, GitError> {
+        match Oid::from_str(hash) {
+            Ok(oid) => self.notes.read(&self.repo, oid),
+            Err(_) => Ok(Vec::new()),
+        }
     }
 
     pub fn parse_commits(&self, include_diffs: bool) -> Result<Vec<CommitInfo>, GitError> {
@@ -26,28 +78,18 @@ impl RepositoryParser {
         for oid in revwalk {
             let oid = oid?;
             let commit = self.repo.find_commit(oid)?;
-
-            let hash = oid.to_string();
-            let author = commit.author().name().unwrap_or("Unknown").to_string();
-            let date =
-                chrono::DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default();
-            let message = commit.message().unwrap_or("").to_string();
-
-            let diff_summary = if include_diffs {
-                DiffExtractor::extract_diff(&self.repo, &commit)?
-            } else {
-                String::new()
+            let Some(info) = self.commit_info(&commit, include_diffs)? else {
+                continue;
             };
 
-            debug!("Parsed commit: {} by {} at {}", &hash[..7], author, date);
+            debug!(
+                "Parsed commit: {} by {} at {}",
+                &info.hash[..7],
+                info.author,
+                info.date
+            );
 
-            commits.push(CommitInfo {
-                hash,
-                author,
-                date,
-                message,
-                diff_summary,
-            });
+            commits.push(info);
         }
 
         Ok(commits)
@@ -75,32 +117,258 @@ impl RepositoryParser {
             }
 
             let commit = self.repo.find_commit(oid)?;
+            commits.extend(self.commit_info(&commit, include_diffs)?);
+        }
+
+        if !found_since {
+            return Err(GitError::CommitNotFound(since_hash.to_string()));
+        }
+
+        Ok(commits)
+    }
+
+    /// Commits reachable from HEAD that are not in `indexed`, newest first.
+    ///
+    /// Each of `boundaries` must be an indexed commit whose whole history was
+    /// indexed with it, so the walk stops there. Anything else reachable is
+    /// checked against `indexed` one by one. Unlike [`Self::parse_commits_since`]
+    /// this does not need HEAD to descend from the last commit indexed, which
+    /// is what lets worktrees on different branches share one index.
+    pub fn parse_unindexed_commits(
+        &self,
+        indexed: &HashSet<&str>,
+        boundaries: &[&str],
+        include_diffs: bool,
+    ) -> Result<Vec<CommitInfo>, GitError> {
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        for boundary in boundaries {
+            // A boundary pruned by gc or never fetched here hides nothing.
+            if let Ok(oid) = Oid::from_str(boundary)
+                && self.repo.find_commit(oid).is_ok()
+            {
+                revwalk.hide(oid)?;
+            }
+        }
+
+        let mut commits = Vec::new();
+        for oid in revwalk {
+            let oid = oid?;
+            if indexed.contains(oid.to_string().as_str()) {
+                continue;
+            }
+            let commit = self.repo.find_commit(oid)?;
+            commits.extend(self.commit_info(&commit, include_diffs)?);
+        }
 
-            let hash = oid.to_string();
-            let author = commit.author().name().unwrap_or("Unknown").to_string();
-            let date =
-                chrono::DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default();
-            let message = commit.message().unwrap_or("").to_string();
+        Ok(commits)
+    }
+
+    /// The full hash of the commit HEAD points at.
+    pub fn head(&self) -> Result<String, GitError> {
+        Ok(self.repo.head()?.peel_to_commit()?.id().to_string())
+    }
+
+    /// Whether `ancestor` is HEAD or part of its history. False for a commit
+    /// this repository does not have, as after a rebase and a gc.
+    pub fn head_descends_from(&self, ancestor: &str) -> Result<bool, GitError> {
+        let head = self.repo.head()?.peel_to_commit()?.id();
+        let Ok(ancestor) = Oid::from_str(ancestor) else {
+            return Ok(false);
+        };
+        if self.repo.find_commit(ancestor).is_err() {
+            return Ok(false);
+        }
+        Ok(head == ancestor || self.repo.graph_descendant_of(head, ancestor)?)
+    }
+
+    /// Full hashes of every commit some ref or worktree HEAD reaches — what
+    /// history still holds once a rebase or force-push has left commits
+    /// behind.
+    pub fn referenced_commits(&self) -> Result<HashSet<String>, GitError> {
+        let mut revwalk = self.repo.revwalk()?;
+        for reference in self.repo.references()? {
+            // A symbolic ref or one naming a blob or tree starts no history.
+            if let Ok(commit) = reference?.peel_to_commit() {
+                revwalk.push(commit.id())?;
+            }
+        }
+
+        // HEAD is per worktree, and a detached one is reached by no ref.
+        let main = Repository::open(self.repo.commondir())?;
+        if let Ok(head) = main.head().and_then(|head| head.peel_to_commit()) {
+            revwalk.push(head.id())?;
+        }
+        for name in main.worktrees()?.iter().flatten().flatten() {
+            let head = main
+                .find_worktree(name)
+                .and_then(|worktree| Repository::open_from_worktree(&worktree))
+                .and_then(|repo| Ok(repo.head()?.peel_to_commit()?.id()));
+            // A worktree whose directory is gone has no HEAD to keep.
+            if let Ok(id) = head {
+                revwalk.push(id)?;
+            }
+        }
+
+        revwalk
+            .map(|oid| Ok(oid?.to_string()))
+            .collect::<Result<_, git2::Error>>()
+            .map_err(GitError::from)
+    }
+
+    /// Every annotated tag that points at a commit, as a document of its own.
+    ///
+    /// Release notes live in tag messages and nowhere else. Each becomes an
+    /// entry whose `hash` is the tagged commit, so a hit leads straight to the
+    /// code. Lightweight tags carry no text and are skipped.
+    pub fn parse_tags(&self) -> Result<Vec<CommitInfo>, GitError> {
+        let mut tags = Vec::new();
 
-            let diff_summary = if include_diffs {
-                DiffExtractor::extract_diff(&self.repo, &commit)?
-            } else {
-                String::new()
+        for reference in self.repo.references_glob("refs/tags/*")? {
+            let reference = reference?;
+            let Ok(tag) = reference.peel_to_tag() else {
+                continue;
+            };
+            let Ok(target) = tag.target().and_then(|object| object.peel_to_commit()) else {
+                continue;
             };
 
-            commits.push(CommitInfo {
-                hash,
-                author,
-                date,
+            let decoded = decode_message(tag.message_bytes().unwrap_or_default(), None);
+            let message = decoded.text.trim_end().to_string();
+            let tagger = tag.tagger();
+            let when = tagger.as_ref().map_or_else(|| target.time(), |t| t.when());
+            let mut info = CommitInfo {
+                hash: target.id().to_string(),
+                author: tagger
+                    .as_ref()
+                    .and_then(|t| t.name().ok())
+                    .unwrap_or("Unknown")
+                    .to_string(),
+                author_email: tagger
+                    .as_ref()
+                    .and_then(|t| t.email().ok())
+                    .unwrap_or("")
+                    .to_string(),
+                date: timestamp(when),
+                date_offset: when.offset_minutes(),
+                // A tag has no committer; its tagger applied it.
+                committer_date: timestamp(when),
+                committer_date_offset: when.offset_minutes(),
+                issues: self.issues.extract(&message),
                 message,
-                diff_summary,
-            });
+                tag: Some(String::from_utf8_lossy(tag.name_bytes()).into_owned()),
+                non_utf8: decoded.non_utf8,
+                ..Default::default()
+            };
+            self.identities.canonicalize(&mut info);
+            tags.push(info);
         }
 
-        if !found_since {
-            return Err(GitError::CommitNotFound(since_hash.to_string()));
+        // Newest first, like the commit walk.
+        tags.sort_by_key(|tag| std::cmp::Reverse(tag.date));
+        Ok(tags)
+    }
+
+    /// Everything the index records about one commit, or `None` when
+    /// `.semanticignore` leaves it out. Shared by the full and incremental
+    /// walks so the two can never disagree on what a commit holds.
+    fn commit_info(
+        &self,
+        commit: &Commit,
+        include_diffs: bool,
+    ) -> Result<Option<CommitInfo>, GitError> {
+        let hash = commit.id().to_string();
+        let author = commit.author();
+        let committer = commit.committer();
+        let decoded = decode_message(
+            commit.message_bytes(),
+            commit.message_encoding().ok().flatten(),
+        );
+        let message = decoded.text.into_owned();
+
+        if self.is_ignored(commit, &message, &author)? {
+            debug!("Skipping ignored commit {}", &hash[..7]);
+            return Ok(None);
         }
 
-        Ok(commits)
+        let ExtractedDiff {
+            summary: diff_summary,
+            files,
+            symbols,
+            redactions,
+            non_utf8: diff_non_utf8,
+        } = if include_diffs {
+            DiffExtractor::extract_diff(
+                &self.repo,
+                commit,
+                &self.policy,
+                &self.ignore,
+                &self.redactor,
+                self.max_diff_size,
+            )?
+        } else {
+            ExtractedDiff::default()
+        };
+
+        let mut info = CommitInfo {
+            hash,
+            author: author.name().unwrap_or("Unknown").to_string(),
+            author_email: author.email().unwrap_or("").to_string(),
+            // The author date, which is what `git log` shows. `commit.time()`
+            // is the committer's, and moves on every rebase.
+            date: timestamp(author.when()),
+            date_offset: author.when().offset_minutes(),
+            committer: committer.name().unwrap_or("Unknown").to_string(),
+            committer_email: committer.email().unwrap_or("").to_string(),
+            committer_date: timestamp(committer.when()),
+            committer_date_offset: committer.when().offset_minutes(),
+            trailers: trailers::parse(&message),
+            conventional: conventional::parse(&message),
+            issues: self.issues.extract(&message),
+            notes: self.notes.read(&self.repo, commit.id())?,
+            message,
+            diff_summary,
+            files,
+            symbols,
+            redactions,
+            non_utf8: decoded.non_utf8 || diff_non_utf8,
+            ..Default::default()
+        };
+        self.identities.canonicalize(&mut info);
+        Ok(Some(info))
     }
+
+    /// Whether a commit rule matches, or every path the commit changes is
+    /// ignored. A commit that changes nothing, like most merges, is kept.
+    ///
+    /// `author:` rules see both the recorded author and the one `.mailmap`
+    /// maps it to, as `--author` does.
+    fn is_ignored(
+        &self,
+        commit: &Commit,
+        message: &str,
+        author: &git2::Signature,
+    ) -> Result<bool, GitError> {
+        let (name, email) = (author.name().unwrap_or(""), author.email().unwrap_or(""));
+        let canonical = self.identities.resolve(name, email);
+        let ignored = [
+            format!("{name} <{email}>"),
+            format!("{} <{}>", canonical.name, canonical.email),
+        ]
+        .iter()
+        .any(|signature| self.ignore.ignores_commit(message, signature));
+        if ignored {
+            return Ok(true);
+        }
+        if !self.ignore.has_path_rules() {
+            return Ok(false);
+        }
+        let paths = DiffExtractor::changed_paths(&self.repo, commit)?;
+        Ok(!paths.is_empty() && paths.iter().all(|path| self.ignore.ignores_path(path)))
+    }
+}
+
+fn timestamp(time: git2::Time) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp(time.seconds(), 0).unwrap_or_default()
 }
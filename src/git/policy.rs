@@ -0,0 +1,169 @@
+use git2::{AttrCheckFlags, AttrValue, Repository};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+use super::GitError;
+
+/// Git config key holding extra paths whose content is left out of the
+/// indexed diff. Multi-valued: `git config --add semantic.diffIgnore '*.snap'`.
+pub const DIFF_IGNORE_KEY: &str = "semantic.diffIgnore";
+
+/// Machine-written files that change in bulk and say nothing about intent.
+/// A one-line dependency bump rewrites hundreds of lockfile lines, which used
+/// to spend the whole diff budget before any source file was reached.
+pub const DEFAULT_DIFF_IGNORE: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "flake.lock",
+    "*.min.js",
+    "*.min.css",
+];
+
+/// Decides which files' content is worth spending the diff budget on.
+///
+/// An excluded file is still recorded — path, status and line counts — so
+/// `--file` and the change filters see it. Only its `+`/`-` lines are dropped.
+pub struct DiffPolicy {
+    ignore: GlobSet,
+}
+
+impl DiffPolicy {
+    /// The built-in list plus `extra`, in gitignore-style glob syntax: a
+    /// pattern without a `/` matches the file name at any depth, one with a
+    /// `/` is anchored at the repository root.
+    pub fn new(extra: &[String]) -> Result<Self, GitError> {
+        let mut builder = GlobSetBuilder::new();
+
+        let patterns = DEFAULT_DIFF_IGNORE
+            .iter()
+            .copied()
+            .chain(extra.iter().map(String::as_str));
+        for pattern in patterns {
+            let glob = GlobBuilder::new(&anchored(pattern))
+                .literal_separator(true)
+                .build()
+                .map_err(|e| GitError::InvalidIgnorePattern(pattern.to_string(), e))?;
+            builder.add(glob);
+        }
+
+        let ignore = builder
+            .build()
+            .map_err(|e| GitError::InvalidIgnorePattern(extra.join(", "), e))?;
+        Ok(Self { ignore })
+    }
+
+    /// The built-in list plus every `semantic.diffIgnore` value in the
+    /// repository's git config.
+    pub fn from_repo(repo: &Repository) -> Result<Self, GitError> {
+        let mut extra = Vec::new();
+        let config = repo.config()?;
+        if let Ok(entries) = config.multivar(DIFF_IGNORE_KEY, None) {
+            entries.for_each(|entry| {
+                if let Ok(value) = entry.value() {
+                    extra.push(value.to_string());
+                }
+            })?;
+        }
+        Self::new(&extra)
+    }
+
+    /// Whether `path`'s content should stay out of the summary.
+    ///
+    /// Besides the ignore list this honours `.gitattributes`: files marked
+    /// `linguist-generated`, and files git itself would not diff (`-diff`, or
+    /// the `binary` macro, which implies it). Attributes are read from the
+    /// current checkout, not from each historical commit — a file marked
+    /// generated today was generated then too.
+    pub fn excludes(&self, repo: &Repository, path: &Path) -> Result<bool, GitError> {
+        if self.ignore.is_match(path) {
+            return Ok(true);
+        }
+
+        let flags = AttrCheckFlags::default();
+        let generated = AttrValue::from_string(repo.get_attr(path, "linguist-generated", flags)?);
+        if matches!(generated, AttrValue::True)
+            || matches!(generated, AttrValue::String(v) if v.eq_ignore_ascii_case("true"))
+        {
+            return Ok(true);
+        }
+
+        let diff = AttrValue::from_string(repo.get_attr(path, "diff", flags)?);
+        Ok(matches!(diff, AttrValue::False))
+    }
+}
+
+/// `pattern` as a glob over repository-relative paths, anchored the way
+/// `.gitignore` anchors it: at the root if it has a `/`, at any depth if not.
+pub fn anchored(pattern: &str) -> String {
+    match pattern.strip_prefix('/') {
+        Some(rooted) => rooted.to_string(),
+        None if pattern.contains('/') => pattern.to_string(),
+        None => format!("**/{pattern}"),
+    }
+}
+
+impl Default for DiffPolicy {
+    fn default() -> Self {
+        Self::new(&[]).expect("built-in ignore patterns are valid globs")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ignored(policy: &DiffPolicy, path: &str) -> bool {
+        policy.ignore.is_match(Path::new(path))
+    }
+
+    #[test]
+    fn lockfiles_are_ignored_at_any_depth() {
+        let policy = DiffPolicy::default();
+        assert!(ignored(&policy, "Cargo.lock"));
+        assert!(ignored(&policy, "web/package-lock.json"));
+        assert!(ignored(&policy, "assets/app.min.js"));
+    }
+
+    #[test]
+    fn source_files_are_not_ignored() {
+        let policy = DiffPolicy::default();
+        assert!(!ignored(&policy, "src/lock.rs"));
+        assert!(!ignored(&policy, "Cargo.toml"));
+        assert!(!ignored(&policy, "docs/Cargo.lock.md"));
+    }
+
+    #[test]
+    fn patterns_with_a_slash_are_anchored_at_the_root() {
+        let policy = DiffPolicy::new(&["vendor/*".to_string()]).unwrap();
+        assert!(ignored(&policy, "vendor/lib.c"));
+        assert!(!ignored(&policy, "src/vendor/lib.c"));
+        assert!(
+            !ignored(&policy, "vendor/nested/lib.c"),
+            "a single star stops at a separator, as in .gitignore"
+        );
+    }
+
+    #[test]
+    fn a_leading_slash_anchors_a_bare_name() {
+        let policy = DiffPolicy::new(&["/schema.json".to_string()]).unwrap();
+        assert!(ignored(&policy, "schema.json"));
+        assert!(!ignored(&policy, "api/schema.json"));
+    }
+
+    #[test]
+    fn an_invalid_pattern_names_itself_in_the_error() {
+        let err = DiffPolicy::new(&["src/[".to_string()]).err().unwrap();
+        assert!(matches!(err, GitError::InvalidIgnorePattern(ref p, _) if p == "src/["));
+        assert_eq!(err.code(), "E2006");
+    }
+}
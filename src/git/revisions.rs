@@ -0,0 +1,65 @@
+//! Revision arguments, read the way `git log` reads them.
+
+use std::collections::HashSet;
+
+use git2::{Object, Oid, Repository, RevparseMode};
+
+use super::GitError;
+
+/// Full ids of the commits `specs` select, as `git log <specs>` would list
+/// them.
+///
+/// Each spec is a ref or commit, `A..B` (reachable from B but not A),
+/// `A...B` (from either but not both), or `^A` to leave out everything
+/// reachable from A. A range missing a side, like `main..`, has HEAD there.
+/// With only `^` exclusions, the walk starts at HEAD, so `^main` means "on
+/// this branch but not main".
+pub fn reachable(repo: &Repository, specs: &[String]) -> Result<HashSet<String>, GitError> {
+    let mut walk = repo.revwalk().map_err(GitError::RevwalkFailed)?;
+    let mut included = false;
+
+    for spec in specs {
+        let invalid = |err: git2::Error| GitError::InvalidRevision(spec.clone(), err);
+
+        if let Some(excluded) = spec.strip_prefix('^') {
+            let id = commit_id(&repo.revparse_single(excluded).map_err(invalid)?);
+            walk.hide(id.map_err(invalid)?).map_err(invalid)?;
+            continue;
+        }
+
+        let revspec = repo.revparse(spec).map_err(invalid)?;
+        let side = |object: Option<&Object>| match object {
+            Some(object) => commit_id(object).map_err(invalid),
+            None => Err(invalid(git2::Error::from_str(
+                "the range is missing an end",
+            ))),
+        };
+        let mode = revspec.mode();
+        if mode.contains(RevparseMode::SINGLE) {
+            walk.push(side(revspec.from())?).map_err(invalid)?;
+        } else if mode.contains(RevparseMode::MERGE_BASE) {
+            let (from, to) = (side(revspec.from())?, side(revspec.to())?);
+            walk.push(from).map_err(invalid)?;
+            walk.push(to).map_err(invalid)?;
+            walk.hide(repo.merge_base(from, to).map_err(invalid)?)
+                .map_err(invalid)?;
+        } else {
+            walk.hide(side(revspec.from())?).map_err(invalid)?;
+            walk.push(side(revspec.to())?).map_err(invalid)?;
+        }
+        included = true;
+    }
+
+    if !included {
+        walk.push_head().map_err(|_| GitError::NoHead)?;
+    }
+
+    walk.map(|id| id.map(|id| id.to_string()))
+        .collect::<Result<_, _>>()
+        .map_err(GitError::RevwalkFailed)
+}
+
+/// The commit `object` names, looking through annotated tags.
+fn commit_id(object: &Object) -> Result<Oid, git2::Error> {
+    object.peel_to_commit().map(|commit| commit.id())
+}
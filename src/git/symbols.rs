@@ -0,0 +1,206 @@
+//! Names of the functions and types a diff touched.
+//!
+//! libgit2 writes the enclosing definition after a hunk's line ranges, the same
+//! context `git diff` shows: `@@ -40,3 +40,4 @@ pub fn encode_text(&self) {`.
+//! Which line counts as a definition comes from the file's diff driver — a
+//! `diff=rust` or `diff=python` attribute picks a language-aware pattern, and
+//! without one git takes the nearest unindented line. Either way the header
+//! holds a whole source line; this module reduces it to the name.
+
+/// Words that introduce or qualify a definition, or open a block that is not
+/// one. Never a symbol name themselves.
+const KEYWORDS: &[&str] = &[
+    "abstract",
+    "async",
+    "class",
+    "const",
+    "def",
+    "else",
+    "enum",
+    "export",
+    "extern",
+    "final",
+    "fn",
+    "for",
+    "func",
+    "function",
+    "if",
+    "impl",
+    "interface",
+    "let",
+    "match",
+    "mod",
+    "module",
+    "mut",
+    "override",
+    "private",
+    "protected",
+    "pub",
+    "public",
+    "return",
+    "static",
+    "struct",
+    "switch",
+    "trait",
+    "type",
+    "unsafe",
+    "var",
+    "virtual",
+    "void",
+    "where",
+    "while",
+];
+
+/// The definition name in a hunk header, if it has one.
+///
+/// A call-shaped header names whatever precedes its first parenthesis —
+/// `def parse(self):`, `int Foo::bar(int x)`, or Go's
+/// `func (s *Server) Handle(w http.ResponseWriter)`, where the receiver's
+/// parenthesis follows a keyword and is skipped. Type parameters between the
+/// name and the parenthesis, as in `fn map<T>(`, are passed over. A header without one names
+/// its last word: `impl Display for Commit {`, `class Parser:`.
+pub fn from_hunk_header(header: &str) -> Option<String> {
+    let context = header.splitn(3, "@@").nth(2)?.trim();
+    if context.is_empty() {
+        return None;
+    }
+
+    for (open, _) in context.match_indices('(') {
+        if let Some(name) = last_identifier(&strip_generics(&context[..open]))
+            && !is_keyword(name)
+        {
+            return Some(name.to_string());
+        }
+    }
+
+    if context.contains('(') {
+        return None;
+    }
+    let without_generics = strip_generics(context);
+    let name = last_identifier(without_generics.trim_end_matches(['{', ':', ' ', '\t']))?;
+    (!is_keyword(name)).then(|| name.to_string())
+}
+
+/// The identifier that `text` ends with, ignoring trailing whitespace.
+fn last_identifier(text: &str) -> Option<&str> {
+    let text = text.trim_end();
+    let start = text
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_identifier_char(c))
+        .last()
+        .map(|(i, _)| i)?;
+    let name = &text[start..];
+    name.starts_with(|c: char| !c.is_ascii_digit())
+        .then_some(name)
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn is_keyword(word: &str) -> bool {
+    KEYWORDS.contains(&word)
+}
+
+/// Drop everything between angle brackets, so `impl<T> Index<T> {` ends in
+/// `Index` rather than the type parameter, and `fn map<T>` in `map`.
+fn strip_generics(text: &str) -> String {
+    let mut depth = 0usize;
+    text.chars()
+        .filter(|&c| match c {
+            '<' => {
+                depth += 1;
+                false
+            }
+            '>' if depth > 0 => {
+                depth -= 1;
+                false
+            }
+            _ => depth == 0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(context: &str) -> Option<String> {
+        from_hunk_header(&format!("@@ -10,3 +10,4 @@ {context}\n"))
+    }
+
+    #[test]
+    fn rust_functions_are_named_by_their_identifier() {
+        assert_eq!(
+            symbol("pub fn encode_text(&self, text: &str) -> Result<Vec<f32>, Error> {"),
+            Some("encode_text".to_string())
+        );
+        assert_eq!(
+            symbol("    pub(crate) async fn load(path: &Path) {"),
+            Some("load".to_string()),
+            "the visibility parenthesis follows a keyword and is skipped"
+        );
+    }
+
+    #[test]
+    fn type_definitions_are_named_by_their_last_word() {
+        assert_eq!(symbol("impl SearchEngine {"), Some("SearchEngine".into()));
+        assert_eq!(symbol("impl<T> Index<T> {"), Some("Index".into()));
+        assert_eq!(
+            symbol("impl fmt::Display for CommitInfo {"),
+            Some("CommitInfo".into())
+        );
+        assert_eq!(symbol("pub struct Config {"), Some("Config".into()));
+        assert_eq!(symbol("class Parser:"), Some("Parser".into()));
+    }
+
+    #[test]
+    fn other_languages_resolve_to_the_called_name() {
+        assert_eq!(symbol("def parse(self, text):"), Some("parse".into()));
+        assert_eq!(
+            symbol("function renderList(items) {"),
+            Some("renderList".into())
+        );
+        assert_eq!(symbol("int Foo::bar(int x)"), Some("bar".into()));
+        assert_eq!(
+            symbol("func (s *Server) Handle(w http.ResponseWriter) {"),
+            Some("Handle".into())
+        );
+        assert_eq!(symbol("class Cache(Base):"), Some("Cache".into()));
+    }
+
+    #[test]
+    fn generic_functions_are_named_past_their_type_parameters() {
+        assert_eq!(
+            symbol("pub fn encode_text<T: AsRef<str>>(&self, t: T) -> Vec<f32> {"),
+            Some("encode_text".into())
+        );
+        assert_eq!(
+            symbol("pub(crate) fn load<P>(path: P) {"),
+            Some("load".into())
+        );
+        assert_eq!(
+            symbol("function map<T>(xs: T[]): T[] {"),
+            Some("map".into())
+        );
+        assert_eq!(
+            symbol("template <typename T> T clamp(T value, T lo, T hi)"),
+            Some("clamp".into())
+        );
+    }
+
+    #[test]
+    fn headers_without_a_definition_yield_nothing() {
+        assert_eq!(from_hunk_header("@@ -1,2 +1,3 @@\n"), None);
+        assert_eq!(symbol(""), None);
+        assert_eq!(symbol("}"), None);
+        assert_eq!(symbol("if (ready) {"), None);
+        assert_eq!(symbol("impl {"), None);
+    }
+
+    #[test]
+    fn a_name_cannot_start_with_a_digit() {
+        assert_eq!(symbol("42"), None);
+    }
+}
@@ -0,0 +1,201 @@
+//! `Key: value` lines at the end of a commit message.
+
+use super::{Identity, Trailer};
+
+/// Lines git itself appends, whose presence marks a paragraph as a trailer
+/// block even when some of its other lines are not trailers.
+const GIT_GENERATED_PREFIXES: &[&str] = &["Signed-off-by: ", "(cherry picked from commit "];
+
+/// The trailers in `message`'s final paragraph, following git's rules.
+///
+/// The subject is never a trailer block, however it is shaped — `fix: typo`
+/// would otherwise parse as key `fix`. The final paragraph counts when every
+/// line is a trailer or an indented continuation of one, or, like
+/// `git interpret-trailers`, when at least a quarter are and one of them is a
+/// line git generates itself. Continuations are folded into the value with a
+/// single space.
+pub fn parse(message: &str) -> Vec<Trailer> {
+    let paragraphs: Vec<&str> = message
+        .trim_end()
+        .split("\n\n")
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .collect();
+    let [_, .., block] = paragraphs.as_slice() else {
+        return Vec::new();
+    };
+
+    let mut trailers: Vec<Trailer> = Vec::new();
+    let mut other_lines = 0;
+    let mut git_generated = false;
+    for line in block.lines() {
+        if line.starts_with([' ', '\t']) && !trailers.is_empty() {
+            let last = trailers.last_mut().expect("checked non-empty");
+            last.value.push(' ');
+            last.value.push_str(line.trim());
+            continue;
+        }
+        git_generated |= GIT_GENERATED_PREFIXES
+            .iter()
+            .any(|prefix| line.starts_with(prefix));
+        match split_trailer(line) {
+            Some(trailer) => trailers.push(trailer),
+            None => other_lines += 1,
+        }
+    }
+
+    let all = other_lines == 0;
+    let enough = git_generated && trailers.len() * 3 >= other_lines;
+    if all || enough { trailers } else { Vec::new() }
+}
+
+/// `Key: value`, where the key is a single token of letters, digits and
+/// hyphens directly followed by the colon.
+fn split_trailer(line: &str) -> Option<Trailer> {
+    let (key, value) = line.split_once(':')?;
+    let is_token = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    is_token.then(|| Trailer {
+        key: key.to_string(),
+        value: value.trim().to_string(),
+    })
+}
+
+/// People credited with `Co-authored-by:` lines, in the order they appear.
+///
+/// Matched case-insensitively anywhere in the message rather than only in the
+/// final paragraph: squash merges routinely bury the trailers of every squashed
+/// commit mid-body, and those co-authors are just as real.
+pub fn co_authors(message: &str) -> Vec<Identity> {
+    message
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case("co-authored-by")
+                .then(|| parse_identity(value))
+                .flatten()
+        })
+        .collect()
+}
+
+/// `Name <email>` as git writes it. A bare name, or a bare address, is kept
+/// with the other half empty.
+pub fn parse_identity(raw: &str) -> Option<Identity> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+
+    match (raw.find('<'), raw.rfind('>')) {
+        (Some(open), Some(close)) if open < close => Some(Identity {
+            name: raw[..open].trim().to_string(),
+            email: raw[open + 1..close].trim().to_string(),
+        }),
+        _ if raw.contains('@') && !raw.contains(' ') => Some(Identity {
+            name: String::new(),
+            email: raw.to_string(),
+        }),
+        _ => Some(Identity {
+            name: raw.to_string(),
+            email: String::new(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(name: &str, email: &str) -> Identity {
+        Identity {
+            name: name.to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    fn trailer(key: &str, value: &str) -> Trailer {
+        Trailer {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn the_final_paragraph_is_parsed_as_trailers() {
+        let message = "fix: reject expired tokens\n\nThe check ran after the cache.\n\n\
+                       Fixes: #123\n\
+                       Reviewed-by: Bob Smith <bob@example.com>\n\
+                       Change-Id: I8f3a2b\n";
+        assert_eq!(
+            parse(message),
+            vec![
+                trailer("Fixes", "#123"),
+                trailer("Reviewed-by", "Bob Smith <bob@example.com>"),
+                trailer("Change-Id", "I8f3a2b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_subject_alone_has_no_trailers() {
+        assert!(parse("fix: typo in README").is_empty());
+        assert!(parse("fix: typo\n").is_empty());
+    }
+
+    #[test]
+    fn a_prose_paragraph_is_not_a_trailer_block() {
+        let message = "feat: cache\n\nNote: this is slow.\nIt gets faster later.";
+        assert!(parse(message).is_empty());
+    }
+
+    #[test]
+    fn continuation_lines_are_folded_into_the_value() {
+        let message = "fix: x\n\nBug: a long\n  description here\nAcked-by: Carol";
+        assert_eq!(
+            parse(message),
+            vec![
+                trailer("Bug", "a long description here"),
+                trailer("Acked-by", "Carol"),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_signed_off_block_tolerates_stray_lines() {
+        let message = "fix: x\n\n[backported to 1.4]\nSigned-off-by: Alice <a@example.com>";
+        assert_eq!(
+            parse(message),
+            vec![trailer("Signed-off-by", "Alice <a@example.com>")]
+        );
+    }
+
+    #[test]
+    fn co_authors_are_read_from_trailers() {
+        let message = "feat: pair on the parser\n\nBody text.\n\n\
+                       Co-authored-by: Bob Smith <bob@example.com>\n\
+                       co-authored-by: Carol <carol@example.com>\n";
+        assert_eq!(
+            co_authors(message),
+            vec![
+                identity("Bob Smith", "bob@example.com"),
+                identity("Carol", "carol@example.com"),
+            ]
+        );
+    }
+
+    #[test]
+    fn other_trailers_and_prose_are_ignored() {
+        let message = "fix: thing\n\nSigned-off-by: Alice <a@example.com>\n\
+                       We co-authored this by accident: no trailer here";
+        assert!(co_authors(message).is_empty());
+    }
+
+    #[test]
+    fn identities_without_brackets_keep_what_they_have() {
+        assert_eq!(parse_identity("Dana"), Some(identity("Dana", "")));
+        assert_eq!(
+            parse_identity("dana@example.com"),
+            Some(identity("", "dana@example.com"))
+        );
+        assert_eq!(parse_identity("   "), None);
+    }
+}
@@ -91,6 +91,9 @@ pub(crate) fn fingerprint(index: &SemanticIndex) -> u64 {
     feed(index.model_version.as_bytes());
     feed(index.last_commit.as_bytes());
     feed(&[index.metadata.include_diffs as u8]);
+    // Every rebuild or in-place edit, such as attaching pull requests,
+    // stamps a new time, which the embedding probes below could miss.
+    feed(&index.metadata.updated_at.timestamp_micros().to_le_bytes());
 
     // Guard against same-count edits (a rebase that swaps one commit for
     // another) by folding in the first and last commit hashes.
@@ -146,6 +149,7 @@ mod tests {
                     .with_timezone(&chrono::Utc),
                 message: format!("commit {hash}"),
                 diff_summary: String::new(),
+                ..Default::default()
             },
             embedding: (0..32).map(|i| (i as f32 * seed).sin()).collect(),
         }
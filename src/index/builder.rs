@@ -1,10 +1,11 @@
 use chrono::Utc;
+use std::collections::BTreeMap;
 use tracing::debug;
 
 use crate::embedding::ModelManager;
-use crate::git::CommitInfo;
+use crate::git::{CommitInfo, MAX_DIFF_SIZE};
 
-use super::{IndexEntry, IndexError, SemanticIndex};
+use super::{EmbeddingCache, IndexEntry, IndexError, SemanticIndex};
 
 pub struct IndexBuilder {
     entries: Vec<IndexEntry>,
@@ -12,7 +13,12 @@ pub struct IndexBuilder {
     model_version: String,
     last_commit: Option<String>,
     include_diffs: bool,
+    ignore_fingerprint: String,
+    max_diff_size: usize,
+    notes_tips: BTreeMap<String, String>,
     created_at: Option<chrono::DateTime<Utc>>,
+    cache: Option<EmbeddingCache>,
+    cache_hits: usize,
 }
 
 impl IndexBuilder {
@@ -26,7 +32,12 @@ impl IndexBuilder {
             model_version,
             last_commit: None,
             include_diffs,
+            ignore_fingerprint: String::new(),
+            max_diff_size: MAX_DIFF_SIZE,
+            notes_tips: BTreeMap::new(),
             created_at: None,
+            cache: None,
+            cache_hits: 0,
         })
     }
 
@@ -45,7 +56,12 @@ impl IndexBuilder {
             model_version,
             last_commit: Some(index.last_commit),
             include_diffs,
+            ignore_fingerprint: index.metadata.ignore_fingerprint,
+            max_diff_size: index.metadata.max_diff_size,
+            notes_tips: index.metadata.notes_tips,
             created_at,
+            cache: None,
+            cache_hits: 0,
         })
     }
 
@@ -54,17 +70,47 @@ impl IndexBuilder {
         self.last_commit = Some(hash);
     }
 
+    /// Record the `.semanticignore` rules the commits were parsed under.
+    pub fn set_ignore_fingerprint(&mut self, fingerprint: String) {
+        self.ignore_fingerprint = fingerprint;
+    }
+
+    /// Record the diff budget, in bytes, the commits were parsed with.
+    pub fn set_max_diff_size(&mut self, bytes: usize) {
+        self.max_diff_size = bytes;
+    }
+
+    /// Record where the notes refs pointed when the commits' notes were read.
+    pub fn set_notes_tips(&mut self, tips: BTreeMap<String, String>) {
+        self.notes_tips = tips;
+    }
+
+    /// Consult `cache` before the model, and fill it with what the model
+    /// computes.
+    pub fn set_cache(&mut self, cache: EmbeddingCache) {
+        self.cache = Some(cache);
+    }
+
+    /// Commits whose embedding came from the cache rather than the model.
+    pub fn cache_hits(&self) -> usize {
+        self.cache_hits
+    }
+
     pub fn add_commit(&mut self, commit: CommitInfo) -> Result<(), IndexError> {
-        if self.entries.iter().any(|e| e.commit.hash == commit.hash) {
+        // A tag shares its target's hash, so the tag name is part of what
+        // makes an entry unique.
+        if self
+            .entries
+            .iter()
+            .any(|e| e.commit.hash == commit.hash && e.commit.tag == commit.tag)
+        {
             debug!("Commit {} already indexed, skipping", &commit.hash[..7]);
             return Ok(());
         }
 
         debug!("Adding commit: {}", &commit.hash[..7]);
 
-        let text = commit.to_text(self.include_diffs);
-        let embedding_array = self.model_manager.encode_text(&text)?;
-        let embedding = embedding_array.to_vec();
+        let embedding = self.embed(&commit)?;
 
         let entry = IndexEntry { commit, embedding };
         self.entries.push(entry);
@@ -72,12 +118,55 @@ impl IndexBuilder {
         Ok(())
     }
 
+    /// Recompute the embedding of the entry at `position`, after something
+    /// that feeds its text — an imported pull request, a new note — changed.
+    pub fn reembed(&mut self, position: usize) -> Result<(), IndexError> {
+        let commit = self.entries[position].commit.clone();
+        self.entries[position].embedding = self.embed(&commit)?;
+        Ok(())
+    }
+
+    /// The embedding of `commit`'s text, from the cache when it has one.
+    ///
+    /// Tags are never cached: one shares its hash with the commit it points
+    /// at, and the two would keep evicting each other.
+    fn embed(&mut self, commit: &CommitInfo) -> Result<Vec<f32>, IndexError> {
+        let text = commit.to_text(self.include_diffs);
+        let cache = self.cache.as_ref().filter(|_| !commit.is_tag());
+
+        if let Some(cache) = cache
+            && let Some(embedding) =
+                cache.get(&self.model_version, self.include_diffs, &commit.hash, &text)
+        {
+            self.cache_hits += 1;
+            return Ok(embedding);
+        }
+
+        let embedding = self.model_manager.encode_text(&text)?.to_vec();
+        if let Some(cache) = cache
+            && let Err(err) = cache.put(
+                &self.model_version,
+                self.include_diffs,
+                &commit.hash,
+                &text,
+                &embedding,
+            )
+        {
+            // A full disk or read-only data dir costs speed, not the index.
+            debug!("could not cache embedding for {}: {err}", &commit.hash[..7]);
+        }
+        Ok(embedding)
+    }
+
     pub fn build(self) -> SemanticIndex {
         let last_commit = self.last_commit.unwrap_or_else(|| "unknown".to_string());
 
         let mut index = SemanticIndex::new(self.model_version, last_commit, self.include_diffs);
         index.entries = self.entries;
         index.metadata.total_commits = index.entries.len();
+        index.metadata.ignore_fingerprint = self.ignore_fingerprint;
+        index.metadata.max_diff_size = self.max_diff_size;
+        index.metadata.notes_tips = self.notes_tips;
         index.metadata.updated_at = Utc::now();
         if let Some(created_at) = self.created_at {
             index.metadata.created_at = created_at;
@@ -0,0 +1,358 @@
+//! Machine-wide embedding cache, shared by every clone of a repository.
+//!
+//! CI workspaces, forks and fresh clones all embed the same commits. A commit
+//! hash names the same content everywhere, so an embedding computed once can
+//! be reused by any clone on the machine that indexes with the same model and
+//! mode.
+//!
+//! The hash alone does not pin the text the model saw: notes, redaction rules
+//! and imported pull requests are per-clone. Each entry therefore stores a
+//! digest of its text, and a mismatch is a miss that the new embedding
+//! overwrites.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use directories::ProjectDirs;
+use sha2::{Digest, Sha256};
+
+use super::IndexError;
+use crate::embedding::EmbeddingError;
+
+/// Git config key turning the cache on. Off unless set, usually globally:
+/// `git config --global semantic.embeddingCache true`.
+pub const EMBEDDING_CACHE_KEY: &str = "semantic.embeddingCache";
+
+/// Git config key capping the cache's size, with git's `k`, `m` and `g`
+/// suffixes.
+pub const EMBEDDING_CACHE_SIZE_KEY: &str = "semantic.embeddingCacheSize";
+
+/// Cap when none is configured: room for several hundred thousand commits.
+pub const DEFAULT_CACHE_SIZE: u64 = 1024 * 1024 * 1024;
+
+const DIGEST_LEN: usize = 32;
+
+/// One directory of cached embeddings, and the size it is kept under.
+#[derive(Debug, Clone)]
+pub struct EmbeddingCache {
+    root: PathBuf,
+    max_bytes: u64,
+}
+
+/// What [`EmbeddingCache::prune`] removed and left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    pub removed: usize,
+    pub freed_bytes: u64,
+    pub kept: usize,
+    pub kept_bytes: u64,
+}
+
+impl EmbeddingCache {
+    /// A cache in `root`, pruned to `max_bytes`.
+    pub fn new(root: PathBuf, max_bytes: u64) -> Self {
+        Self { root, max_bytes }
+    }
+
+    /// The cache in the user's data directory, beside the downloaded model.
+    pub fn in_data_dir(max_bytes: u64) -> Result<Self, IndexError> {
+        let dirs = ProjectDirs::from("com", "git-semantic", "git-semantic")
+            .ok_or(EmbeddingError::ProjectDirsNotFound)?;
+        Ok(Self::new(dirs.data_dir().join("embeddings"), max_bytes))
+    }
+
+    /// The cache `config` asks for, or `None` when it is not turned on.
+    pub fn from_config(config: &git2::Config) -> Result<Option<Self>, IndexError> {
+        if !config.get_bool(EMBEDDING_CACHE_KEY).unwrap_or(false) {
+            return Ok(None);
+        }
+        Self::in_data_dir(configured_size(config)).map(Some)
+    }
+
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// The embedding of `text`, if one was cached for this commit, model and
+    /// mode from the same text.
+    pub fn get(
+        &self,
+        model: &str,
+        include_diffs: bool,
+        hash: &str,
+        text: &str,
+    ) -> Option<Vec<f32>> {
+        let path = self.entry_path(model, include_diffs, hash)?;
+        let bytes = fs::read(&path).ok()?;
+        let (digest, vector) = bytes.split_at_checked(DIGEST_LEN)?;
+        if digest != digest_of(text).as_slice() || vector.len() % 4 != 0 {
+            return None;
+        }
+
+        // Pruning removes the least recently *used* entries, so a hit counts
+        // as a use. Failing to record it only makes the entry look older.
+        if let Ok(file) = fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+
+        Some(
+            vector
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect(),
+        )
+    }
+
+    /// Remember `embedding` as this commit's for `text`.
+    pub fn put(
+        &self,
+        model: &str,
+        include_diffs: bool,
+        hash: &str,
+        text: &str,
+        embedding: &[f32],
+    ) -> Result<(), IndexError> {
+        let Some(path) = self.entry_path(model, include_diffs, hash) else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut bytes = Vec::with_capacity(DIGEST_LEN + embedding.len() * 4);
+        bytes.extend_from_slice(&digest_of(text));
+        for value in embedding {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        // Another clone may be writing the same entry; each writes its own
+        // staging file, and whichever rename lands last wins intact.
+        let mut staging = path.as_os_str().to_owned();
+        staging.push(format!(".{}.tmp", std::process::id()));
+        fs::write(&staging, bytes)?;
+        fs::rename(&staging, &path)?;
+        Ok(())
+    }
+
+    /// Remove the least recently used entries until the cache fits in
+    /// `max_bytes`.
+    pub fn prune(&self) -> Result<PruneReport, IndexError> {
+        let mut entries = Vec::new();
+        collect_entries(&self.root, &mut entries)?;
+        // Oldest first, so the loop below drops those.
+        entries.sort_by_key(|(_, _, used)| *used);
+
+        let mut report = PruneReport {
+            kept: entries.len(),
+            kept_bytes: entries.iter().map(|(_, size, _)| size).sum(),
+            ..PruneReport::default()
+        };
+        for (path, size, _) in entries {
+            if report.kept_bytes <= self.max_bytes {
+                break;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                // Pruned concurrently by another process; it is gone either way.
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+            report.removed += 1;
+            report.freed_bytes += size;
+            report.kept -= 1;
+            report.kept_bytes -= size;
+        }
+        Ok(report)
+    }
+
+    /// `<root>/<model>/<mode>/<first two hex digits>/<rest of the hash>`, as
+    /// git fans out loose objects. `None` for a hash that is not plain hex,
+    /// which could otherwise name a path outside the cache.
+    fn entry_path(&self, model: &str, include_diffs: bool, hash: &str) -> Option<PathBuf> {
+        if hash.len() < 3 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let model: String = model
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let mode = if include_diffs { "full" } else { "quick" };
+        let hash = hash.to_ascii_lowercase();
+        Some(
+            self.root
+                .join(model)
+                .join(mode)
+                .join(&hash[..2])
+                .join(&hash[2..]),
+        )
+    }
+}
+
+/// The configured cap, falling back to the default when unset or invalid.
+pub fn configured_size(config: &git2::Config) -> u64 {
+    config
+        .get_i64(EMBEDDING_CACHE_SIZE_KEY)
+        .ok()
+        .and_then(|size| u64::try_from(size).ok())
+        .unwrap_or(DEFAULT_CACHE_SIZE)
+}
+
+/// A size as git writes one: a byte count with an optional `k`, `m` or `g`
+/// suffix, in powers of 1024.
+pub fn parse_size(raw: &str) -> Result<u64, IndexError> {
+    let invalid = || IndexError::InvalidCacheSize(raw.to_string());
+    let trimmed = raw.trim();
+    let (digits, unit) = match trimmed.char_indices().last() {
+        Some((at, suffix)) if suffix.is_ascii_alphabetic() => {
+            let unit = match suffix.to_ascii_lowercase() {
+                'k' => 1 << 10,
+                'm' => 1 << 20,
+                'g' => 1 << 30,
+                _ => return Err(invalid()),
+            };
+            (&trimmed[..at], unit)
+        }
+        _ => (trimmed, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(unit))
+        .ok_or_else(invalid)
+}
+
+fn digest_of(text: &str) -> [u8; DIGEST_LEN] {
+    Sha256::digest(text.as_bytes()).into()
+}
+
+/// Every cached entry under `dir`, with its size and last use. A cache that
+/// was never written is empty, not an error.
+fn collect_entries(
+    dir: &Path,
+    entries: &mut Vec<(PathBuf, u64, SystemTime)>,
+) -> Result<(), IndexError> {
+    let listing = match fs::read_dir(dir) {
+        Ok(listing) => listing,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    for entry in listing {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            collect_entries(&entry.path(), entries)?;
+        } else {
+            let used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            entries.push((entry.path(), metadata.len(), used));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    const HASH: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    fn cache(dir: &TempDir, max_bytes: u64) -> EmbeddingCache {
+        EmbeddingCache::new(dir.path().to_path_buf(), max_bytes)
+    }
+
+    #[test]
+    fn an_entry_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let cache = cache(&dir, DEFAULT_CACHE_SIZE);
+        cache
+            .put("bge-small-en-v1.5", true, HASH, "fix: race", &[0.5, -1.25])
+            .unwrap();
+
+        assert_eq!(
+            cache.get("bge-small-en-v1.5", true, HASH, "fix: race"),
+            Some(vec![0.5, -1.25])
+        );
+    }
+
+    #[test]
+    fn model_mode_and_text_are_all_part_of_the_key() {
+        let dir = TempDir::new().unwrap();
+        let cache = cache(&dir, DEFAULT_CACHE_SIZE);
+        cache.put("model-a", true, HASH, "text", &[1.0]).unwrap();
+
+        assert!(cache.get("model-b", true, HASH, "text").is_none());
+        assert!(cache.get("model-a", false, HASH, "text").is_none());
+        assert!(
+            cache
+                .get("model-a", true, HASH, "text with a note")
+                .is_none(),
+            "the same commit embedded from different text is a miss"
+        );
+    }
+
+    #[test]
+    fn hashes_that_are_not_hex_are_never_paths() {
+        let dir = TempDir::new().unwrap();
+        let cache = cache(&dir, DEFAULT_CACHE_SIZE);
+        cache.put("m", true, "../../escape", "t", &[1.0]).unwrap();
+        assert!(cache.get("m", true, "../../escape", "t").is_none());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn pruning_drops_the_least_recently_used_first() {
+        let dir = TempDir::new().unwrap();
+        let writer = cache(&dir, DEFAULT_CACHE_SIZE);
+        let hashes = ["aa11", "bb22", "cc33"];
+        for hash in hashes {
+            writer.put("m", true, hash, hash, &[0.0; 8]).unwrap();
+        }
+        // Age them explicitly rather than sleeping between writes.
+        let epoch = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        for (age, hash) in hashes.iter().enumerate() {
+            let path = writer.entry_path("m", true, hash).unwrap();
+            let file = fs::File::options().write(true).open(path).unwrap();
+            file.set_modified(epoch + Duration::from_secs(age as u64))
+                .unwrap();
+        }
+        // A hit makes the oldest the newest.
+        assert!(writer.get("m", true, "aa11", "aa11").is_some());
+
+        let entry_size = (DIGEST_LEN + 8 * 4) as u64;
+        let report = cache(&dir, entry_size * 2).prune().unwrap();
+
+        assert_eq!(report.removed, 1);
+        assert_eq!(report.kept, 2);
+        assert_eq!(report.kept_bytes, entry_size * 2);
+        assert!(writer.get("m", true, "bb22", "bb22").is_none());
+        assert!(writer.get("m", true, "aa11", "aa11").is_some());
+        assert!(writer.get("m", true, "cc33", "cc33").is_some());
+    }
+
+    #[test]
+    fn pruning_a_cache_that_was_never_written_is_a_no_op() {
+        let dir = TempDir::new().unwrap();
+        let missing = EmbeddingCache::new(dir.path().join("never"), 0);
+        assert_eq!(missing.prune().unwrap(), PruneReport::default());
+    }
+
+    #[test]
+    fn sizes_take_git_suffixes() {
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+        assert_eq!(parse_size("512k").unwrap(), 512 << 10);
+        assert_eq!(parse_size("200M").unwrap(), 200 << 20);
+        assert_eq!(parse_size("2g").unwrap(), 2 << 30);
+        for bad in ["", "lots", "10x", "-5", "1.5g"] {
+            assert_eq!(parse_size(bad).err().unwrap().code(), "E3011", "{bad:?}");
+        }
+    }
+}
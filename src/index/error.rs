@@ -14,6 +14,9 @@ pub enum IndexError {
     #[error("index is corrupted or was created by an incompatible version")]
     CorruptedIndex(#[source] bincode::Error),
 
+    #[error("index format {0} is not supported by this version")]
+    UnsupportedFormat(u32),
+
     #[error("failed to write index: disk may be full or permissions denied")]
     SaveFailed(#[source] std::io::Error),
 
@@ -25,6 +28,30 @@ pub enum IndexError {
 
     #[error(transparent)]
     Embedding(#[from] crate::embedding::EmbeddingError),
+
+    #[error("pull request export is not a JSON array of pull requests")]
+    InvalidPullRequestExport(#[source] serde_json::Error),
+
+    #[error("invalid cache size '{0}'")]
+    InvalidCacheSize(String),
+
+    #[error("no shared index found at refs/semantic/index")]
+    SharedIndexNotFound,
+
+    #[error("the shared index was embedded with {shared}, but this version embeds with {local}")]
+    ModelMismatch { shared: String, local: String },
+
+    #[error("{0} failed")]
+    TransferFailed(String),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+
+    #[error("line {line} of the export: {reason}")]
+    InvalidExport { line: usize, reason: String },
+
+    #[error("this repository already has an index")]
+    IndexExists,
 }
 
 impl IndexError {
@@ -41,6 +68,9 @@ impl IndexError {
             Self::CorruptedIndex(_) => {
                 Some("The index file is unreadable. Rebuild it with: git-semantic index --force")
             }
+            Self::UnsupportedFormat(_) => {
+                Some("Rebuild the index for this version with: git-semantic index --force")
+            }
             Self::SaveFailed(_) => {
                 Some("Check available disk space and file permissions on the .git directory.")
             }
@@ -49,6 +79,26 @@ impl IndexError {
                 "The index may be from an incompatible version. Rebuild with: git-semantic index --force",
             ),
             Self::Embedding(_) => None, // Delegate to EmbeddingError's own hint
+            Self::InvalidPullRequestExport(_) => Some(
+                "Export with e.g.: gh pr list --state merged --json number,title,body,mergeCommit,labels",
+            ),
+            Self::InvalidCacheSize(_) => {
+                Some("Give a byte count, optionally with a k, m or g suffix, e.g. 500m.")
+            }
+            Self::SharedIndexNotFound => {
+                Some("Publish one from a machine with an index: git-semantic index push")
+            }
+            Self::ModelMismatch { .. } => Some(
+                "Its vectors cannot be compared with this version's queries. Build locally instead: git-semantic index",
+            ),
+            Self::TransferFailed(_) => {
+                Some("Check the remote name, and that plain git push/fetch can reach it.")
+            }
+            Self::Git(_) => Some("Check that the repository is intact and its .git is writable."),
+            Self::InvalidExport { .. } => Some(
+                "Each line must be one JSON object, the first a header as written by: git-semantic export",
+            ),
+            Self::IndexExists => Some("Pass --force to replace it."),
         }
     }
 
@@ -63,6 +113,15 @@ impl IndexError {
             Self::Io(_) => "E3006",
             Self::Bincode(_) => "E3007",
             Self::Embedding(_) => "E3008",
+            Self::UnsupportedFormat(_) => "E3009",
+            Self::InvalidPullRequestExport(_) => "E3010",
+            Self::InvalidCacheSize(_) => "E3011",
+            Self::SharedIndexNotFound => "E3012",
+            Self::ModelMismatch { .. } => "E3013",
+            Self::TransferFailed(_) => "E3014",
+            Self::Git(_) => "E3015",
+            Self::InvalidExport { .. } => "E3016",
+            Self::IndexExists => "E3017",
         }
     }
 }
@@ -0,0 +1,189 @@
+//! Reading indexes written before the on-disk format was versioned.
+//!
+//! Up to 1.5.0 the index file was a bare bincode `SemanticIndex`. Bincode is
+//! not self-describing, so any field added to [`CommitInfo`] since makes those
+//! files undecodable with the current types. The layout they were written
+//! with is frozen here and upgraded on load, so an existing index keeps
+//! working instead of demanding a full re-embed.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::git::{CommitInfo, IssueExtractor, MAX_DIFF_SIZE, conventional, trailers};
+
+use super::{IndexEntry, IndexMetadata, SemanticIndex};
+
+#[derive(Deserialize)]
+struct CommitV1 {
+    hash: String,
+    author: String,
+    date: DateTime<Utc>,
+    message: String,
+    diff_summary: String,
+}
+
+#[derive(Deserialize)]
+struct EntryV1 {
+    commit: CommitV1,
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct MetadataV1 {
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    total_commits: usize,
+    include_diffs: bool,
+}
+
+#[derive(Deserialize)]
+struct IndexV1 {
+    entries: Vec<EntryV1>,
+    model_version: String,
+    last_commit: String,
+    metadata: MetadataV1,
+}
+
+/// Decode a headerless 1.5.0-era index and upgrade it to the current layout.
+///
+/// The `Files:` line the old format kept inside `diff_summary` is lifted into
+/// structured records. Fields it never recorded come back empty, which every
+/// reader already treats as "not known" — the same state as a quick-mode
+/// commit.
+pub(crate) fn decode(bytes: &[u8]) -> Result<SemanticIndex, bincode::Error> {
+    let old: IndexV1 = bincode::deserialize(bytes)?;
+    let issues = IssueExtractor::default();
+
+    Ok(SemanticIndex {
+        entries: old
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let mut commit = CommitInfo {
+                    hash: entry.commit.hash,
+                    author: entry.commit.author,
+                    date: entry.commit.date,
+                    // 1.5.0 stored the commit's own timestamp, which is the
+                    // committer's; it is the best guess for both.
+                    committer_date: entry.commit.date,
+                    message: entry.commit.message,
+                    diff_summary: entry.commit.diff_summary,
+                    ..Default::default()
+                };
+                commit.lift_files_line();
+                commit.trailers = trailers::parse(&commit.message);
+                commit.conventional = conventional::parse(&commit.message);
+                commit.issues = issues.extract(&commit.message);
+
+                IndexEntry {
+                    commit,
+                    embedding: entry.embedding,
+                }
+            })
+            .collect(),
+        model_version: old.model_version,
+        last_commit: old.last_commit,
+        metadata: IndexMetadata {
+            created_at: old.metadata.created_at,
+            updated_at: old.metadata.updated_at,
+            total_commits: old.metadata.total_commits,
+            include_diffs: old.metadata.include_diffs,
+            ignore_fingerprint: String::new(),
+            // What 1.5.0 always kept.
+            max_diff_size: MAX_DIFF_SIZE,
+            // Unknown, so the first update reads every commit's notes again.
+            notes_tips: Default::default(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::ChangeStatus;
+    use serde::Serialize;
+
+    /// What 1.5.0 actually wrote, reproduced field for field.
+    #[derive(Serialize)]
+    struct WrittenCommit<'a> {
+        hash: &'a str,
+        author: &'a str,
+        date: DateTime<Utc>,
+        message: &'a str,
+        diff_summary: &'a str,
+    }
+
+    #[derive(Serialize)]
+    struct WrittenEntry<'a> {
+        commit: WrittenCommit<'a>,
+        embedding: Vec<f32>,
+    }
+
+    #[derive(Serialize)]
+    struct WrittenIndex<'a> {
+        entries: Vec<WrittenEntry<'a>>,
+        model_version: &'a str,
+        last_commit: &'a str,
+        metadata: (DateTime<Utc>, DateTime<Utc>, usize, bool),
+    }
+
+    fn written() -> Vec<u8> {
+        let date = chrono::DateTime::parse_from_rfc3339("2024-06-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        bincode::serialize(&WrittenIndex {
+            entries: vec![WrittenEntry {
+                commit: WrittenCommit {
+                    hash: "abc1234",
+                    author: "Alice",
+                    date,
+                    message: "fix: old commit",
+                    diff_summary: "Files: src/auth.rs\n+token",
+                },
+                embedding: vec![0.25; 4],
+            }],
+            model_version: "bge-small-en-v1.5",
+            last_commit: "abc1234",
+            metadata: (date, date, 1, true),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn a_legacy_index_decodes_into_the_current_layout() {
+        let index = decode(&written()).unwrap();
+
+        assert_eq!(index.entries.len(), 1);
+        assert_eq!(index.model_version, "bge-small-en-v1.5");
+        assert_eq!(index.last_commit, "abc1234");
+        assert!(index.metadata.include_diffs);
+
+        let commit = &index.entries[0].commit;
+        assert_eq!(commit.message, "fix: old commit");
+        assert_eq!(commit.changed_files(), Some(vec!["src/auth.rs"]));
+        assert_eq!(commit.files[0].status, ChangeStatus::Unknown);
+        assert_eq!(
+            commit.diff_summary, "+token",
+            "the path line is lifted out, not duplicated"
+        );
+        assert_eq!(index.entries[0].embedding, vec![0.25; 4]);
+    }
+
+    #[test]
+    fn migration_leaves_the_embedded_text_unchanged() {
+        // The stored embeddings were computed from the old text, so the
+        // migrated commit must render the same text or BM25 and the vectors
+        // would disagree about what a commit says.
+        let index = decode(&written()).unwrap();
+        assert_eq!(
+            index.entries[0].commit.to_text(true),
+            "fix: old commit\nAlice\nFiles: src/auth.rs\n+token"
+        );
+    }
+
+    #[test]
+    fn garbage_is_still_an_error() {
+        assert!(decode(b"definitely not an index").is_err());
+    }
+}
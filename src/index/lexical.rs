@@ -68,7 +68,7 @@ pub fn build_lexical(index: &SemanticIndex, params: Bm25Params) -> Bm25Index {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::git::CommitInfo;
+    use crate::git::{ChangeStatus, CommitInfo, FileChange};
     use crate::index::IndexEntry;
 
     fn entry(hash: &str, message: &str, files: &str) -> IndexEntry {
@@ -80,7 +80,12 @@ mod tests {
                     .unwrap()
                     .with_timezone(&chrono::Utc),
                 message: message.to_string(),
-                diff_summary: format!("Files: {files}\n+something"),
+                diff_summary: "+something".to_string(),
+                files: files
+                    .split(", ")
+                    .map(|path| FileChange::new(path, ChangeStatus::Modified))
+                    .collect(),
+                ..Default::default()
             },
             embedding: vec![0.1; 32],
         }
@@ -1,19 +1,26 @@
 pub(crate) mod ann;
 mod builder;
+pub mod cache;
 mod error;
+mod legacy;
 mod lexical;
+pub mod portable;
+pub mod pulls;
+pub mod share;
 mod storage;
 
 pub use ann::{AnnSidecar, EXACT_SCAN_THRESHOLD, build_graph};
 pub use builder::IndexBuilder;
+pub use cache::EmbeddingCache;
 pub use error::IndexError;
 pub use lexical::{LexicalSidecar, build_lexical};
-pub use storage::IndexStorage;
+pub use storage::{IndexLock, IndexStorage};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-use crate::git::CommitInfo;
+use crate::git::{CommitInfo, MAX_DIFF_SIZE};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexEntry {
@@ -27,6 +34,19 @@ pub struct IndexMetadata {
     pub updated_at: DateTime<Utc>,
     pub total_commits: usize,
     pub include_diffs: bool,
+    /// The `.semanticignore` rules the index was built under, as
+    /// [`IgnoreRules::fingerprint`](crate::git::IgnoreRules::fingerprint)
+    /// names them. A mismatch means commits were kept or dropped by rules
+    /// that no longer apply.
+    pub ignore_fingerprint: String,
+    /// Bytes of diff text kept per commit, as `semantic.maxDiffSize` set it.
+    /// Only a full index keeps any, and there a mismatch means summaries were
+    /// cut to a budget that no longer applies.
+    pub max_diff_size: usize,
+    /// The commit each configured notes ref pointed at when the commits'
+    /// notes were read, by ref name. Notes usually land after a commit is
+    /// indexed, so a tip that has moved means some need reading again.
+    pub notes_tips: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +69,9 @@ impl SemanticIndex {
                 updated_at: now,
                 total_commits: 0,
                 include_diffs,
+                ignore_fingerprint: String::new(),
+                max_diff_size: MAX_DIFF_SIZE,
+                notes_tips: BTreeMap::new(),
             },
         }
     }
@@ -100,6 +123,7 @@ mod tests {
                     .with_timezone(&Utc),
                 message: "test commit".to_string(),
                 diff_summary: "+added line".to_string(),
+                ..Default::default()
             },
             embedding: vec![0.1, 0.2, 0.3],
         };
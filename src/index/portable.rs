@@ -0,0 +1,382 @@
+//! The index as JSON Lines, for `export` and `import`.
+//!
+//! The on-disk index is bincode, readable only by a build with the same struct
+//! layout. An export is the same content one commit per line with named
+//! fields, so other tools can read the embeddings, an index can move between
+//! versions and machines, and vectors computed elsewhere can seed one.
+//!
+//! The first line is a [`Header`] naming the model and mode; each line after
+//! it is a commit's fields plus its `embedding`.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+use chrono::{DateTime, Utc};
+use git2::{ErrorCode, Repository};
+use serde::{Deserialize, Serialize};
+
+use crate::git::CommitInfo;
+
+use super::{IndexEntry, IndexError, SemanticIndex};
+
+/// Written in every header, and checked on import.
+const EXPORT_FORMAT: &str = "git-semantic-index";
+
+/// Bumped when a reader of older exports would misread newer ones.
+const EXPORT_VERSION: u32 = 1;
+
+/// The first line of an export.
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+    /// The model that produced the embeddings.
+    model: String,
+    /// `full` when the embeddings include diffs, `quick` when only messages.
+    mode: String,
+    /// The HEAD the index was last brought up to, if known.
+    #[serde(default)]
+    last_commit: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated_at: Option<DateTime<Utc>>,
+    /// Informational; import counts the records it reads.
+    #[serde(default)]
+    commits: usize,
+    /// The `.semanticignore` rules the commits were chosen under.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    ignore: String,
+    /// The diff budget, in bytes, the commits were parsed with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_diff_size: Option<usize>,
+    /// Where each notes ref pointed when the commits' notes were read.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    notes: BTreeMap<String, String>,
+}
+
+/// Every line after the header.
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+    #[serde(flatten)]
+    commit: CommitInfo,
+    embedding: Vec<f32>,
+}
+
+/// Write `index` as JSON Lines. Returns the number of records written.
+pub fn write_jsonl(index: &SemanticIndex, mut out: impl Write) -> Result<usize, IndexError> {
+    let header = Header {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        model: index.model_version.clone(),
+        mode: if index.metadata.include_diffs {
+            "full"
+        } else {
+            "quick"
+        }
+        .to_string(),
+        last_commit: index.last_commit.clone(),
+        created_at: Some(index.metadata.created_at),
+        updated_at: Some(index.metadata.updated_at),
+        commits: index.entries.len(),
+        ignore: index.metadata.ignore_fingerprint.clone(),
+        max_diff_size: Some(index.metadata.max_diff_size),
+        notes: index.metadata.notes_tips.clone(),
+    };
+    write_line(&mut out, &header)?;
+
+    for entry in &index.entries {
+        // Serialized from a borrowed pair rather than a `Record`, which would
+        // need a copy of every commit.
+        #[derive(Serialize)]
+        struct RecordRef<'a> {
+            #[serde(flatten)]
+            commit: &'a CommitInfo,
+            embedding: &'a [f32],
+        }
+        write_line(
+            &mut out,
+            &RecordRef {
+                commit: &entry.commit,
+                embedding: &entry.embedding,
+            },
+        )?;
+    }
+
+    out.flush()?;
+    Ok(index.entries.len())
+}
+
+fn write_line(out: &mut impl Write, value: &impl Serialize) -> Result<(), IndexError> {
+    serde_json::to_writer(&mut *out, value).map_err(std::io::Error::from)?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Rebuild an index from JSON Lines written by [`write_jsonl`], or by hand.
+///
+/// Blank lines are skipped. Every hash must be 7 to 40 hex digits, and is
+/// stored as whatever `resolve` expands it to — [`resolve_in`] a repository
+/// for a real import. Every embedding must have the same, non-zero length: a
+/// search compares each against one query vector.
+pub fn read_jsonl(
+    input: impl BufRead,
+    resolve: impl Fn(&str) -> Result<String, String>,
+) -> Result<SemanticIndex, IndexError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .filter(|(_, line)| !matches!(line, Ok(text) if text.trim().is_empty()));
+
+    let Some((number, line)) = lines.next() else {
+        return Err(invalid(
+            1,
+            "the file is empty; the first line must be a header",
+        ));
+    };
+    let header: Header =
+        serde_json::from_str(&line?).map_err(|err| invalid(number, format!("header: {err}")))?;
+    if header.format != EXPORT_FORMAT {
+        return Err(invalid(
+            number,
+            format!(
+                "header format is '{}', not '{EXPORT_FORMAT}'",
+                header.format
+            ),
+        ));
+    }
+    if header.version > EXPORT_VERSION {
+        return Err(invalid(
+            number,
+            format!(
+                "export version {} is newer than this version reads ({EXPORT_VERSION})",
+                header.version
+            ),
+        ));
+    }
+    let include_diffs = match header.mode.as_str() {
+        "full" => true,
+        "quick" => false,
+        other => {
+            return Err(invalid(
+                number,
+                format!("mode is '{other}', not 'full' or 'quick'"),
+            ));
+        }
+    };
+
+    // Only a hint for where the next `index` can stop walking, so one this
+    // repository cannot resolve is kept as written rather than refused.
+    let last_commit = resolve(&header.last_commit).unwrap_or(header.last_commit);
+    let mut index = SemanticIndex::new(header.model, last_commit, include_diffs);
+    if let Some(created_at) = header.created_at {
+        index.metadata.created_at = created_at;
+    }
+    if let Some(updated_at) = header.updated_at {
+        index.metadata.updated_at = updated_at;
+    }
+    index.metadata.ignore_fingerprint = header.ignore;
+    if let Some(max_diff_size) = header.max_diff_size {
+        index.metadata.max_diff_size = max_diff_size;
+    }
+    index.metadata.notes_tips = header.notes;
+
+    for (number, line) in lines {
+        let mut record: Record =
+            serde_json::from_str(&line?).map_err(|err| invalid(number, err.to_string()))?;
+        // Everything downstream abbreviates a hash to its first seven
+        // characters, and looks it up in the repository as hex.
+        let hash = &record.commit.hash;
+        if !(7..=40).contains(&hash.len()) || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid(
+                number,
+                format!("'{hash}' is not a commit hash: expected 7 to 40 hex digits"),
+            ));
+        }
+        // Everything after import compares full ids, so an abbreviated hash
+        // would never match the commit it names.
+        record.commit.hash = resolve(hash).map_err(|reason| invalid(number, reason))?;
+        let expected = index
+            .entries
+            .first()
+            .map_or(record.embedding.len(), |first| first.embedding.len());
+        if record.embedding.is_empty() || record.embedding.len() != expected {
+            return Err(invalid(
+                number,
+                format!(
+                    "embedding has {} dimensions, expected {expected}",
+                    record.embedding.len()
+                ),
+            ));
+        }
+        index.entries.push(IndexEntry {
+            commit: record.commit,
+            embedding: record.embedding,
+        });
+    }
+
+    index.metadata.total_commits = index.entries.len();
+    Ok(index)
+}
+
+/// A resolver for [`read_jsonl`] that expands a hash to the full id of the
+/// commit it names in `repo`.
+pub fn resolve_in(repo: &Repository) -> impl Fn(&str) -> Result<String, String> + '_ {
+    |hash| match repo.find_commit_by_prefix(hash) {
+        Ok(commit) => Ok(commit.id().to_string()),
+        Err(err) if err.code() == ErrorCode::Ambiguous => {
+            Err(format!("'{hash}' names more than one commit here"))
+        }
+        Err(_) => Err(format!("'{hash}' is not a commit in this repository")),
+    }
+}
+
+fn invalid(line: usize, reason: impl Into<String>) -> IndexError {
+    IndexError::InvalidExport {
+        line,
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn as_written(hash: &str) -> Result<String, String> {
+        Ok(hash.to_string())
+    }
+
+    fn sample_index() -> SemanticIndex {
+        let mut index =
+            SemanticIndex::new("bge-small-en-v1.5".to_string(), "abc1234".to_string(), true);
+        for (hash, message) in [("abc1234", "fix: second"), ("def5678", "feat: first")] {
+            index.entries.push(IndexEntry {
+                commit: CommitInfo {
+                    hash: hash.to_string(),
+                    author: "Alice".to_string(),
+                    date: DateTime::parse_from_rfc3339("2024-06-15T12:00:00Z")
+                        .unwrap()
+                        .with_timezone(&Utc),
+                    date_offset: 120,
+                    message: message.to_string(),
+                    issues: vec!["#12".to_string()],
+                    ..Default::default()
+                },
+                embedding: vec![0.25, -0.5, 1.0],
+            });
+        }
+        index.metadata.total_commits = 2;
+        index
+    }
+
+    #[test]
+    fn an_export_imports_back_unchanged() {
+        let mut sample = sample_index();
+        sample.metadata.max_diff_size = 20_000;
+        sample
+            .metadata
+            .notes_tips
+            .insert("refs/notes/review".to_string(), "f".repeat(40));
+        let mut buffer = Vec::new();
+        assert_eq!(write_jsonl(&sample, &mut buffer).unwrap(), 2);
+        assert_eq!(String::from_utf8_lossy(&buffer).lines().count(), 3);
+
+        let index = read_jsonl(buffer.as_slice(), as_written).unwrap();
+        assert_eq!(index.model_version, "bge-small-en-v1.5");
+        assert_eq!(index.last_commit, "abc1234");
+        assert!(index.metadata.include_diffs);
+        assert_eq!(index.metadata.total_commits, 2);
+        assert_eq!(index.metadata.max_diff_size, 20_000);
+        assert_eq!(index.metadata.notes_tips, sample.metadata.notes_tips);
+        assert_eq!(index.entries[1].commit.hash, "def5678");
+        assert_eq!(index.entries[1].commit.date_offset, 120);
+        assert_eq!(index.entries[1].commit.issues, ["#12"]);
+        assert_eq!(index.entries[1].embedding, vec![0.25, -0.5, 1.0]);
+    }
+
+    #[test]
+    fn a_record_needs_only_a_hash_and_an_embedding() {
+        let jsonl = r#"{"format":"git-semantic-index","version":1,"model":"m","mode":"quick"}
+
+{"hash":"abc1234","message":"seeded elsewhere","embedding":[0.1,0.2]}
+"#;
+        let index = read_jsonl(jsonl.as_bytes(), as_written).unwrap();
+        assert!(!index.metadata.include_diffs);
+        assert_eq!(index.entries.len(), 1);
+        assert_eq!(index.entries[0].commit.message, "seeded elsewhere");
+        assert!(index.entries[0].commit.files.is_empty());
+    }
+
+    #[test]
+    fn a_file_without_a_header_is_rejected() {
+        let jsonl = r#"{"hash":"abc1234","embedding":[0.1,0.2]}"#;
+        assert!(matches!(
+            read_jsonl(jsonl.as_bytes(), as_written),
+            Err(IndexError::InvalidExport { line: 1, .. })
+        ));
+        assert!(matches!(
+            read_jsonl("".as_bytes(), as_written),
+            Err(IndexError::InvalidExport { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn embeddings_of_different_lengths_are_rejected_with_their_line() {
+        let jsonl = r#"{"format":"git-semantic-index","version":1,"model":"m","mode":"full"}
+{"hash":"aaaaaaa","embedding":[0.1,0.2]}
+{"hash":"bbbbbbb","embedding":[0.1]}
+"#;
+        assert!(matches!(
+            read_jsonl(jsonl.as_bytes(), as_written),
+            Err(IndexError::InvalidExport { line: 3, reason }) if reason.contains("expected 2")
+        ));
+    }
+
+    #[test]
+    fn hashes_that_are_not_hex_commit_ids_are_rejected_with_their_line() {
+        for hash in ["", "abc", "abc123z", &"a".repeat(41)] {
+            let jsonl = format!(
+                "{{\"format\":\"git-semantic-index\",\"version\":1,\"model\":\"m\",\"mode\":\"quick\"}}\n\
+                 {{\"hash\":\"abc1234\",\"embedding\":[0.1]}}\n\
+                 {{\"hash\":\"{hash}\",\"embedding\":[0.1]}}\n"
+            );
+            assert!(
+                matches!(
+                    read_jsonl(jsonl.as_bytes(), as_written),
+                    Err(IndexError::InvalidExport { line: 3, reason }) if reason.contains("hex")
+                ),
+                "{hash:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn hashes_are_stored_as_resolved_and_unresolvable_ones_are_rejected_with_their_line() {
+        let full = |hash: &str| match hash {
+            "abc1234" => Ok(format!("abc1234{}", "0".repeat(33))),
+            other => Err(format!("'{other}' is not a commit in this repository")),
+        };
+        let jsonl = r#"{"format":"git-semantic-index","version":1,"model":"m","mode":"quick","last_commit":"abc1234"}
+{"hash":"abc1234","embedding":[0.1]}
+"#;
+        let index = read_jsonl(jsonl.as_bytes(), full).unwrap();
+        assert_eq!(index.entries[0].commit.hash.len(), 40);
+        assert_eq!(index.last_commit, index.entries[0].commit.hash);
+
+        let jsonl = format!("{jsonl}{{\"hash\":\"def5678\",\"embedding\":[0.1]}}\n");
+        assert!(matches!(
+            read_jsonl(jsonl.as_bytes(), full),
+            Err(IndexError::InvalidExport { line: 3, reason }) if reason.contains("def5678")
+        ));
+    }
+
+    #[test]
+    fn a_newer_export_version_is_rejected() {
+        let jsonl = r#"{"format":"git-semantic-index","version":99,"model":"m","mode":"full"}"#;
+        assert!(matches!(
+            read_jsonl(jsonl.as_bytes(), as_written),
+            Err(IndexError::InvalidExport { line: 1, .. })
+        ));
+    }
+}
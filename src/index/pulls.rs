@@ -0,0 +1,325 @@
+//! Pull and merge request metadata imported from an offline export.
+//!
+//! A squash merge leaves a one-line subject behind; the reasoning lives in the
+//! PR description. Nothing here talks to a forge — the input is whatever a
+//! script or `gh pr list --json …` already saved — so indexing stays offline.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::git::{CommitInfo, PullRequest};
+
+use super::{IndexError, SemanticIndex};
+
+/// One record of an export, in any of the shapes accepted. Every field is
+/// optional so a dump trimmed to the columns someone cared about still loads.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ExportRecord {
+    /// GitHub's number.
+    number: Option<u64>,
+    /// GitLab's per-project number, which is the one people write as `!12`.
+    iid: Option<u64>,
+    title: Option<String>,
+    /// GitHub's description.
+    body: Option<String>,
+    /// GitLab's description.
+    description: Option<String>,
+    merge_commit_sha: Option<String>,
+    squash_commit_sha: Option<String>,
+    /// `gh pr list --json mergeCommit`.
+    #[serde(rename = "mergeCommit")]
+    merge_commit: Option<CommitRef>,
+    labels: Vec<Label>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitRef {
+    oid: String,
+}
+
+/// GitHub labels are objects, GitLab's are plain strings.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Label {
+    Named { name: String },
+    Plain(String),
+}
+
+/// A pull request and the commits it may have landed as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedPullRequest {
+    pub pull_request: PullRequest,
+    /// Merge and squash commit SHAs named by the export, possibly abbreviated.
+    pub commits: Vec<String>,
+}
+
+/// Read a JSON array of pull requests in GitHub REST, `gh` CLI, or GitLab
+/// merge request shape.
+pub fn parse_export(json: &str) -> Result<Vec<ImportedPullRequest>, IndexError> {
+    let records: Vec<ExportRecord> =
+        serde_json::from_str(json).map_err(IndexError::InvalidPullRequestExport)?;
+
+    Ok(records
+        .into_iter()
+        .filter_map(|record| {
+            let number = record.iid.or(record.number)?;
+            let commits = [
+                record.merge_commit_sha,
+                record.squash_commit_sha,
+                record.merge_commit.map(|c| c.oid),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|sha| !sha.is_empty())
+            .collect();
+
+            Some(ImportedPullRequest {
+                pull_request: PullRequest {
+                    number,
+                    title: record.title.unwrap_or_default(),
+                    body: record.body.or(record.description).unwrap_or_default(),
+                    labels: record
+                        .labels
+                        .into_iter()
+                        .map(|label| match label {
+                            Label::Named { name } => name,
+                            Label::Plain(name) => name,
+                        })
+                        .collect(),
+                },
+                commits,
+            })
+        })
+        .collect())
+}
+
+/// Attach each pull request to the commit it landed as, returning the
+/// positions of the entries that changed.
+///
+/// A PR is matched by its merge or squash SHA first. When the export has none
+/// that are indexed — the branch was rebased after merging, or the dump
+/// predates the merge — a commit whose subject ends in GitHub's squash suffix
+/// `(#N)` is taken instead. Tags are never matched.
+pub fn attach(index: &mut SemanticIndex, pulls: Vec<ImportedPullRequest>) -> Vec<usize> {
+    let mut changed = Vec::new();
+
+    for imported in pulls {
+        let by_sha = index.entries.iter().position(|entry| {
+            !entry.commit.is_tag()
+                && imported
+                    .commits
+                    .iter()
+                    .any(|sha| sha.len() >= 7 && entry.commit.hash.starts_with(sha.as_str()))
+        });
+        let suffix = format!("(#{})", imported.pull_request.number);
+        let position = by_sha.or_else(|| {
+            index.entries.iter().position(|entry| {
+                !entry.commit.is_tag()
+                    && entry
+                        .commit
+                        .message
+                        .lines()
+                        .next()
+                        .is_some_and(|subject| subject.trim_end().ends_with(&suffix))
+            })
+        });
+
+        if let Some(position) = position {
+            let commit = &mut index.entries[position].commit;
+            if commit.pull_request.as_ref() != Some(&imported.pull_request) {
+                commit.pull_request = Some(imported.pull_request);
+                changed.push(position);
+            }
+        }
+    }
+
+    changed.sort_unstable();
+    changed.dedup();
+    changed
+}
+
+/// Give `commits` the pull requests their entries in `previous` carried.
+///
+/// A rebuild parses every commit afresh from git, which knows nothing of
+/// what `import-prs` attached; without this each one would silently drop it.
+pub fn carry_over(previous: &SemanticIndex, commits: &mut [CommitInfo]) {
+    let attached: HashMap<&str, &PullRequest> = previous
+        .entries
+        .iter()
+        .filter(|entry| !entry.commit.is_tag())
+        .filter_map(|entry| {
+            Some((
+                entry.commit.hash.as_str(),
+                entry.commit.pull_request.as_ref()?,
+            ))
+        })
+        .collect();
+    if attached.is_empty() {
+        return;
+    }
+    for commit in commits.iter_mut().filter(|commit| !commit.is_tag()) {
+        if let Some(pull_request) = attached.get(commit.hash.as_str()) {
+            commit.pull_request = Some((*pull_request).clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::CommitInfo;
+    use crate::index::IndexEntry;
+
+    fn index_of(commits: &[(&str, &str)]) -> SemanticIndex {
+        let mut index = SemanticIndex::new("test".into(), "head".into(), true);
+        index.entries = commits
+            .iter()
+            .map(|(hash, message)| IndexEntry {
+                commit: CommitInfo {
+                    hash: hash.to_string(),
+                    message: message.to_string(),
+                    ..Default::default()
+                },
+                embedding: vec![0.0; 4],
+            })
+            .collect();
+        index
+    }
+
+    #[test]
+    fn github_rest_records_are_read() {
+        let pulls = parse_export(
+            r#"[{"number": 12, "title": "Retry uploads", "body": "Because S3 flakes.",
+                 "merge_commit_sha": "abc1234def", "labels": [{"name": "bug"}],
+                 "html_url": "https://github.com/acme/api/pull/12"}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(pulls.len(), 1);
+        assert_eq!(pulls[0].pull_request.number, 12);
+        assert_eq!(pulls[0].pull_request.body, "Because S3 flakes.");
+        assert_eq!(pulls[0].pull_request.labels, vec!["bug"]);
+        assert_eq!(pulls[0].commits, vec!["abc1234def"]);
+    }
+
+    #[test]
+    fn gh_cli_and_gitlab_records_are_read() {
+        let pulls = parse_export(
+            r#"[{"number": 3, "title": "gh", "body": null,
+                 "mergeCommit": {"oid": "fff0000111"}, "labels": []},
+                {"id": 9001, "iid": 7, "title": "MR", "description": "Why.",
+                 "merge_commit_sha": null, "squash_commit_sha": "eee9999888",
+                 "labels": ["perf", "backend"]}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(pulls[0].commits, vec!["fff0000111"]);
+        assert_eq!(pulls[0].pull_request.body, "");
+        assert_eq!(pulls[1].pull_request.number, 7, "the iid, not the id");
+        assert_eq!(pulls[1].pull_request.body, "Why.");
+        assert_eq!(pulls[1].pull_request.labels, vec!["perf", "backend"]);
+        assert_eq!(pulls[1].commits, vec!["eee9999888"]);
+    }
+
+    #[test]
+    fn records_without_a_number_are_skipped() {
+        let pulls = parse_export(r#"[{"title": "no number"}]"#).unwrap();
+        assert!(pulls.is_empty());
+    }
+
+    #[test]
+    fn malformed_exports_are_an_error() {
+        let err = parse_export(r#"{"not": "an array"}"#).err().unwrap();
+        assert_eq!(err.code(), "E3010");
+    }
+
+    #[test]
+    fn pull_requests_attach_by_sha_then_by_squash_suffix() {
+        let mut index = index_of(&[
+            ("abc1234def5678", "fix: retry uploads"),
+            ("bbb2222ccc3333", "feat: dark mode (#40)"),
+            ("ccc3333ddd4444", "chore: unrelated"),
+        ]);
+        let pr = |number: u64, commits: &[&str]| ImportedPullRequest {
+            pull_request: PullRequest {
+                number,
+                title: format!("PR {number}"),
+                ..Default::default()
+            },
+            commits: commits.iter().map(|c| c.to_string()).collect(),
+        };
+
+        let changed = attach(
+            &mut index,
+            vec![
+                pr(12, &["abc1234"]),
+                pr(40, &["0000000deadbeef"]),
+                pr(99, &[]),
+            ],
+        );
+
+        assert_eq!(changed, vec![0, 1]);
+        assert_eq!(
+            index.entries[0]
+                .commit
+                .pull_request
+                .as_ref()
+                .unwrap()
+                .number,
+            12
+        );
+        assert_eq!(
+            index.entries[1]
+                .commit
+                .pull_request
+                .as_ref()
+                .unwrap()
+                .number,
+            40
+        );
+        assert!(index.entries[2].commit.pull_request.is_none());
+
+        let again = attach(&mut index, vec![pr(12, &["abc1234"])]);
+        assert!(
+            again.is_empty(),
+            "re-importing the same data changes nothing"
+        );
+    }
+
+    #[test]
+    fn a_rebuild_keeps_the_pull_requests_of_the_same_commits() {
+        let mut previous = index_of(&[("aaa1111", "fix: retry"), ("bbb2222", "chore: gone")]);
+        attach(
+            &mut previous,
+            vec![ImportedPullRequest {
+                pull_request: PullRequest {
+                    number: 12,
+                    ..Default::default()
+                },
+                commits: vec!["aaa1111".to_string()],
+            }],
+        );
+
+        let mut rebuilt = vec![
+            CommitInfo {
+                hash: "ccc3333".to_string(),
+                ..Default::default()
+            },
+            CommitInfo {
+                hash: "aaa1111".to_string(),
+                ..Default::default()
+            },
+            CommitInfo {
+                hash: "aaa1111".to_string(),
+                tag: Some("v1.0".to_string()),
+                ..Default::default()
+            },
+        ];
+        carry_over(&previous, &mut rebuilt);
+
+        assert!(rebuilt[0].pull_request.is_none());
+        assert_eq!(rebuilt[1].pull_request.as_ref().unwrap().number, 12);
+        assert!(rebuilt[2].pull_request.is_none(), "tags never carry one");
+    }
+}
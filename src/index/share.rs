@@ -0,0 +1,202 @@
+//! Sharing a prebuilt index through git refs.
+//!
+//! Embedding a large history takes a while, and every clone used to pay for it
+//! separately. A published index is an ordinary git object under
+//! [`SHARED_INDEX_REF`], so whatever can push and fetch the repository can
+//! move it — CI builds it once, everyone else fetches it and only embeds the
+//! commits made since.
+
+use std::path::Path;
+use std::process::Command;
+
+use git2::{Oid, Repository, Signature};
+
+use super::storage::{decode_index, encode_index};
+use super::{IndexError, SemanticIndex};
+
+/// Where a published index lives, locally and on the remote.
+pub const SHARED_INDEX_REF: &str = "refs/semantic/index";
+
+/// The one file in a published commit's tree.
+const SHARED_INDEX_FILE: &str = "semantic-index";
+
+/// Replace the published index with `index`.
+///
+/// The blob is wrapped in a commit because every forge accepts pushed commits
+/// and not all of them accept a ref to a bare blob. Each one is parentless:
+/// nobody wants the history of an index, and chaining them would keep every
+/// superseded copy reachable on the remote forever.
+pub fn publish(repo: &Repository, index: &SemanticIndex) -> Result<Oid, IndexError> {
+    let blob = repo.blob(&encode_index(index)?)?;
+    let mut tree = repo.treebuilder(None)?;
+    tree.insert(SHARED_INDEX_FILE, blob, git2::FileMode::Blob.into())?;
+    let tree = repo.find_tree(tree.write()?)?;
+
+    // CI runners often have no identity configured; the commit is never
+    // looked at as history, so a placeholder is fine there.
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("git-semantic", "git-semantic@localhost"))?;
+    let mode = if index.metadata.include_diffs {
+        "full"
+    } else {
+        "quick"
+    };
+    let message = format!(
+        "git-semantic index\n\nModel: {}\nMode: {mode}\nCommits: {}\nHead: {}\n",
+        index.model_version,
+        index.entries.len(),
+        index.last_commit
+    );
+
+    let commit = repo.commit(None, &signature, &signature, &message, &tree, &[])?;
+    repo.reference(SHARED_INDEX_REF, commit, true, "git-semantic index push")?;
+    Ok(commit)
+}
+
+/// The index published under [`SHARED_INDEX_REF`] in this repository.
+pub fn read(repo: &Repository) -> Result<SemanticIndex, IndexError> {
+    let reference = match repo.find_reference(SHARED_INDEX_REF) {
+        Ok(reference) => reference,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => {
+            return Err(IndexError::SharedIndexNotFound);
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let tree = reference.peel_to_commit()?.tree()?;
+    let entry = tree
+        .get_name(SHARED_INDEX_FILE)
+        .ok_or(IndexError::SharedIndexNotFound)?;
+    let blob = repo.find_blob(entry.id())?;
+    decode_index(blob.content())
+}
+
+/// Refuse a shared index embedded by a model other than `local_model`.
+///
+/// Its vectors and this version's query vectors would live in different
+/// spaces, so every search would rank on noise without any error to say so.
+pub fn check_model(shared: &SemanticIndex, local_model: &str) -> Result<(), IndexError> {
+    if shared.model_version == local_model {
+        return Ok(());
+    }
+    Err(IndexError::ModelMismatch {
+        shared: shared.model_version.clone(),
+        local: local_model.to_string(),
+    })
+}
+
+/// Push the published index to `remote`, replacing whatever it had.
+pub fn push(repo_path: &Path, remote: &str) -> Result<(), IndexError> {
+    run_git(repo_path, "push", remote)
+}
+
+/// Fetch `remote`'s published index into [`SHARED_INDEX_REF`].
+pub fn fetch(repo_path: &Path, remote: &str) -> Result<(), IndexError> {
+    run_git(repo_path, "fetch", remote)
+}
+
+/// Run `git <verb> -- <remote> +ref:ref`.
+///
+/// Through the `git` binary rather than libgit2 so the user's credential
+/// helpers, SSH config and proxies apply exactly as they do to their own
+/// pushes. Its output goes straight to the terminal for the same reason.
+/// The `--` keeps a remote that starts with `-`, such as
+/// `--upload-pack=<command>`, from being read as an option.
+fn run_git(repo_path: &Path, verb: &str, remote: &str) -> Result<(), IndexError> {
+    let refspec = format!("+{SHARED_INDEX_REF}:{SHARED_INDEX_REF}");
+    let command = format!("git {verb} {remote} {refspec}");
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args([verb, "--", remote, &refspec])
+        .status()
+        .map_err(|err| IndexError::TransferFailed(format!("{command}: {err}")))?;
+
+    if !status.success() {
+        return Err(IndexError::TransferFailed(command));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::CommitInfo;
+    use crate::index::IndexEntry;
+    use tempfile::TempDir;
+
+    fn sample_index(model: &str) -> SemanticIndex {
+        let mut index = SemanticIndex::new(model.to_string(), "abc1234".to_string(), true);
+        index.entries.push(IndexEntry {
+            commit: CommitInfo {
+                hash: "abc1234".to_string(),
+                message: "test commit".to_string(),
+                ..Default::default()
+            },
+            embedding: vec![0.25; 8],
+        });
+        index.metadata.total_commits = 1;
+        index
+    }
+
+    #[test]
+    fn a_published_index_reads_back() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        publish(&repo, &sample_index("bge-small-en-v1.5")).unwrap();
+        let shared = read(&repo).unwrap();
+
+        assert_eq!(shared.last_commit, "abc1234");
+        assert_eq!(shared.entries[0].embedding, vec![0.25; 8]);
+        assert!(shared.metadata.include_diffs);
+    }
+
+    #[test]
+    fn republishing_replaces_rather_than_chains() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        publish(&repo, &sample_index("bge-small-en-v1.5")).unwrap();
+        let mut newer = sample_index("bge-small-en-v1.5");
+        newer.last_commit = "def5678".to_string();
+        let commit = publish(&repo, &newer).unwrap();
+
+        assert_eq!(repo.find_commit(commit).unwrap().parent_count(), 0);
+        assert_eq!(read(&repo).unwrap().last_commit, "def5678");
+    }
+
+    #[test]
+    fn nothing_published_is_its_own_error() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        assert!(matches!(read(&repo), Err(IndexError::SharedIndexNotFound)));
+    }
+
+    #[test]
+    fn a_remote_is_never_read_as_an_option() {
+        let dir = TempDir::new().unwrap();
+        Repository::init(dir.path()).unwrap();
+        let marker = dir.path().join("ran");
+        let remote = format!("--upload-pack=touch {}", marker.display());
+
+        assert!(matches!(
+            fetch(dir.path(), &remote),
+            Err(IndexError::TransferFailed(_))
+        ));
+        assert!(!marker.exists(), "the remote ran as a command");
+    }
+
+    #[test]
+    fn another_models_index_is_refused() {
+        let shared = sample_index("some-other-model");
+
+        assert!(check_model(&shared, "some-other-model").is_ok());
+        assert!(matches!(
+            check_model(&shared, "bge-small-en-v1.5"),
+            Err(IndexError::ModelMismatch { shared, local })
+                if shared == "some-other-model" && local == "bge-small-en-v1.5"
+        ));
+    }
+}
@@ -7,11 +7,35 @@ use crate::text::{Bm25Index, Bm25Params};
 use crate::vector::{HnswIndex, HnswParams};
 
 use super::ann::{AnnSidecar, build_graph};
+use super::legacy;
 use super::lexical::{LexicalSidecar, build_lexical};
 use super::{IndexError, SemanticIndex};
 
+/// Leading bytes of every index written since the format was versioned. Files
+/// without it predate versioning and are upgraded by [`legacy`].
+const INDEX_MAGIC: &[u8; 4] = b"GSIX";
+
+/// Bumped whenever `SemanticIndex`'s serialized layout changes.
+const INDEX_FORMAT: u32 = 17;
+
+const INDEX_FILE: &str = "semantic-index";
+
+/// Per-worktree: the HEAD that worktree last brought the shared index up to.
+const HEAD_FILE: &str = "semantic-head";
+
 pub struct IndexStorage {
     index_path: PathBuf,
+    /// Where this worktree records the HEAD it last indexed.
+    head_path: PathBuf,
+}
+
+/// Exclusive hold on a repository's index, released on drop.
+///
+/// Updating is load, embed for minutes, save. Two worktrees doing that at
+/// once would each save what they loaded plus their own commits, and the
+/// second write would drop the first one's.
+pub struct IndexLock {
+    _file: fs::File,
 }
 
 impl IndexStorage {
@@ -30,20 +54,62 @@ impl IndexStorage {
     /// relative to the submodule directory; reading the file by hand resolves
     /// it against the process's working directory instead and puts the index
     /// somewhere else entirely.
+    ///
+    /// Linked worktrees share their repository's objects, so they share one
+    /// index too: it lives in the *common* git dir, and only the HEAD each
+    /// worktree last indexed stays in its own.
     pub fn new(repo_path: &Path) -> Result<Self, IndexError> {
-        let git_dir = Repository::discover(repo_path)
-            .map_err(|_| discovery_failure(repo_path))?
-            .path()
-            .to_path_buf();
+        let repo = Repository::discover(repo_path).map_err(|_| discovery_failure(repo_path))?;
+        let git_dir = repo.path();
+        let index_path = repo.commondir().join(INDEX_FILE);
+
+        adopt_worktree_index(&git_dir.join(INDEX_FILE), &index_path);
 
         Ok(Self {
-            index_path: git_dir.join("semantic-index"),
+            index_path,
+            head_path: git_dir.join(HEAD_FILE),
         })
     }
 
     pub fn save(&self, index: &SemanticIndex) -> Result<(), IndexError> {
-        let encoded = bincode::serialize(index)?;
-        fs::write(&self.index_path, encoded)?;
+        write_atomically(&self.index_path, &encode_index(index)?)
+    }
+
+    /// Take the index for updating, waiting for any other holder.
+    pub fn lock(&self) -> Result<IndexLock, IndexError> {
+        let file = self.lock_file()?;
+        file.lock()?;
+        Ok(IndexLock { _file: file })
+    }
+
+    /// Take the index for updating if nobody else has it.
+    pub fn try_lock(&self) -> Result<Option<IndexLock>, IndexError> {
+        let file = self.lock_file()?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(IndexLock { _file: file })),
+            Err(fs::TryLockError::WouldBlock) => Ok(None),
+            Err(fs::TryLockError::Error(err)) => Err(err.into()),
+        }
+    }
+
+    fn lock_file(&self) -> Result<fs::File, IndexError> {
+        Ok(fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.sidecar_path("lock"))?)
+    }
+
+    /// The HEAD this worktree last indexed, if it has indexed at all.
+    pub fn worktree_head(&self) -> Option<String> {
+        let head = fs::read_to_string(&self.head_path).ok()?;
+        let head = head.trim();
+        (!head.is_empty()).then(|| head.to_string())
+    }
+
+    /// Record `hash` as the HEAD this worktree has indexed up to.
+    pub fn set_worktree_head(&self, hash: &str) -> Result<(), IndexError> {
+        fs::write(&self.head_path, format!("{hash}\n"))?;
         Ok(())
     }
 
@@ -73,7 +139,8 @@ impl IndexStorage {
         path
     }
 
-    /// Load the cached graph, rebuilding it when absent, stale, or unreadable.
+    /// Load the cached graph, rebuilding it when absent, stale, built with
+    /// other parameters, or unreadable.
     ///
     /// Returns the graph and whether it had to be rebuilt, so callers can
     /// mention the one-off cost. Failing to *write* the cache is swallowed: a
@@ -85,6 +152,7 @@ impl IndexStorage {
     ) -> (HnswIndex, bool) {
         if let Some(sidecar) = self.read_ann_sidecar()
             && sidecar.matches(index)
+            && same_construction(sidecar.graph().params(), params)
         {
             return (sidecar.into_graph(), false);
         }
@@ -105,7 +173,8 @@ impl IndexStorage {
         self.write_ann_sidecar(&sidecar)
     }
 
-    /// Load the cached BM25 index, rebuilding when absent, stale, or unreadable.
+    /// Load the cached BM25 index, rebuilding when absent, stale, built with
+    /// other parameters, or unreadable.
     ///
     /// Same contract as [`Self::load_or_build_ann`]: the bool reports whether a
     /// rebuild happened, and failing to persist is logged rather than raised.
@@ -116,6 +185,7 @@ impl IndexStorage {
     ) -> (Bm25Index, bool) {
         if let Some(sidecar) = self.read_lexical_sidecar()
             && sidecar.matches(index)
+            && sidecar.index().params() == params
         {
             return (sidecar.into_index(), false);
         }
@@ -145,9 +215,7 @@ impl IndexStorage {
     }
 
     fn write_lexical_sidecar(&self, sidecar: &LexicalSidecar) -> Result<(), IndexError> {
-        let encoded = bincode::serialize(sidecar)?;
-        fs::write(self.lexical_path(), encoded)?;
-        Ok(())
+        write_atomically(&self.lexical_path(), &bincode::serialize(sidecar)?)
     }
 
     /// A missing, truncated, or format-mismatched sidecar is a cache miss.
@@ -157,9 +225,7 @@ impl IndexStorage {
     }
 
     fn write_ann_sidecar(&self, sidecar: &AnnSidecar) -> Result<(), IndexError> {
-        let encoded = bincode::serialize(sidecar)?;
-        fs::write(self.ann_path(), encoded)?;
-        Ok(())
+        write_atomically(&self.ann_path(), &bincode::serialize(sidecar)?)
     }
 
     pub fn load(&self) -> Result<SemanticIndex, IndexError> {
@@ -171,8 +237,7 @@ impl IndexStorage {
             }
         })?;
 
-        let index = bincode::deserialize(&data)?;
-        Ok(index)
+        decode_index(&data)
     }
 
     pub fn index_size_mb(&self) -> Result<f64, IndexError> {
@@ -181,6 +246,65 @@ impl IndexStorage {
     }
 }
 
+/// Serialize `index` behind the magic and format version.
+pub(super) fn encode_index(index: &SemanticIndex) -> Result<Vec<u8>, IndexError> {
+    let mut encoded = Vec::with_capacity(INDEX_MAGIC.len() + 4);
+    encoded.extend_from_slice(INDEX_MAGIC);
+    encoded.extend_from_slice(&INDEX_FORMAT.to_le_bytes());
+    bincode::serialize_into(&mut encoded, index)?;
+    Ok(encoded)
+}
+
+/// Decode either a versioned index or a pre-versioning one.
+///
+/// A legacy file begins with its entry count as a little-endian `u64`, which
+/// would have to exceed a billion billion to spell the magic — so the two
+/// cannot be confused.
+pub(super) fn decode_index(data: &[u8]) -> Result<SemanticIndex, IndexError> {
+    let Some(rest) = data.strip_prefix(INDEX_MAGIC) else {
+        return Ok(legacy::decode(data)?);
+    };
+
+    let (version, payload) = rest
+        .split_first_chunk::<4>()
+        .ok_or(IndexError::UnsupportedFormat(0))?;
+    let version = u32::from_le_bytes(*version);
+    if version != INDEX_FORMAT {
+        return Err(IndexError::UnsupportedFormat(version));
+    }
+
+    Ok(bincode::deserialize(payload)?)
+}
+
+/// Whether a graph built with `built` is the one `wanted` would build. The
+/// query-time width is not part of that; it is passed with each search.
+fn same_construction(built: HnswParams, wanted: HnswParams) -> bool {
+    (built.m, built.ef_construction, built.seed) == (wanted.m, wanted.ef_construction, wanted.seed)
+}
+
+/// Write through a temporary file renamed into place. Every worktree reads
+/// these files, and a search in one must never see half of what another is
+/// writing.
+fn write_atomically(path: &Path, bytes: &[u8]) -> Result<(), IndexError> {
+    let mut staging = path.as_os_str().to_owned();
+    staging.push(format!(".{}.tmp", std::process::id()));
+    fs::write(&staging, bytes)?;
+    fs::rename(&staging, path)?;
+    Ok(())
+}
+
+/// Move an index built inside a linked worktree's own git dir, before
+/// indexes were shared, to the common dir — unless one is already there.
+/// Best effort: failing leaves the worktree to build the shared one afresh.
+fn adopt_worktree_index(old: &Path, shared: &Path) {
+    if old == shared || !old.exists() || shared.exists() {
+        return;
+    }
+    if let Err(err) = fs::rename(old, shared) {
+        tracing::debug!("could not move {} to the common dir: {err}", old.display());
+    }
+}
+
 /// Tell "you are not in a repository" apart from "this repository's pointer is
 /// broken". Both stop the command, but only one of them is the user's fault,
 /// and the hints they carry say different things.
@@ -213,6 +337,7 @@ mod tests {
                         .with_timezone(&chrono::Utc),
                     message: format!("commit {i}"),
                     diff_summary: String::new(),
+                    ..Default::default()
                 },
                 embedding: (0..32)
                     .map(|d| ((i * 32 + d) as f32 * 0.017).sin())
@@ -252,6 +377,7 @@ mod tests {
                     .with_timezone(&chrono::Utc),
                 message: "test commit".to_string(),
                 diff_summary: String::new(),
+                ..Default::default()
             },
             embedding: vec![0.1; 384],
         });
@@ -339,6 +465,84 @@ mod tests {
         );
     }
 
+    /// A repository with one commit and a linked worktree of it. Returns the
+    /// main checkout and the worktree directory, which lives inside it.
+    fn create_repo_with_worktree() -> (TempDir, PathBuf) {
+        let dir = create_git_repo();
+        let repo = Repository::open(dir.path()).unwrap();
+        let sig = git2::Signature::now("Alice", "alice@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+
+        let worktree = dir.path().join("wt");
+        repo.worktree("wt", &worktree, None).unwrap();
+        (dir, worktree)
+    }
+
+    #[test]
+    fn test_linked_worktrees_share_one_index() {
+        let (dir, worktree) = create_repo_with_worktree();
+        let main = IndexStorage::new(dir.path()).unwrap();
+        let linked = IndexStorage::new(&worktree).unwrap();
+
+        assert_eq!(main.index_path(), linked.index_path());
+        assert_eq!(main.ann_path(), linked.ann_path());
+        main.save(&sample_index()).unwrap();
+        assert_eq!(linked.load().unwrap().last_commit, "abc1234");
+    }
+
+    #[test]
+    fn test_each_worktree_keeps_its_own_head() {
+        let (dir, worktree) = create_repo_with_worktree();
+        let main = IndexStorage::new(dir.path()).unwrap();
+        let linked = IndexStorage::new(&worktree).unwrap();
+
+        assert_eq!(linked.worktree_head(), None);
+        main.set_worktree_head("aaaa").unwrap();
+        linked.set_worktree_head("bbbb").unwrap();
+        assert_eq!(main.worktree_head().as_deref(), Some("aaaa"));
+        assert_eq!(linked.worktree_head().as_deref(), Some("bbbb"));
+    }
+
+    #[test]
+    fn test_an_index_built_inside_a_worktree_moves_to_the_common_dir() {
+        let (dir, worktree) = create_repo_with_worktree();
+        let own_git_dir = Repository::open(&worktree).unwrap().path().to_path_buf();
+        fs::write(
+            own_git_dir.join(INDEX_FILE),
+            encode_index(&sample_index()).unwrap(),
+        )
+        .unwrap();
+
+        let linked = IndexStorage::new(&worktree).unwrap();
+        assert!(!own_git_dir.join(INDEX_FILE).exists());
+        assert_eq!(
+            IndexStorage::new(dir.path())
+                .unwrap()
+                .load()
+                .unwrap()
+                .last_commit,
+            "abc1234"
+        );
+        assert_eq!(linked.load().unwrap().last_commit, "abc1234");
+    }
+
+    #[test]
+    fn test_the_lock_is_exclusive_across_worktrees() {
+        let (dir, worktree) = create_repo_with_worktree();
+        let main = IndexStorage::new(dir.path()).unwrap();
+        let linked = IndexStorage::new(&worktree).unwrap();
+
+        let held = main.try_lock().unwrap();
+        assert!(held.is_some());
+        assert!(linked.try_lock().unwrap().is_none());
+        drop(held);
+        assert!(linked.try_lock().unwrap().is_some());
+    }
+
     #[test]
     fn test_a_broken_gitdir_pointer_is_not_reported_as_a_missing_repository() {
         let dir = TempDir::new().unwrap();
@@ -367,6 +571,58 @@ mod tests {
         assert!(loaded.metadata.include_diffs);
     }
 
+    #[test]
+    fn test_saved_index_carries_the_format_header() {
+        let dir = create_git_repo();
+        let storage = IndexStorage::new(dir.path()).unwrap();
+        storage.save(&sample_index()).unwrap();
+
+        let bytes = fs::read(storage.index_path()).unwrap();
+        assert!(bytes.starts_with(INDEX_MAGIC));
+        assert_eq!(bytes[4..8], INDEX_FORMAT.to_le_bytes());
+    }
+
+    #[test]
+    fn test_a_headerless_index_still_loads() {
+        // 1.5.0 wrote the bare struct. Its commit layout differs from today's,
+        // so decoding it as the current type would fail.
+        let dir = create_git_repo();
+        let storage = IndexStorage::new(dir.path()).unwrap();
+        let date = chrono::DateTime::parse_from_rfc3339("2024-06-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let legacy = (
+            vec![(
+                ("abc1234", "Alice", date, "test commit", ""),
+                vec![0.1f32; 384],
+            )],
+            "bge-small-en-v1.5",
+            "abc1234",
+            (date, date, 1usize, false),
+        );
+        fs::write(storage.index_path(), bincode::serialize(&legacy).unwrap()).unwrap();
+
+        let loaded = storage.load().unwrap();
+        assert_eq!(loaded.entries[0].commit.hash, "abc1234");
+        assert_eq!(loaded.last_commit, "abc1234");
+        assert!(!loaded.metadata.include_diffs);
+    }
+
+    #[test]
+    fn test_an_unknown_format_version_is_rejected() {
+        let dir = create_git_repo();
+        let storage = IndexStorage::new(dir.path()).unwrap();
+
+        let mut bytes = INDEX_MAGIC.to_vec();
+        bytes.extend_from_slice(&(INDEX_FORMAT + 1).to_le_bytes());
+        fs::write(storage.index_path(), bytes).unwrap();
+
+        assert!(matches!(
+            storage.load(),
+            Err(IndexError::UnsupportedFormat(v)) if v == INDEX_FORMAT + 1
+        ));
+    }
+
     #[test]
     fn test_load_nonexistent_index() {
         let dir = create_git_repo();
@@ -458,6 +714,29 @@ mod tests {
         assert_eq!(graph.len(), 15);
     }
 
+    #[test]
+    fn test_load_or_build_ann_rebuilds_when_construction_params_change() {
+        let dir = create_git_repo();
+        let storage = IndexStorage::new(dir.path()).unwrap();
+        let index = index_with(20);
+        storage.load_or_build_ann(&index, HnswParams::default());
+
+        let wider = HnswParams {
+            ef_search: 200,
+            ..HnswParams::default()
+        };
+        let (_, rebuilt) = storage.load_or_build_ann(&index, wider);
+        assert!(!rebuilt, "the query-time width needs no rebuild");
+
+        let denser = HnswParams {
+            m: 24,
+            ..HnswParams::default()
+        };
+        let (graph, rebuilt) = storage.load_or_build_ann(&index, denser);
+        assert!(rebuilt);
+        assert_eq!(graph.params().m, 24);
+    }
+
     #[test]
     fn test_load_or_build_ann_survives_corrupt_sidecar() {
         let dir = create_git_repo();
@@ -530,6 +809,19 @@ mod tests {
         assert_eq!(lexical.len(), 15);
     }
 
+    #[test]
+    fn test_load_or_build_lexical_rebuilds_when_params_change() {
+        let dir = create_git_repo();
+        let storage = IndexStorage::new(dir.path()).unwrap();
+        let index = index_with(10);
+        storage.load_or_build_lexical(&index, Bm25Params::default());
+
+        let params = Bm25Params { k1: 1.5, b: 0.5 };
+        let (lexical, rebuilt) = storage.load_or_build_lexical(&index, params);
+        assert!(rebuilt);
+        assert_eq!(lexical.params(), params);
+    }
+
     #[test]
     fn test_refresh_lexical_writes_a_usable_sidecar() {
         let dir = create_git_repo();
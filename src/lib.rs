@@ -1,4 +1,5 @@
 pub mod cli;
+pub mod config;
 pub mod embedding;
 pub mod git;
 pub mod index;
@@ -1,6 +1,6 @@
-use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
-use git_semantic::{cli, embedding, git, index, search};
+use git_semantic::{cli, config, embedding, git, index, search};
 use std::process;
 
 #[derive(Parser)]
@@ -23,6 +23,29 @@ enum Mode {
     Lexical,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum DateFieldArg {
+    /// When the change was written (default)
+    Author,
+    /// When it was applied; moves on rebase and cherry-pick
+    Committer,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum ExportFormat {
+    /// JSON Lines: a header, then one commit and its embedding per line
+    Jsonl,
+}
+
+impl From<DateFieldArg> for cli::DateField {
+    fn from(field: DateFieldArg) -> Self {
+        match field {
+            DateFieldArg::Author => Self::Author,
+            DateFieldArg::Committer => Self::Committer,
+        }
+    }
+}
+
 impl From<Mode> for cli::RetrievalMode {
     fn from(mode: Mode) -> Self {
         match mode {
@@ -33,6 +56,155 @@ impl From<Mode> for cli::RetrievalMode {
     }
 }
 
+/// Metadata filters for `search`, kept apart from the ranking options so the
+/// subcommand stays small and the mapping to [`cli::SearchFilters`] is one
+/// place.
+#[derive(Args)]
+struct FilterArgs {
+    /// Filter by author or co-author name (repeatable; any may match)
+    #[arg(long, value_name = "NAME")]
+    author: Vec<String>,
+
+    /// Leave out commits by this author or co-author name (repeatable)
+    #[arg(long, value_name = "NAME")]
+    exclude_author: Vec<String>,
+
+    /// Filter by author or co-author email (repeatable; any may match)
+    #[arg(long)]
+    email: Vec<String>,
+
+    /// Leave out commits by this author or co-author email (repeatable)
+    #[arg(long, value_name = "EMAIL")]
+    exclude_email: Vec<String>,
+
+    /// Filter by who applied the commit, name or email (repeatable; any may
+    /// match)
+    #[arg(long)]
+    committer: Vec<String>,
+
+    /// Leave out commits applied by this name or email (repeatable)
+    #[arg(long, value_name = "COMMITTER")]
+    exclude_committer: Vec<String>,
+
+    /// Filter by commits after this date: YYYY-MM-DD, RFC 3339, or relative
+    /// like yesterday, '2 weeks ago' or 'last quarter'
+    #[arg(long, visible_alias = "since", value_name = "DATE")]
+    after: Option<String>,
+
+    /// Filter by commits before this date, in the same forms as --after
+    #[arg(long, visible_alias = "until", value_name = "DATE")]
+    before: Option<String>,
+
+    /// Which date --after and --before compare against
+    #[arg(long, value_enum, default_value_t = DateFieldArg::Author)]
+    date_field: DateFieldArg,
+
+    /// Filter by file path, or a glob such as 'src/**/*.rs' (repeatable;
+    /// any may match)
+    #[arg(long, value_name = "PATH")]
+    file: Vec<String>,
+
+    /// Leave out commits that only touched paths like this (repeatable)
+    #[arg(long, value_name = "PATH")]
+    exclude_file: Vec<String>,
+
+    /// Only commits whose message matches this regex, ignoring case
+    /// (repeatable; any may match)
+    #[arg(long, value_name = "REGEX")]
+    message: Vec<String>,
+
+    /// Leave out commits whose message matches this regex (repeatable)
+    #[arg(long, value_name = "REGEX")]
+    exclude_message: Vec<String>,
+
+    /// Only commits that added a file (a --file one, when given)
+    #[arg(long)]
+    added: bool,
+
+    /// Only commits that deleted a file (a --file one, when given)
+    #[arg(long)]
+    deleted: bool,
+
+    /// Only commits changing at least N lines (in --file, when given)
+    #[arg(long, value_name = "N")]
+    min_lines: Option<usize>,
+
+    /// Only commits that changed this function or type (exact name)
+    #[arg(long, value_name = "NAME")]
+    symbol: Option<String>,
+
+    /// Only commits with this trailer, e.g. reviewed-by=bob or just
+    /// change-id (repeatable; all must match)
+    #[arg(long, value_name = "KEY[=VALUE]")]
+    trailer: Vec<String>,
+
+    /// Only Conventional Commits of these types, e.g. fix,perf
+    #[arg(long = "type", value_name = "TYPE", value_delimiter = ',')]
+    types: Vec<String>,
+
+    /// Leave out Conventional Commits of these types, e.g. chore,docs
+    #[arg(long, value_name = "TYPE", value_delimiter = ',')]
+    exclude_type: Vec<String>,
+
+    /// Only Conventional Commits with this scope
+    #[arg(long)]
+    scope: Option<String>,
+
+    /// Only commits marked as breaking changes
+    #[arg(long)]
+    breaking: bool,
+
+    /// Only commits referencing this issue, e.g. '#123', GH-123 or JIRA-456
+    #[arg(long, value_name = "REF")]
+    issue: Option<String>,
+
+    /// Only commits from a pull request with this label (after import-prs)
+    #[arg(long, value_name = "NAME")]
+    label: Option<String>,
+
+    /// Only the commit that landed this pull request, e.g. 123 or '#123'
+    #[arg(long, value_name = "NUMBER")]
+    pr: Option<String>,
+
+    /// Only commits reachable from this ref, or in a range such as
+    /// main..feature; ^REF leaves a ref's history out (repeatable)
+    #[arg(long = "in", value_name = "REV")]
+    revisions: Vec<String>,
+}
+
+impl From<FilterArgs> for cli::SearchFilters {
+    fn from(args: FilterArgs) -> Self {
+        Self {
+            authors: args.author,
+            exclude_authors: args.exclude_author,
+            emails: args.email,
+            exclude_emails: args.exclude_email,
+            committers: args.committer,
+            exclude_committers: args.exclude_committer,
+            after: args.after,
+            before: args.before,
+            date_field: args.date_field.into(),
+            files: args.file,
+            exclude_files: args.exclude_file,
+            messages: args.message,
+            exclude_messages: args.exclude_message,
+            added: args.added,
+            deleted: args.deleted,
+            min_lines: args.min_lines,
+            symbol: args.symbol,
+            trailer: args.trailer,
+            types: args.types,
+            exclude_types: args.exclude_type,
+            scope: args.scope,
+            breaking: args.breaking,
+            issue: args.issue,
+            label: args.label,
+            pr: args.pr,
+            revisions: args.revisions,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize git-semantic (download models and prepare environment)
@@ -43,12 +215,17 @@ enum Commands {
     },
 
     /// Index the git repository
+    #[command(args_conflicts_with_subcommands = true)]
     Index {
+        #[command(subcommand)]
+        action: Option<IndexAction>,
+
         /// Only index commit messages (faster)
         #[arg(long)]
         quick: bool,
 
-        /// Index messages and diffs (more thorough)
+        /// Index messages and diffs (more thorough; the default unless
+        /// semantic.indexMode says quick)
         #[arg(long)]
         full: bool,
 
@@ -70,28 +247,21 @@ enum Commands {
 
     /// Search the git history
     Search {
-        /// Search query in natural language
+        /// Search query in natural language, optionally with inline filters
+        /// such as author:alice after:2024-01-01 file:src/auth/ -path:tests
         query: String,
 
-        /// Number of results to return
-        #[arg(short = 'n', long, default_value = "10")]
-        results: usize,
-
-        /// Filter by author
-        #[arg(long)]
-        author: Option<String>,
-
-        /// Filter by commits after this date (YYYY-MM-DD)
-        #[arg(long)]
-        after: Option<String>,
+        /// Only commits in these revisions, as git log takes them, after
+        /// `--`: a ref, a range like main..feature, or ^REF to leave one out
+        #[arg(value_name = "REVISION", last = true)]
+        range: Vec<String>,
 
-        /// Filter by commits before this date (YYYY-MM-DD)
-        #[arg(long)]
-        before: Option<String>,
+        /// Number of results to return [default: semantic.results, or 10]
+        #[arg(short = 'n', long)]
+        results: Option<usize>,
 
-        /// Filter by file path
-        #[arg(long)]
-        file: Option<String>,
+        #[command(flatten)]
+        filters: Box<FilterArgs>,
 
         /// Score every commit instead of using the approximate graph
         #[arg(long)]
@@ -101,17 +271,43 @@ enum Commands {
         #[arg(long, value_name = "N")]
         ef: Option<usize>,
 
-        /// Which retrievers to use
-        #[arg(long, value_enum, default_value_t = Mode::Hybrid)]
-        mode: Mode,
+        /// Which retrievers to use [default: semantic.mode, or hybrid]
+        #[arg(long, value_enum)]
+        mode: Option<Mode>,
 
         /// Spread results across distinct changes instead of near-duplicates
         #[arg(long)]
         diverse: bool,
 
         /// Diversity balance: 1.0 is pure relevance, 0.0 pure novelty
-        #[arg(long, value_name = "L", default_value_t = git_semantic::search::DEFAULT_LAMBDA)]
-        lambda: f32,
+        /// [default: semantic.lambda, or 0.7]
+        #[arg(long, value_name = "L")]
+        lambda: Option<f32>,
+
+        /// Emit machine-readable JSON instead of formatted text
+        #[arg(long)]
+        json: bool,
+
+        /// Search every repository listed in semantic.workspace as one
+        #[arg(long, conflicts_with = "path")]
+        workspace: bool,
+
+        /// Repository path (defaults to current directory)
+        #[arg(long)]
+        path: Option<String>,
+    },
+
+    /// List every commit that references an issue or ticket
+    Issue {
+        /// The reference: '#123', GH-123, JIRA-456, or owner/repo#123
+        reference: String,
+
+        /// Rank the commits by relevance to this instead of by date
+        query: Option<String>,
+
+        /// Which date orders the commits when there is no query
+        #[arg(long, value_enum, default_value_t = DateFieldArg::Author)]
+        date_field: DateFieldArg,
 
         /// Emit machine-readable JSON instead of formatted text
         #[arg(long)]
@@ -122,6 +318,63 @@ enum Commands {
         path: Option<String>,
     },
 
+    /// Attach pull request descriptions and labels from an exported JSON file
+    ///
+    /// Accepts an array in GitHub REST, `gh pr list --json`, or GitLab merge
+    /// request shape, e.g.:
+    ///
+    ///   gh pr list --state merged --limit 1000 \
+    ///     --json number,title,body,mergeCommit,labels > prs.json
+    #[command(name = "import-prs", verbatim_doc_comment)]
+    ImportPrs {
+        /// The exported JSON file
+        file: String,
+
+        /// Repository path (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+
+    /// Write the index, embeddings included, in a portable format
+    Export {
+        /// Output format: one JSON object per line, a header first
+        #[arg(long, value_enum, default_value_t = ExportFormat::Jsonl)]
+        format: ExportFormat,
+
+        /// File to write (defaults to stdout)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+
+        /// Repository path (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+
+    /// Rebuild the index from a file written by export
+    Import {
+        /// The JSON Lines file
+        file: String,
+
+        /// Replace an existing index
+        #[arg(long)]
+        force: bool,
+
+        /// Repository path (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+
+    /// Show each tuning setting's effective value and where it was set
+    ///
+    /// Settings are semantic.* git config keys, or the same names in a
+    /// [semantic] table of a checked-in .git-semantic.toml. A flag beats local
+    /// git config, which beats the file, which beats global git config.
+    Config {
+        /// Repository path (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+
     /// Show index statistics
     Stats {
         /// Repository path (defaults to current directory)
@@ -129,6 +382,12 @@ enum Commands {
         path: Option<String>,
     },
 
+    /// Manage the machine-wide embedding cache (semantic.embeddingCache)
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
     /// Print a shell completion script to stdout
     ///
     /// Source it from your shell config, or drop it somewhere on the
@@ -145,6 +404,46 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum IndexAction {
+    /// Publish the index under refs/semantic/index and push it to a remote
+    Push {
+        /// Remote name or URL to push to
+        #[arg(default_value = "origin")]
+        remote: String,
+
+        /// Repository path (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+
+    /// Fetch a remote's refs/semantic/index and index any newer commits locally
+    Fetch {
+        /// Remote name or URL to fetch from
+        #[arg(default_value = "origin")]
+        remote: String,
+
+        /// Replace an existing local index
+        #[arg(long)]
+        force: bool,
+
+        /// Repository path (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Remove the least recently used embeddings until the cache fits its cap
+    Prune {
+        /// Cap to prune to, e.g. 500m or 2g (defaults to
+        /// semantic.embeddingCacheSize, or 1g); 0 empties the cache
+        #[arg(long, value_name = "SIZE")]
+        max_size: Option<String>,
+    },
+}
+
 /// Diagnostics on stderr, silent unless `RUST_LOG` asks for them.
 ///
 /// Two separate things used to send log records to stdout on every run.
@@ -193,13 +492,39 @@ fn main() {
             cli::commands::init(force)
         }
         Commands::Index {
+            action: Some(IndexAction::Push { remote, path }),
+            ..
+        } => {
+            let repo_path = path.unwrap_or_else(|| ".".to_string());
+            cli::commands::index_push(&repo_path, &remote)
+        }
+        Commands::Index {
+            action:
+                Some(IndexAction::Fetch {
+                    remote,
+                    force,
+                    path,
+                }),
+            ..
+        } => {
+            let repo_path = path.unwrap_or_else(|| ".".to_string());
+            cli::commands::index_fetch(&repo_path, &remote, force)
+        }
+        Commands::Index {
+            action: None,
             quick,
             full,
             force,
             path,
         } => {
             let repo_path = path.unwrap_or_else(|| ".".to_string());
-            let include_diffs = full || !quick;
+            let include_diffs = if full {
+                Some(true)
+            } else if quick {
+                Some(false)
+            } else {
+                None
+            };
             cli::commands::index(&repo_path, include_diffs, force)
         }
         Commands::Update { path } => {
@@ -208,44 +533,75 @@ fn main() {
         }
         Commands::Search {
             query,
+            range,
             results,
-            author,
-            after,
-            before,
-            file,
+            filters,
             exact,
             ef,
             mode,
             diverse,
             lambda,
             json,
+            workspace,
             path,
         } => {
-            let repo_path = path.unwrap_or_else(|| ".".to_string());
-            let filters = cli::SearchFilters {
-                author,
-                after,
-                before,
-                file,
+            let mut filters: cli::SearchFilters = (*filters).into();
+            filters.revisions.extend(range);
+            let request = cli::SearchRequest {
+                query,
+                num_results: results,
+                filters,
+                exact,
+                ef,
+                mode: mode.map(Into::into),
+                diverse,
+                lambda,
+                json,
             };
-            cli::commands::search(
-                &repo_path,
-                cli::SearchRequest {
-                    query,
-                    num_results: results,
-                    filters,
-                    exact,
-                    ef,
-                    mode: mode.into(),
-                    diversity: diverse.then_some(lambda),
-                    json,
-                },
-            )
+            if workspace {
+                cli::commands::search_workspace(request)
+            } else {
+                let repo_path = path.unwrap_or_else(|| ".".to_string());
+                cli::commands::search(&repo_path, request)
+            }
+        }
+        Commands::Issue {
+            reference,
+            query,
+            date_field,
+            json,
+            path,
+        } => {
+            let repo_path = path.unwrap_or_else(|| ".".to_string());
+            cli::commands::issue(&repo_path, &reference, query, date_field.into(), json)
+        }
+        Commands::ImportPrs { file, path } => {
+            let repo_path = path.unwrap_or_else(|| ".".to_string());
+            cli::commands::import_prs(&repo_path, &file)
+        }
+        Commands::Export {
+            format: ExportFormat::Jsonl,
+            output,
+            path,
+        } => {
+            let repo_path = path.unwrap_or_else(|| ".".to_string());
+            cli::commands::export(&repo_path, output.as_deref())
+        }
+        Commands::Import { file, force, path } => {
+            let repo_path = path.unwrap_or_else(|| ".".to_string());
+            cli::commands::import(&repo_path, &file, force)
+        }
+        Commands::Config { path } => {
+            let repo_path = path.unwrap_or_else(|| ".".to_string());
+            cli::commands::config(&repo_path)
         }
         Commands::Stats { path } => {
             let repo_path = path.unwrap_or_else(|| ".".to_string());
             cli::commands::stats(&repo_path)
         }
+        Commands::Cache {
+            action: CacheAction::Prune { max_size },
+        } => cli::commands::cache_prune(max_size.as_deref()),
         Commands::Completions { shell } => {
             print_completions(shell);
             Ok(())
@@ -309,6 +665,9 @@ fn extract_hint(err: &anyhow::Error) -> Option<&'static str> {
     if let Some(e) = err.downcast_ref::<search::SearchError>() {
         return e.hint();
     }
+    if let Some(e) = err.downcast_ref::<config::ConfigError>() {
+        return e.hint();
+    }
     None
 }
 
@@ -326,5 +685,8 @@ fn extract_code(err: &anyhow::Error) -> Option<&'static str> {
     if let Some(e) = err.downcast_ref::<search::SearchError>() {
         return Some(e.code());
     }
+    if let Some(e) = err.downcast_ref::<config::ConfigError>() {
+        return Some(e.code());
+    }
     None
 }
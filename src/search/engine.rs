@@ -1,7 +1,11 @@
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
 use tracing::debug;
 
 use crate::cli::SearchFilters;
 use crate::embedding::ModelManager;
+use crate::git::GitError;
 use crate::index::{EXACT_SCAN_THRESHOLD, SemanticIndex};
 use crate::text::Bm25Index;
 use crate::vector::HnswIndex;
@@ -10,6 +14,7 @@ use crate::vector::scoring::{Scored, TopK, dot, normalize};
 use super::filter::FilterEngine;
 use super::fusion::{Ranking, reciprocal_rank_fusion};
 use super::mmr::{Candidate, rerank};
+use super::query::InlineQuery;
 use super::{SearchError, SearchResult};
 
 /// Which retrievers contribute to the ranking.
@@ -67,7 +72,7 @@ pub enum SearchStrategy {
 }
 
 /// Knobs for one query.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SearchOptions {
     pub num_results: usize,
     pub filters: SearchFilters,
@@ -79,6 +84,9 @@ pub struct SearchOptions {
     pub mode: RetrievalMode,
     /// Rerank for diversity with MMR at this lambda. `None` disables it.
     pub diversity: Option<f32>,
+    /// The repository `filters.revisions` are resolved in. `None` looks for
+    /// one from the current directory.
+    pub repository: Option<PathBuf>,
 }
 
 impl SearchOptions {
@@ -90,6 +98,7 @@ impl SearchOptions {
             ef: None,
             mode: RetrievalMode::default(),
             diversity: None,
+            repository: None,
         }
     }
 }
@@ -121,7 +130,8 @@ impl SearchEngine {
         Ok(Self { model_manager })
     }
 
-    /// Rank commits against `query`.
+    /// Rank commits against `query`, after lifting any inline filter
+    /// operators out of it (see [`InlineQuery`]).
     ///
     /// `graph` is the cached HNSW index when one is available. Passing `None`
     /// degrades to an exhaustive scan rather than failing, so search keeps
@@ -143,11 +153,26 @@ impl SearchEngine {
             ef,
             mode,
             diversity,
+            repository,
         } = options;
 
+        // Operators written in the query join the flags; only the words left
+        // over are embedded and matched.
+        let InlineQuery {
+            text: query,
+            filters,
+        } = InlineQuery::parse(query, filters)?;
+        let query = query.as_str();
+
         // Compile filters before embedding so a malformed date fails in
         // microseconds instead of after a model forward pass.
-        let filter = FilterEngine::new(filters)?;
+        let mut filter = FilterEngine::new(filters)?;
+        filter.follow_renames(index.entries.iter().map(|entry| &entry.commit));
+        if filter.has_revisions() {
+            let path = repository.as_deref().unwrap_or(Path::new("."));
+            let repo = Repository::discover(path).map_err(GitError::RepositoryNotFound)?;
+            filter.resolve_revisions(&repo)?;
+        }
 
         // Metadata filtering touches scalar fields only — cheap next to a
         // 384-dimensional dot product — so it runs first and defines the search
@@ -450,12 +475,7 @@ mod tests {
     use crate::vector::HnswParams;
 
     fn no_filters() -> SearchFilters {
-        SearchFilters {
-            author: None,
-            after: None,
-            before: None,
-            file: None,
-        }
+        SearchFilters::default()
     }
 
     /// The planner is pure given a compiled filter and candidate count, so its
@@ -511,6 +531,7 @@ mod tests {
                     .with_timezone(&chrono::Utc),
                 message: format!("commit number {idx}"),
                 diff_summary: String::new(),
+                ..Default::default()
             },
             embedding,
         }
@@ -588,7 +609,7 @@ mod tests {
             query,
             10,
             SearchFilters {
-                author: Some("bob".to_string()),
+                authors: vec!["bob".to_string()],
                 ..no_filters()
             },
         );
@@ -606,7 +627,7 @@ mod tests {
         let query = &index.entries[10].embedding;
 
         let filters = || SearchFilters {
-            author: Some("bob".to_string()),
+            authors: vec!["bob".to_string()],
             ..no_filters()
         };
 
@@ -2,29 +2,77 @@ use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum SearchError {
-    #[error("invalid date format '{value}' — expected YYYY-MM-DD")]
-    InvalidDateFormat {
+    #[error("invalid date '{value}'")]
+    InvalidDateFormat { value: String },
+
+    #[error("invalid trailer filter '{0}' — expected KEY or KEY=VALUE")]
+    InvalidTrailerFilter(String),
+
+    #[error("invalid pull request number '{0}'")]
+    InvalidPullRequest(String),
+
+    #[error("invalid query term '{term}': {reason}")]
+    InvalidQuery { term: String, reason: String },
+
+    #[error("the query is only filters — there is nothing to search for")]
+    EmptyQuery,
+
+    #[error("invalid path glob '{value}'")]
+    InvalidPathGlob {
         value: String,
         #[source]
-        source: chrono::ParseError,
+        source: globset::Error,
+    },
+
+    #[error("invalid message pattern '{value}'")]
+    InvalidMessagePattern {
+        value: String,
+        #[source]
+        source: regex::Error,
     },
 
     #[error("no index found for this repository")]
     IndexNotLoaded,
 
+    #[error("the workspace lists no repositories")]
+    EmptyWorkspace,
+
     #[error(transparent)]
     Embedding(#[from] crate::embedding::EmbeddingError),
+
+    #[error(transparent)]
+    Git(#[from] crate::git::GitError),
 }
 
 impl SearchError {
     /// Returns a user-facing hint for how to resolve this error.
     pub fn hint(&self) -> Option<&'static str> {
         match self {
-            Self::InvalidDateFormat { .. } => {
-                Some("Use the format YYYY-MM-DD, e.g. --after 2024-01-15")
+            Self::InvalidDateFormat { .. } => Some(
+                "Use YYYY-MM-DD, an RFC 3339 timestamp, or a relative date like yesterday, '2 weeks ago' or 'last quarter'",
+            ),
+            Self::InvalidTrailerFilter(_) => {
+                Some("Name the trailer key, e.g. --trailer reviewed-by=bob or --trailer fixes")
+            }
+            Self::InvalidPullRequest(_) => Some("Give the number, e.g. --pr 123 or --pr '#123'"),
+            Self::InvalidQuery { .. } => Some(
+                "Operators are written key:value, e.g. author:alice file:src/auth/ — quote values with spaces",
+            ),
+            Self::EmptyQuery => {
+                Some("Add the words to rank by, e.g. author:alice \"token refresh\"")
+            }
+            Self::InvalidPathGlob { .. } => {
+                Some("Globs use .gitignore syntax, e.g. --file 'src/**/*.rs'")
+            }
+            Self::InvalidMessagePattern { .. } => {
+                Some("Patterns use Rust regex syntax, e.g. --message '^fix(\\(auth\\))?:'")
             }
             Self::IndexNotLoaded => Some("Run: git-semantic index"),
+            Self::EmptyWorkspace => Some(
+                "Add repositories with: git config --global --add semantic.workspace ~/src/api",
+            ),
             Self::Embedding(_) => None, // Delegate to EmbeddingError's own hint
+            Self::Git(err) => err.hint(),
         }
     }
 
@@ -34,6 +82,14 @@ impl SearchError {
             Self::InvalidDateFormat { .. } => "E4001",
             Self::IndexNotLoaded => "E4002",
             Self::Embedding(_) => "E4003",
+            Self::InvalidTrailerFilter(_) => "E4004",
+            Self::InvalidPullRequest(_) => "E4005",
+            Self::EmptyWorkspace => "E4006",
+            Self::InvalidQuery { .. } => "E4007",
+            Self::EmptyQuery => "E4008",
+            Self::InvalidPathGlob { .. } => "E4009",
+            Self::InvalidMessagePattern { .. } => "E4010",
+            Self::Git(err) => err.code(),
         }
     }
 }
@@ -0,0 +1,365 @@
+//! Searching several repositories as one.
+//!
+//! A product split across repositories has its history split too. A workspace
+//! is the list of them, read from git config; each is searched on its own
+//! index and the results are merged into a single ranking.
+
+use std::path::PathBuf;
+
+use super::{RetrievalMode, SearchError, SearchOutcome, SearchResult, SearchStrategy};
+
+/// Git config key listing a workspace's repositories. Multi-valued, usually
+/// set globally; `~/` is expanded.
+pub const WORKSPACE_KEY: &str = "semantic.workspace";
+
+/// One repository of a workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceRepo {
+    /// What results are tagged with: the directory name, or the configured
+    /// path when two repositories share a directory name.
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// The repositories `search --workspace` covers, in configured order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Workspace {
+    pub repos: Vec<WorkspaceRepo>,
+}
+
+impl Workspace {
+    /// A workspace of `paths`, deduplicated, each named for its directory.
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut paths: Vec<PathBuf> = paths.into_iter().collect();
+        let mut seen = std::collections::HashSet::new();
+        paths.retain(|path| seen.insert(path.clone()));
+
+        let dir_name = |path: &PathBuf| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string())
+        };
+        let repos = paths
+            .iter()
+            .map(|path| {
+                let name = dir_name(path);
+                let shared = paths.iter().filter(|other| dir_name(other) == name).count() > 1;
+                WorkspaceRepo {
+                    name: if shared {
+                        path.display().to_string()
+                    } else {
+                        name
+                    },
+                    path: path.clone(),
+                }
+            })
+            .collect();
+        Self { repos }
+    }
+
+    /// Every [`WORKSPACE_KEY`] in `config`. Fails when there are none, since
+    /// searching nothing is never what was meant.
+    pub fn from_config(config: &git2::Config) -> Result<Self, SearchError> {
+        let mut paths = Vec::new();
+        if let Ok(entries) = config.multivar(WORKSPACE_KEY, None) {
+            let _ = entries.for_each(|entry| {
+                if let Ok(value) = entry.value() {
+                    let value = value.trim();
+                    if !value.is_empty() {
+                        paths.push(expand_home(value));
+                    }
+                }
+            });
+        }
+
+        let workspace = Self::new(paths);
+        if workspace.repos.is_empty() {
+            return Err(SearchError::EmptyWorkspace);
+        }
+        Ok(workspace)
+    }
+}
+
+/// `~/…` under the home directory, as git expands paths in its own config.
+fn expand_home(value: &str) -> PathBuf {
+    match (value.strip_prefix("~/"), directories::BaseDirs::new()) {
+        (Some(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ => PathBuf::from(value),
+    }
+}
+
+/// Results from every repository, in one ranking.
+#[derive(Debug)]
+pub struct FederatedOutcome {
+    /// The merged results, re-ranked from 1, with the workspace-wide
+    /// candidate count, strategy and mode.
+    pub outcome: SearchOutcome,
+    /// For each result, the index of the repository it came from.
+    pub repos: Vec<usize>,
+}
+
+/// Merge per-repository outcomes, in workspace order, into the best `k`.
+///
+/// A semantic search orders by cosine similarity, which is comparable across
+/// repositories — every index was embedded by the same model. Keyword scores
+/// are not: BM25 weighs terms by how rare they are in *its* corpus, and a
+/// hybrid hit's similarity says nothing of the keyword side that ranked it.
+/// Those modes fuse the per-repository rankings instead. Each hit sits in one
+/// ranking only, so reciprocal rank fusion comes down to interleaving them by
+/// rank, repositories in workspace order within a rank.
+pub fn merge(outcomes: Vec<SearchOutcome>, k: usize) -> FederatedOutcome {
+    let strategy = outcomes
+        .iter()
+        .map(|outcome| outcome.strategy)
+        .min_by_key(|strategy| match strategy {
+            // Report the weakest guarantee any repository got.
+            SearchStrategy::Approximate => 0,
+            SearchStrategy::ApproximateThenExact => 1,
+            SearchStrategy::Exact => 2,
+        })
+        .unwrap_or(SearchStrategy::Exact);
+    let semantic = outcomes.iter().any(|o| o.mode.uses_semantic());
+    let lexical = outcomes.iter().any(|o| o.mode.uses_lexical());
+    let mode = match (semantic, lexical) {
+        (true, true) => RetrievalMode::Hybrid,
+        (false, true) => RetrievalMode::Lexical,
+        _ => RetrievalMode::Semantic,
+    };
+    let candidate_count = outcomes.iter().map(|o| o.candidate_count).sum();
+    let diversified = outcomes.iter().any(|o| o.diversified);
+
+    let mut hits: Vec<(usize, SearchResult)> = outcomes
+        .into_iter()
+        .enumerate()
+        .flat_map(|(repo, outcome)| outcome.results.into_iter().map(move |hit| (repo, hit)))
+        .collect();
+    let by_rank = |(repo_a, a): &(usize, SearchResult), (repo_b, b): &(usize, SearchResult)| {
+        a.rank.cmp(&b.rank).then(repo_a.cmp(repo_b))
+    };
+    if mode == RetrievalMode::Semantic {
+        hits.sort_by(|x, y| {
+            y.1.similarity
+                .partial_cmp(&x.1.similarity)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| by_rank(x, y))
+        });
+    } else {
+        hits.sort_by(by_rank);
+    }
+    hits.truncate(k);
+
+    let mut repos = Vec::with_capacity(hits.len());
+    let results = hits
+        .into_iter()
+        .enumerate()
+        .map(|(position, (repo, mut hit))| {
+            repos.push(repo);
+            hit.rank = position + 1;
+            hit
+        })
+        .collect();
+
+    FederatedOutcome {
+        outcome: SearchOutcome {
+            results,
+            strategy,
+            candidate_count,
+            mode,
+            diversified,
+        },
+        repos,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::CommitInfo;
+
+    fn hit(hash: &str, similarity: f32, rank: usize) -> SearchResult {
+        SearchResult {
+            commit: CommitInfo {
+                hash: hash.to_string(),
+                ..Default::default()
+            },
+            similarity,
+            rank,
+        }
+    }
+
+    fn outcome(results: Vec<SearchResult>, strategy: SearchStrategy) -> SearchOutcome {
+        in_mode(results, strategy, RetrievalMode::Semantic)
+    }
+
+    fn in_mode(
+        results: Vec<SearchResult>,
+        strategy: SearchStrategy,
+        mode: RetrievalMode,
+    ) -> SearchOutcome {
+        SearchOutcome {
+            candidate_count: 10,
+            results,
+            strategy,
+            mode,
+            diversified: false,
+        }
+    }
+
+    fn hashes(merged: &FederatedOutcome) -> Vec<&str> {
+        merged
+            .outcome
+            .results
+            .iter()
+            .map(|result| result.commit.hash.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn results_are_merged_by_similarity_across_repositories() {
+        let merged = merge(
+            vec![
+                outcome(
+                    vec![hit("a1", 0.9, 1), hit("a2", 0.5, 2)],
+                    SearchStrategy::Exact,
+                ),
+                outcome(
+                    vec![hit("b1", 0.7, 1), hit("b2", 0.6, 2)],
+                    SearchStrategy::Exact,
+                ),
+            ],
+            3,
+        );
+
+        let hashes: Vec<&str> = merged
+            .outcome
+            .results
+            .iter()
+            .map(|result| result.commit.hash.as_str())
+            .collect();
+        assert_eq!(hashes, ["a1", "b1", "b2"]);
+        assert_eq!(merged.repos, [0, 1, 1]);
+        let ranks: Vec<usize> = merged.outcome.results.iter().map(|r| r.rank).collect();
+        assert_eq!(ranks, [1, 2, 3]);
+        assert_eq!(merged.outcome.candidate_count, 20);
+    }
+
+    #[test]
+    fn keyword_hits_are_interleaved_by_rank() {
+        let merged = merge(
+            vec![
+                in_mode(
+                    vec![hit("a1", f32::NAN, 1), hit("a2", f32::NAN, 2)],
+                    SearchStrategy::Exact,
+                    RetrievalMode::Lexical,
+                ),
+                in_mode(
+                    vec![hit("b1", f32::NAN, 1), hit("b2", f32::NAN, 2)],
+                    SearchStrategy::Exact,
+                    RetrievalMode::Lexical,
+                ),
+            ],
+            10,
+        );
+
+        assert_eq!(hashes(&merged), ["a1", "b1", "a2", "b2"]);
+        assert_eq!(merged.repos, [0, 1, 0, 1]);
+        assert_eq!(merged.outcome.mode, RetrievalMode::Lexical);
+    }
+
+    #[test]
+    fn hybrid_hits_keep_their_repository_rank_over_similarity() {
+        // b's keyword side put b1 first despite its weak cosine; a2 has the
+        // best cosine but a ranked it second.
+        let merged = merge(
+            vec![
+                in_mode(
+                    vec![hit("a1", 0.4, 1), hit("a2", 0.9, 2)],
+                    SearchStrategy::Exact,
+                    RetrievalMode::Hybrid,
+                ),
+                in_mode(
+                    vec![hit("b1", 0.1, 1), hit("b2", f32::NAN, 2)],
+                    SearchStrategy::Exact,
+                    RetrievalMode::Hybrid,
+                ),
+            ],
+            3,
+        );
+
+        assert_eq!(hashes(&merged), ["a1", "b1", "a2"]);
+        assert_eq!(merged.outcome.mode, RetrievalMode::Hybrid);
+        let ranks: Vec<usize> = merged.outcome.results.iter().map(|r| r.rank).collect();
+        assert_eq!(ranks, [1, 2, 3]);
+    }
+
+    #[test]
+    fn the_weakest_strategy_is_reported() {
+        let merged = merge(
+            vec![
+                outcome(vec![], SearchStrategy::Exact),
+                outcome(vec![], SearchStrategy::Approximate),
+            ],
+            10,
+        );
+        assert_eq!(merged.outcome.strategy, SearchStrategy::Approximate);
+    }
+
+    #[test]
+    fn repositories_are_named_for_their_directory_unless_that_is_ambiguous() {
+        let workspace = Workspace::new([
+            PathBuf::from("/src/api"),
+            PathBuf::from("/src/web/app"),
+            PathBuf::from("/vendor/app"),
+            PathBuf::from("/src/api"),
+        ]);
+        let names: Vec<&str> = workspace.repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["api", "/src/web/app", "/vendor/app"]);
+    }
+
+    #[test]
+    fn a_leading_tilde_is_the_home_directory() {
+        let home = directories::BaseDirs::new()
+            .unwrap()
+            .home_dir()
+            .to_path_buf();
+        assert_eq!(expand_home("~/src/api"), home.join("src/api"));
+        assert_eq!(expand_home("/src/~/api"), PathBuf::from("/src/~/api"));
+    }
+
+    #[test]
+    fn an_empty_workspace_is_an_error() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = git2::Config::open(&dir.path().join("config")).unwrap();
+        assert!(matches!(
+            Workspace::from_config(&config),
+            Err(SearchError::EmptyWorkspace)
+        ));
+    }
+
+    #[test]
+    fn the_workspace_is_read_from_git_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = git2::Config::open(&dir.path().join("config")).unwrap();
+        config
+            .set_multivar(WORKSPACE_KEY, "^$", "/src/api")
+            .unwrap();
+        config
+            .set_multivar(WORKSPACE_KEY, "^$", "/src/web")
+            .unwrap();
+
+        let workspace = Workspace::from_config(&config).unwrap();
+        assert_eq!(
+            workspace.repos,
+            [
+                WorkspaceRepo {
+                    name: "api".to_string(),
+                    path: PathBuf::from("/src/api"),
+                },
+                WorkspaceRepo {
+                    name: "web".to_string(),
+                    path: PathBuf::from("/src/web"),
+                },
+            ]
+        );
+    }
+}
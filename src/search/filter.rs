@@ -1,7 +1,15 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::{BTreeSet, HashSet};
+
+use chrono::{
+    DateTime, Datelike, Days, Local, Months, NaiveDate, NaiveDateTime, Offset, TimeDelta, TimeZone,
+    Utc,
+};
+use git2::Repository;
+use globset::{GlobBuilder, GlobMatcher};
+use regex::{Regex, RegexBuilder};
 
 use crate::cli::SearchFilters;
-use crate::git::CommitInfo;
+use crate::git::{ChangeStatus, CommitInfo, DateField, FileChange, anchored, issues};
 
 use super::SearchError;
 
@@ -10,62 +18,237 @@ use super::SearchError;
 /// The previous version re-parsed `--after` and `--before` on every call and
 /// only knew how to transform a fully materialized result list. Search now
 /// evaluates filters *before* scoring, so it needs a per-commit predicate that
-/// is cheap enough to run N times — hence parsing up front and lowercasing the
-/// author once.
+/// is cheap enough to run N times — hence parsing up front, lowercasing names
+/// once, and compiling globs and regexes here.
+///
+/// Repeated values of one filter are alternatives — `--author alice --author
+/// bob` is either of them — and each `exclude_*` list drops whatever it
+/// matches.
 pub struct FilterEngine {
-    author: Option<String>,
+    authors: Vec<String>,
+    exclude_authors: Vec<String>,
+    emails: Vec<String>,
+    exclude_emails: Vec<String>,
+    committers: Vec<String>,
+    exclude_committers: Vec<String>,
     after: Option<DateTime<Utc>>,
     before: Option<DateTime<Utc>>,
-    file: Option<String>,
+    date_field: DateField,
+    files: Vec<PathPattern>,
+    /// Earlier names of the `--file` targets, found by [`Self::follow_renames`].
+    file_aliases: Vec<String>,
+    exclude_files: Vec<PathPattern>,
+    messages: Vec<Regex>,
+    exclude_messages: Vec<Regex>,
+    added: bool,
+    deleted: bool,
+    min_lines: Option<usize>,
+    symbol: Option<String>,
+    /// `(key, lowercase value)` pairs; a `None` value only needs the key.
+    trailers: Vec<(String, Option<String>)>,
+    /// Lowercased, so `--type Fix` still matches.
+    types: Vec<String>,
+    exclude_types: Vec<String>,
+    scope: Option<String>,
+    breaking: bool,
+    /// Normalized, so `GH-12` and `#12` ask for the same thing.
+    issue: Option<String>,
+    label: Option<String>,
+    pr: Option<u64>,
+    revisions: Vec<String>,
+    /// Full ids of the commits `revisions` reach, once
+    /// [`Self::resolve_revisions`] has run.
+    reachable: Option<HashSet<String>>,
 }
 
 impl FilterEngine {
-    /// Compile raw CLI strings, surfacing date errors immediately.
+    /// Compile raw CLI strings, surfacing date, glob and regex errors
+    /// immediately.
     pub fn new(filters: SearchFilters) -> Result<Self, SearchError> {
+        Self::at(filters, Local::now())
+    }
+
+    /// [`Self::new`], reading relative dates such as `yesterday` against
+    /// `now`, and days and times without an offset in its time zone, instead
+    /// of the local clock and zone.
+    fn at<Tz: TimeZone>(filters: SearchFilters, now: DateTime<Tz>) -> Result<Self, SearchError> {
         let after = filters
             .after
             .as_deref()
-            .map(|raw| parse_day(raw, DayEdge::Start))
+            .map(|raw| parse_date(raw, DayEdge::Start, &now))
             .transpose()?;
 
         let before = filters
             .before
             .as_deref()
-            .map(|raw| parse_day(raw, DayEdge::End))
+            .map(|raw| parse_date(raw, DayEdge::End, &now))
+            .transpose()?;
+
+        let trailers = filters
+            .trailer
+            .iter()
+            .map(|raw| parse_trailer(raw))
+            .collect::<Result<_, _>>()?;
+
+        let pr = filters
+            .pr
+            .as_deref()
+            .map(|raw| {
+                raw.trim()
+                    .trim_start_matches(['#', '!'])
+                    .parse()
+                    .map_err(|_| SearchError::InvalidPullRequest(raw.to_string()))
+            })
             .transpose()?;
 
         Ok(Self {
-            author: filters.author.map(|a| a.to_lowercase()),
+            authors: lowercase(filters.authors),
+            exclude_authors: lowercase(filters.exclude_authors),
+            emails: lowercase(filters.emails),
+            exclude_emails: lowercase(filters.exclude_emails),
+            committers: lowercase(filters.committers),
+            exclude_committers: lowercase(filters.exclude_committers),
             after,
             before,
-            file: filters.file,
+            date_field: filters.date_field,
+            files: compile(filters.files, PathPattern::new)?,
+            file_aliases: Vec::new(),
+            exclude_files: compile(filters.exclude_files, PathPattern::new)?,
+            messages: compile(filters.messages, message_pattern)?,
+            exclude_messages: compile(filters.exclude_messages, message_pattern)?,
+            added: filters.added,
+            deleted: filters.deleted,
+            min_lines: filters.min_lines,
+            symbol: filters.symbol,
+            trailers,
+            types: conventional_types(filters.types),
+            exclude_types: conventional_types(filters.exclude_types),
+            scope: filters.scope,
+            breaking: filters.breaking,
+            issue: filters.issue.as_deref().map(issues::normalize),
+            label: filters.label,
+            pr,
+            revisions: filters.revisions,
+            reachable: None,
         })
     }
 
+    /// Extend `--file` to the names its targets had before being renamed, the
+    /// way `git log --follow` does.
+    ///
+    /// Each commit only records its own renames, so the chain is only visible
+    /// across the whole index — hence a separate pass rather than part of
+    /// [`Self::new`]. Chains are followed to a fixed point, so a file moved
+    /// twice is still found under its first name.
+    pub fn follow_renames<'a>(&mut self, commits: impl IntoIterator<Item = &'a CommitInfo>) {
+        if self.files.is_empty() {
+            return;
+        }
+
+        let renames: Vec<(&str, &str)> =
+            commits.into_iter().flat_map(CommitInfo::renames).collect();
+        let mut names: BTreeSet<&str> = BTreeSet::new();
+
+        loop {
+            let known = names.len();
+            for &(from, to) in &renames {
+                if self.files.iter().any(|file| file.names(to)) || names.contains(to) {
+                    names.insert(from);
+                }
+            }
+            if names.len() == known {
+                break;
+            }
+        }
+
+        self.file_aliases = names.into_iter().map(str::to_string).collect();
+    }
+
+    /// Whether the search is limited to revisions that still need resolving
+    /// against the repository.
+    pub fn has_revisions(&self) -> bool {
+        !self.revisions.is_empty()
+    }
+
+    /// Limit the search to the commits `--in` reaches in `repo`.
+    ///
+    /// Like [`Self::follow_renames`], this needs more than the filters
+    /// themselves, so it is a separate step after [`Self::new`].
+    pub fn resolve_revisions(&mut self, repo: &Repository) -> Result<(), SearchError> {
+        if self.has_revisions() {
+            self.reachable = Some(crate::git::reachable(repo, &self.revisions)?);
+        }
+        Ok(())
+    }
+
     /// True when at least one filter would exclude something.
     pub fn is_active(&self) -> bool {
-        self.author.is_some()
+        !self.authors.is_empty()
+            || !self.exclude_authors.is_empty()
+            || !self.emails.is_empty()
+            || !self.exclude_emails.is_empty()
+            || !self.committers.is_empty()
+            || !self.exclude_committers.is_empty()
             || self.after.is_some()
             || self.before.is_some()
-            || self.file.is_some()
+            || !self.files.is_empty()
+            || !self.exclude_files.is_empty()
+            || !self.messages.is_empty()
+            || !self.exclude_messages.is_empty()
+            || self.added
+            || self.deleted
+            || self.min_lines.is_some()
+            || self.symbol.is_some()
+            || !self.trailers.is_empty()
+            || !self.types.is_empty()
+            || !self.exclude_types.is_empty()
+            || self.scope.is_some()
+            || self.breaking
+            || self.issue.is_some()
+            || self.label.is_some()
+            || self.pr.is_some()
+            || self.has_revisions()
     }
 
     /// Whether `commit` passes every active filter.
     pub fn matches(&self, commit: &CommitInfo) -> bool {
-        if let Some(author) = &self.author
-            && !commit.author.to_lowercase().contains(author)
+        // A set lookup, so it goes first: `--in release/2.3` usually rules
+        // out most of the index on its own.
+        if let Some(reachable) = &self.reachable
+            && !reachable.contains(&commit.hash)
         {
             return false;
         }
 
+        // Co-authors count: `--author bob` should find the pairing session
+        // Alice committed with Bob in the trailer.
+        if !admits(&self.authors, &self.exclude_authors, |author| {
+            commit.credits_name(author)
+        }) {
+            return false;
+        }
+
+        if !admits(&self.emails, &self.exclude_emails, |email| {
+            commit.credits_email(email)
+        }) {
+            return false;
+        }
+
+        if !admits(&self.committers, &self.exclude_committers, |committer| {
+            applied_by(commit, committer)
+        }) {
+            return false;
+        }
+
+        let date = commit.date_for(self.date_field);
         if let Some(after) = self.after
-            && commit.date < after
+            && date < after
         {
             return false;
         }
 
         if let Some(before) = self.before
-            && commit.date > before
+            && date > before
         {
             return false;
         }
@@ -73,47 +256,448 @@ impl FilterEngine {
         // Matches against the commit's recorded path list, not the raw diff
         // text, so `--file src/auth.rs` no longer depends on that string
         // happening to appear inside an added or removed line.
-        if let Some(file) = &self.file
-            && !commit.touches_path(file)
+        if !self.files.is_empty()
+            && !self.files.iter().any(|file| file.touched_by(commit))
+            && !commit.files.iter().any(|change| self.is_alias(change))
+        {
+            return false;
+        }
+
+        // Excluding `tests` drops a commit that only touched tests, not one
+        // that fixed the code and its test together. Without recorded paths
+        // there is nothing to exclude.
+        if !self.exclude_files.is_empty()
+            && !commit.files.is_empty()
+            && !commit.files.iter().any(|change| self.selects(change))
+        {
+            return false;
+        }
+
+        if !admits(&self.messages, &self.exclude_messages, |pattern| {
+            pattern.is_match(&commit.message)
+        }) {
+            return false;
+        }
+
+        // Exact, case-sensitive: `encode_text` should not also pull in
+        // `encode_text_batch` or every `encode*` helper.
+        if let Some(symbol) = &self.symbol
+            && !commit.touches_symbol(symbol)
+        {
+            return false;
+        }
+
+        if !self
+            .trailers
+            .iter()
+            .all(|(key, value)| commit.has_trailer(key, value.as_deref()))
+        {
+            return false;
+        }
+
+        // A commit that does not follow the convention has no type, scope or
+        // marker, so any of these filters excludes it — except a type
+        // exclusion, which it cannot match.
+        let conventional = commit.conventional.as_ref();
+        if !self.types.is_empty() && !conventional.is_some_and(|c| self.types.contains(&c.kind)) {
+            return false;
+        }
+
+        if conventional.is_some_and(|c| self.exclude_types.contains(&c.kind)) {
+            return false;
+        }
+
+        if let Some(scope) = &self.scope
+            && !conventional.is_some_and(|c| c.in_scope(scope))
+        {
+            return false;
+        }
+
+        if self.breaking && !conventional.is_some_and(|c| c.breaking) {
+            return false;
+        }
+
+        if let Some(issue) = &self.issue
+            && !commit.mentions_issue(issue)
         {
             return false;
         }
 
+        // Without an imported pull request there is nothing to match, so both
+        // filters exclude the commit.
+        let pull_request = commit.pull_request.as_ref();
+        if let Some(label) = &self.label
+            && !pull_request
+                .is_some_and(|pr| pr.labels.iter().any(|l| l.eq_ignore_ascii_case(label)))
+        {
+            return false;
+        }
+
+        if let Some(number) = self.pr
+            && pull_request.is_none_or(|pr| pr.number != number)
+        {
+            return false;
+        }
+
+        if self.added && !self.has_change(commit, ChangeStatus::Added) {
+            return false;
+        }
+
+        if self.deleted && !self.has_change(commit, ChangeStatus::Deleted) {
+            return false;
+        }
+
+        if let Some(min_lines) = self.min_lines {
+            let changed: usize = commit
+                .files
+                .iter()
+                .filter(|change| self.selects(change))
+                .map(FileChange::lines_changed)
+                .sum();
+            if changed < min_lines {
+                return false;
+            }
+        }
+
         true
     }
+
+    fn has_change(&self, commit: &CommitInfo, status: ChangeStatus) -> bool {
+        commit
+            .files
+            .iter()
+            .any(|change| change.status == status && self.selects(change))
+    }
+
+    /// Whether the change filters should look at `change`: every file when
+    /// there is no `--file`, otherwise only the files it names, and in either
+    /// case none that are excluded.
+    fn selects(&self, change: &FileChange) -> bool {
+        if self
+            .exclude_files
+            .iter()
+            .any(|excluded| excluded.touches(change))
+        {
+            return false;
+        }
+        self.files.is_empty()
+            || self.files.iter().any(|file| file.touches(change))
+            || self.is_alias(change)
+    }
+
+    /// Whether `change` is to or from one of the earlier names found by
+    /// [`Self::follow_renames`]. Those are whole paths, so they compare
+    /// exactly: `lib/a.rs` must not pull in `lib/a.rs.orig`.
+    fn is_alias(&self, change: &FileChange) -> bool {
+        self.file_aliases.iter().any(|old| {
+            change.path == *old
+                || (change.status == ChangeStatus::Renamed
+                    && change.old_path.as_deref() == Some(old.as_str()))
+        })
+    }
+}
+
+/// A `--file` value: a substring of the path, or — once it uses `*`, `?`, `[`
+/// or `{` — a glob anchored the way `.gitignore` anchors it.
+enum PathPattern {
+    Substring(String),
+    Glob(GlobMatcher),
+}
+
+impl PathPattern {
+    fn new(raw: String) -> Result<Self, SearchError> {
+        if !raw.contains(['*', '?', '[', '{']) {
+            return Ok(Self::Substring(raw));
+        }
+        GlobBuilder::new(&anchored(&raw))
+            .literal_separator(true)
+            .build()
+            .map(|glob| Self::Glob(glob.compile_matcher()))
+            .map_err(|source| SearchError::InvalidPathGlob { value: raw, source })
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        match self {
+            Self::Substring(needle) => path.contains(needle.as_str()),
+            Self::Glob(glob) => glob.is_match(path),
+        }
+    }
+
+    /// Whether `path` is what the pattern names, for seeding rename chains.
+    ///
+    /// Stricter than [`Self::matches`]: a substring counts only as whole
+    /// path components, so `src/` does not make the source of every rename
+    /// under `src` an alias, and `a.rs` does not pick up `data.rs`.
+    fn names(&self, path: &str) -> bool {
+        let Self::Substring(needle) = self else {
+            return self.matches(path);
+        };
+        let needle = needle.trim_matches('/');
+        if needle.is_empty() {
+            return false;
+        }
+        path.match_indices(needle).any(|(at, _)| {
+            let before = &path[..at];
+            let after = &path[at + needle.len()..];
+            (before.is_empty() || before.ends_with('/'))
+                && (after.is_empty() || after.starts_with('/'))
+        })
+    }
+
+    /// Whether `change` is to a matching path, or renamed from one.
+    fn touches(&self, change: &FileChange) -> bool {
+        match self {
+            Self::Substring(needle) => change.touches(needle),
+            Self::Glob(_) => {
+                self.matches(&change.path)
+                    || (change.status == ChangeStatus::Renamed
+                        && change
+                            .old_path
+                            .as_deref()
+                            .is_some_and(|old| self.matches(old)))
+            }
+        }
+    }
+
+    /// Whether `commit` changed a matching path. An index too old to record
+    /// paths only has its summary to go on, which a glob cannot be matched
+    /// against.
+    fn touched_by(&self, commit: &CommitInfo) -> bool {
+        match self {
+            Self::Substring(needle) => commit.touches_path(needle),
+            Self::Glob(_) => commit.files.iter().any(|change| self.touches(change)),
+        }
+    }
+}
+
+/// Whether a commit passes a filter with alternatives `any` and exclusions
+/// `none`: it must match one of `any`, when there are some, and none of `none`.
+fn admits<T>(any: &[T], none: &[T], matches: impl Fn(&T) -> bool) -> bool {
+    (any.is_empty() || any.iter().any(&matches)) && !none.iter().any(&matches)
+}
+
+/// Whether `needle` (lowercase) appears in the name or email of whoever
+/// applied the commit, raw or through the mailmap.
+fn applied_by(commit: &CommitInfo, needle: &str) -> bool {
+    [
+        &commit.committer,
+        &commit.committer_email,
+        &commit.canonical_committer.name,
+        &commit.canonical_committer.email,
+    ]
+    .iter()
+    .any(|field| field.to_lowercase().contains(needle))
+}
+
+fn lowercase(values: Vec<String>) -> Vec<String> {
+    values
+        .into_iter()
+        .map(|value| value.to_lowercase())
+        .collect()
+}
+
+fn conventional_types(types: Vec<String>) -> Vec<String> {
+    types
+        .iter()
+        .map(|kind| kind.trim().to_lowercase())
+        .filter(|kind| !kind.is_empty())
+        .collect()
+}
+
+fn compile<T>(
+    values: Vec<String>,
+    pattern: impl Fn(String) -> Result<T, SearchError>,
+) -> Result<Vec<T>, SearchError> {
+    values.into_iter().map(pattern).collect()
+}
+
+/// A `--message` regex, matched case-insensitively like every other text
+/// filter; `(?-i)` turns that off.
+fn message_pattern(raw: String) -> Result<Regex, SearchError> {
+    RegexBuilder::new(&raw)
+        .case_insensitive(true)
+        .build()
+        .map_err(|source| SearchError::InvalidMessagePattern { value: raw, source })
+}
+
+/// `key` or `key=value`, with surrounding whitespace dropped and the value
+/// lowercased for matching.
+fn parse_trailer(raw: &str) -> Result<(String, Option<String>), SearchError> {
+    let (key, value) = match raw.split_once('=') {
+        Some((key, value)) => (key.trim(), Some(value.trim().to_lowercase())),
+        None => (raw.trim(), None),
+    };
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return Err(SearchError::InvalidTrailerFilter(raw.to_string()));
+    }
+    Ok((key.to_string(), value))
 }
 
+/// Which end of a named period a bound takes: `--after yesterday` starts at
+/// the beginning of yesterday, `--before yesterday` runs to its end.
+#[derive(Clone, Copy)]
 enum DayEdge {
     Start,
     End,
 }
 
-/// Parse `YYYY-MM-DD` to an inclusive bound at the requested edge of the day.
-fn parse_day(raw: &str, edge: DayEdge) -> Result<DateTime<Utc>, SearchError> {
-    let date =
-        NaiveDate::parse_from_str(raw, "%Y-%m-%d").map_err(|e| SearchError::InvalidDateFormat {
-            value: raw.to_string(),
-            source: e,
-        })?;
+/// Parse a date bound the way `git log --since` reads one, relative to `now`:
+///
+/// - `2024-01-15`, a whole day
+/// - `2024-01-15T09:30:00+02:00` (RFC 3339) or `2024-01-15 09:30`, an instant
+/// - `now`, `today`, `yesterday`
+/// - `3 days ago`, `2.weeks.ago`, `an hour ago`, an instant
+/// - `this week`, `last month`, `last quarter`, `last year`, a whole period
+///
+/// Days, periods and times without an offset are in `now`'s time zone — the
+/// local one, as in git. Weeks start on Monday, and a bound at a whole day or
+/// period is inclusive at the requested edge.
+fn parse_date<Tz: TimeZone>(
+    raw: &str,
+    edge: DayEdge,
+    now: &DateTime<Tz>,
+) -> Result<DateTime<Utc>, SearchError> {
+    let invalid = || SearchError::InvalidDateFormat {
+        value: raw.to_string(),
+    };
+    let text = raw.trim().to_lowercase();
+
+    if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+        return Ok(day_edge(date, date, edge, now));
+    }
+    if let Ok(instant) = DateTime::parse_from_rfc3339(raw.trim()) {
+        return Ok(instant.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(instant) = NaiveDateTime::parse_from_str(&text, format) {
+            return Ok(in_zone(instant, edge, now));
+        }
+    }
+
+    let today = now.date_naive();
+    let words: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || c == '.')
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let (first, last) = match words.as_slice() {
+        ["now"] => return Ok(now.with_timezone(&Utc)),
+        ["today"] => period("day", 0, today),
+        ["yesterday"] => period("day", 1, today),
+        ["this", unit] => period(unit, 0, today),
+        ["last", unit] => period(unit, 1, today),
+        [count, unit, "ago"] => {
+            let count = match *count {
+                "a" | "an" | "one" => 1,
+                digits => digits.parse().map_err(|_| invalid())?,
+            };
+            return ago(count, unit, now).ok_or_else(invalid);
+        }
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+
+    Ok(day_edge(first, last, edge, now))
+}
+
+/// The instant `count` `unit`s before `now`. Months and years step by the
+/// calendar, so a month before March 31 is the last day of February.
+fn ago<Tz: TimeZone>(count: u32, unit: &str, now: &DateTime<Tz>) -> Option<DateTime<Utc>> {
+    let now = now.with_timezone(&Utc);
+    let unit = unit.strip_suffix('s').unwrap_or(unit);
+    let seconds = match unit {
+        "second" | "sec" => 1,
+        "minute" | "min" => 60,
+        "hour" => 60 * 60,
+        "day" => 24 * 60 * 60,
+        "week" => 7 * 24 * 60 * 60,
+        "month" => return now.checked_sub_months(Months::new(count)),
+        "year" => return now.checked_sub_months(Months::new(count.checked_mul(12)?)),
+        _ => return None,
+    };
+    now.checked_sub_signed(TimeDelta::try_seconds(i64::from(count) * seconds)?)
+}
+
+/// The first and last day of the `unit` containing `today`, or of the one
+/// `back` units before it.
+fn period(unit: &str, back: u32, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let days = |n: u32| Days::new(u64::from(n));
+    match unit {
+        "day" => {
+            let day = today.checked_sub_days(days(back))?;
+            Some((day, day))
+        }
+        "week" => {
+            let monday = today
+                .checked_sub_days(days(today.weekday().num_days_from_monday()))?
+                .checked_sub_days(days(7 * back))?;
+            Some((monday, monday.checked_add_days(days(6))?))
+        }
+        "month" | "quarter" | "year" => {
+            let length = match unit {
+                "month" => 1,
+                "quarter" => 3,
+                _ => 12,
+            };
+            let start_month = (today.month0() / length) * length + 1;
+            let first = NaiveDate::from_ymd_opt(today.year(), start_month, 1)?
+                .checked_sub_months(Months::new(length * back))?;
+            let last = first
+                .checked_add_months(Months::new(length))?
+                .checked_sub_days(days(1))?;
+            Some((first, last))
+        }
+        _ => None,
+    }
+}
 
+/// The inclusive bound at `edge` of the days from `first` to `last`, in
+/// `now`'s time zone.
+fn day_edge<Tz: TimeZone>(
+    first: NaiveDate,
+    last: NaiveDate,
+    edge: DayEdge,
+    now: &DateTime<Tz>,
+) -> DateTime<Utc> {
     let time = match edge {
-        DayEdge::Start => date.and_hms_opt(0, 0, 0),
-        DayEdge::End => date.and_hms_opt(23, 59, 59),
+        DayEdge::Start => first.and_hms_opt(0, 0, 0),
+        DayEdge::End => last.and_hms_opt(23, 59, 59),
     };
 
     // Both literals are valid for every representable date, so this cannot fail;
     // the fallback keeps the function total instead of panicking.
-    Ok(time
-        .unwrap_or_else(|| date.and_hms_opt(0, 0, 0).unwrap_or_default())
-        .and_utc())
+    let time = time.unwrap_or_else(|| first.and_hms_opt(0, 0, 0).unwrap_or_default());
+    in_zone(time, edge, now)
+}
+
+/// The instant `local` names in `now`'s time zone. A time that a clock change
+/// repeats is read as early as it can be for a start and as late for an end;
+/// one it skips takes the offset in force at `now`.
+fn in_zone<Tz: TimeZone>(local: NaiveDateTime, edge: DayEdge, now: &DateTime<Tz>) -> DateTime<Utc> {
+    let readings = now.timezone().from_local_datetime(&local);
+    let instant = match edge {
+        DayEdge::Start => readings.earliest(),
+        DayEdge::End => readings.latest(),
+    };
+    match instant {
+        Some(instant) => instant.with_timezone(&Utc),
+        None => {
+            let offset = TimeDelta::seconds(i64::from(now.offset().fix().local_minus_utc()));
+            (local - offset).and_utc()
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::git::{Identity, PullRequest, Trailer};
+    use chrono::FixedOffset;
 
+    /// Fixtures spell changed paths as a leading `Files:` line, the way
+    /// summaries used to, and lift it into structured records.
     fn commit(author: &str, date: &str, diff_summary: &str) -> CommitInfo {
-        CommitInfo {
+        let mut commit = CommitInfo {
             hash: "abc1234".to_string(),
             author: author.to_string(),
             date: chrono::DateTime::parse_from_rfc3339(&format!("{date}T12:00:00Z"))
@@ -121,6 +705,23 @@ mod tests {
                 .with_timezone(&chrono::Utc),
             message: "test commit".to_string(),
             diff_summary: diff_summary.to_string(),
+            ..Default::default()
+        };
+        commit.lift_files_line();
+        commit
+    }
+
+    fn changed(author: &str, changes: &[(&str, ChangeStatus, usize, usize)]) -> CommitInfo {
+        CommitInfo {
+            files: changes
+                .iter()
+                .map(|&(path, status, insertions, deletions)| FileChange {
+                    insertions,
+                    deletions,
+                    ..FileChange::new(path, status)
+                })
+                .collect(),
+            ..commit(author, "2024-06-01", "")
         }
     }
 
@@ -135,12 +736,7 @@ mod tests {
     }
 
     fn no_filters() -> SearchFilters {
-        SearchFilters {
-            author: None,
-            after: None,
-            before: None,
-            file: None,
-        }
+        SearchFilters::default()
     }
 
     #[test]
@@ -161,13 +757,97 @@ mod tests {
             commit("Bob Martinez", "2024-07-01", ""),
         ];
         let engine = FilterEngine::new(SearchFilters {
-            author: Some("alice".to_string()),
+            authors: vec!["alice".to_string()],
             ..no_filters()
         })
         .unwrap();
         assert_eq!(surviving(&engine, &commits), vec!["Alice Chen"]);
     }
 
+    #[test]
+    fn test_author_filter_matches_co_authors() {
+        let paired = CommitInfo {
+            co_authors: vec![Identity {
+                name: "Bob Smith".to_string(),
+                email: "bob@example.com".to_string(),
+            }],
+            ..commit("Alice", "2024-06-01", "")
+        };
+        let commits = [paired, commit("Carol", "2024-06-01", "")];
+        let engine = FilterEngine::new(SearchFilters {
+            authors: vec!["bob".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+
+        assert_eq!(surviving(&engine, &commits), vec!["Alice"]);
+    }
+
+    #[test]
+    fn test_author_filter_matches_the_mailmap_name() {
+        let aliased = CommitInfo {
+            canonical_author: Identity {
+                name: "Yan Xue".to_string(),
+                email: "yan@example.com".to_string(),
+            },
+            ..commit("yan", "2024-06-01", "")
+        };
+        let commits = [aliased, commit("Carol", "2024-06-01", "")];
+        let engine = FilterEngine::new(SearchFilters {
+            authors: vec!["yan xue".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+
+        assert_eq!(surviving(&engine, &commits), vec!["yan"]);
+    }
+
+    #[test]
+    fn test_email_filter_tells_same_named_authors_apart() {
+        let work = CommitInfo {
+            author_email: "alex@corp.example".to_string(),
+            ..commit("Alex Kim", "2024-06-01", "")
+        };
+        let other = CommitInfo {
+            author_email: "alex.kim@gmail.com".to_string(),
+            ..commit("Alex Kim", "2024-06-02", "")
+        };
+        let engine = FilterEngine::new(SearchFilters {
+            emails: vec!["@CORP.example".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+
+        let kept: Vec<_> = [work, other]
+            .into_iter()
+            .filter(|c| engine.matches(c))
+            .collect();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].author_email, "alex@corp.example");
+    }
+
+    #[test]
+    fn test_committer_filter_matches_name_or_email() {
+        let landed = |committer: &str, email: &str, author: &str| CommitInfo {
+            committer: committer.to_string(),
+            committer_email: email.to_string(),
+            ..commit(author, "2024-06-01", "")
+        };
+        let commits = [
+            landed("GitHub", "noreply@github.com", "Web"),
+            landed("Maintainer", "lead@example.com", "Patch"),
+        ];
+
+        for needle in ["github", "noreply@"] {
+            let engine = FilterEngine::new(SearchFilters {
+                committers: vec![needle.to_string()],
+                ..no_filters()
+            })
+            .unwrap();
+            assert_eq!(surviving(&engine, &commits), vec!["Web"], "{needle}");
+        }
+    }
+
     #[test]
     fn test_author_filter_partial_match() {
         let commits = [
@@ -175,7 +855,7 @@ mod tests {
             commit("Bob Martinez", "2024-07-01", ""),
         ];
         let engine = FilterEngine::new(SearchFilters {
-            author: Some("chen".to_string()),
+            authors: vec!["chen".to_string()],
             ..no_filters()
         })
         .unwrap();
@@ -239,7 +919,7 @@ mod tests {
             commit("Bob", "2024-07-01", "Files: src/main.rs\n+fn main() {}"),
         ];
         let engine = FilterEngine::new(SearchFilters {
-            file: Some("src/auth.rs".to_string()),
+            files: vec!["src/auth.rs".to_string()],
             ..no_filters()
         })
         .unwrap();
@@ -255,7 +935,7 @@ mod tests {
         )];
         for needle in ["Cargo.toml", "src/auth.rs", "src/main.rs", "src/"] {
             let engine = FilterEngine::new(SearchFilters {
-                file: Some(needle.to_string()),
+                files: vec![needle.to_string()],
                 ..no_filters()
             })
             .unwrap();
@@ -268,76 +948,645 @@ mod tests {
     }
 
     #[test]
-    fn test_file_filter_ignores_paths_mentioned_only_in_diff_content() {
-        // The commit edits main.rs and merely *mentions* auth.rs in a removed
-        // line. Substring-searching the whole diff would wrongly match.
-        let commits = [commit(
-            "Alice",
-            "2024-06-01",
-            "Files: src/main.rs\n-use crate::src/auth.rs::Token;",
-        )];
+    fn test_excluded_paths_drop_commits_with_nothing_else() {
+        let commits = [
+            commit("Tests only", "2024-06-01", "Files: tests/auth.rs\n"),
+            commit("Both", "2024-06-01", "Files: src/auth.rs, tests/auth.rs\n"),
+            commit("Elsewhere", "2024-06-01", "Files: src/db.rs\n"),
+        ];
         let engine = FilterEngine::new(SearchFilters {
-            file: Some("src/auth.rs".to_string()),
+            exclude_files: vec!["tests".to_string()],
             ..no_filters()
         })
         .unwrap();
-        assert!(
-            surviving(&engine, &commits).is_empty(),
-            "a path only referenced in diff text is not a changed file"
-        );
+        assert_eq!(surviving(&engine, &commits), vec!["Both", "Elsewhere"]);
+
+        let engine = FilterEngine::new(SearchFilters {
+            files: vec!["auth".to_string()],
+            exclude_files: vec!["tests".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(surviving(&engine, &commits), vec!["Both"]);
     }
 
     #[test]
-    fn test_file_filter_falls_back_on_a_legacy_index() {
-        // No `Files:` line — an index built before paths were recorded.
-        let commits = [commit("Alice", "2024-06-01", "+modified src/auth.rs")];
+    fn test_repeated_authors_are_alternatives() {
+        let commits = [
+            commit("Alice", "2024-06-01", ""),
+            commit("Bob", "2024-06-01", ""),
+            commit("Carol", "2024-06-01", ""),
+        ];
         let engine = FilterEngine::new(SearchFilters {
-            file: Some("src/auth.rs".to_string()),
+            authors: vec!["alice".to_string(), "BOB".to_string()],
             ..no_filters()
         })
         .unwrap();
-        assert_eq!(
-            surviving(&engine, &commits).len(),
-            1,
-            "legacy indexes should keep the old substring behaviour"
-        );
+        assert_eq!(surviving(&engine, &commits), vec!["Alice", "Bob"]);
     }
 
     #[test]
-    fn test_combined_filters() {
+    fn test_excluded_authors_drop_their_commits_even_as_co_author() {
+        let paired = CommitInfo {
+            co_authors: vec![Identity {
+                name: "release-bot".to_string(),
+                email: "bot@example.com".to_string(),
+            }],
+            ..commit("Alice", "2024-06-01", "")
+        };
         let commits = [
-            commit("Alice", "2024-06-01", "Files: src/auth.rs"),
-            commit("Alice", "2024-01-01", "Files: src/auth.rs"),
-            commit("Bob", "2024-06-01", "Files: src/auth.rs"),
+            paired,
+            commit("release-bot", "2024-06-01", ""),
+            commit("Bob", "2024-06-01", ""),
         ];
         let engine = FilterEngine::new(SearchFilters {
-            author: Some("alice".to_string()),
-            after: Some("2024-03-01".to_string()),
-            file: Some("src/auth.rs".to_string()),
-            before: None,
+            exclude_authors: vec!["Release-Bot".to_string()],
+            ..no_filters()
         })
         .unwrap();
-
-        let kept: Vec<&CommitInfo> = commits.iter().filter(|c| engine.matches(c)).collect();
-        assert_eq!(kept.len(), 1);
-        assert_eq!(kept[0].author, "Alice");
-        assert_eq!(kept[0].date.format("%Y-%m-%d").to_string(), "2024-06-01");
+        assert_eq!(surviving(&engine, &commits), vec!["Bob"]);
     }
 
     #[test]
-    fn test_invalid_date_format_fails_at_compile_time() {
-        let result = FilterEngine::new(SearchFilters {
-            after: Some("not-a-date".to_string()),
+    fn test_excluded_committers_and_emails() {
+        let landed = |author: &str, email: &str, committer: &str| CommitInfo {
+            author_email: email.to_string(),
+            committer: committer.to_string(),
+            ..commit(author, "2024-06-01", "")
+        };
+        let commits = [
+            landed("Web", "web@example.com", "GitHub"),
+            landed("Bot", "ci@bots.example", "Maintainer"),
+            landed("Patch", "patch@example.com", "Maintainer"),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            exclude_committers: vec!["github".to_string()],
+            exclude_emails: vec!["@bots.example".to_string()],
             ..no_filters()
-        });
-        assert!(
-            result.is_err(),
-            "a bad date should fail before any embedding work"
-        );
+        })
+        .unwrap();
+        assert_eq!(surviving(&engine, &commits), vec!["Patch"]);
     }
 
     #[test]
-    fn test_invalid_before_date_is_also_rejected() {
+    fn test_file_globs_match_whole_paths() {
+        let commits = [
+            commit("Nested", "2024-06-01", "Files: src/index/builder.rs\n"),
+            commit("Top", "2024-06-01", "Files: src/main.rs\n"),
+            commit("Test", "2024-06-01", "Files: tests/cli.rs\n"),
+            commit("Toml", "2024-06-01", "Files: src/config.toml\n"),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            files: vec!["src/**/*.rs".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(surviving(&engine, &commits), vec!["Nested", "Top"]);
+
+        // Without a slash a glob matches at any depth, as in .gitignore.
+        let engine = FilterEngine::new(SearchFilters {
+            files: vec!["*.rs".to_string()],
+            exclude_files: vec!["tests/**".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(surviving(&engine, &commits), vec!["Nested", "Top"]);
+    }
+
+    #[test]
+    fn test_file_globs_follow_renames() {
+        let moved = CommitInfo {
+            files: vec![FileChange {
+                old_path: Some("lib/auth.rs".to_string()),
+                ..FileChange::new("src/auth.rs", ChangeStatus::Renamed)
+            }],
+            ..commit("Move", "2024-06-01", "")
+        };
+        let engine = FilterEngine::new(SearchFilters {
+            files: vec!["lib/*.rs".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        assert!(engine.matches(&moved));
+    }
+
+    #[test]
+    fn test_message_patterns_are_case_insensitive_regexes() {
+        let said = |author: &str, message: &str| CommitInfo {
+            message: message.to_string(),
+            ..commit(author, "2024-06-01", "")
+        };
+        let commits = [
+            said("Alice", "Fix(auth): refresh tokens early"),
+            said("Bob", "fix: typo in README"),
+            said("Carol", "Revert \"fix(auth): refresh tokens early\""),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            messages: vec![r"^fix(\(auth\))?:".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(surviving(&engine, &commits), vec!["Alice", "Bob"]);
+
+        let engine = FilterEngine::new(SearchFilters {
+            exclude_messages: vec!["^revert".to_string(), "readme".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(surviving(&engine, &commits), vec!["Alice"]);
+    }
+
+    #[test]
+    fn test_excluded_types_keep_commits_without_a_type() {
+        let commits = [
+            conventional("Chore", "chore: bump deps"),
+            conventional("Fix", "fix: crash"),
+            conventional("Plain", "Make it faster"),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            exclude_types: vec!["chore".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(surviving(&engine, &commits), vec!["Fix", "Plain"]);
+    }
+
+    #[test]
+    fn test_invalid_globs_and_patterns_are_reported() {
+        let err = FilterEngine::new(SearchFilters {
+            files: vec!["src/[a-".to_string()],
+            ..no_filters()
+        })
+        .err()
+        .unwrap();
+        assert_eq!(err.code(), "E4009");
+
+        let err = FilterEngine::new(SearchFilters {
+            messages: vec!["(unclosed".to_string()],
+            ..no_filters()
+        })
+        .err()
+        .unwrap();
+        assert_eq!(err.code(), "E4010");
+    }
+
+    #[test]
+    fn test_file_filter_ignores_paths_mentioned_only_in_diff_content() {
+        // The commit edits main.rs and merely *mentions* auth.rs in a removed
+        // line. Substring-searching the whole diff would wrongly match.
+        let commits = [commit(
+            "Alice",
+            "2024-06-01",
+            "Files: src/main.rs\n-use crate::src/auth.rs::Token;",
+        )];
+        let engine = FilterEngine::new(SearchFilters {
+            files: vec!["src/auth.rs".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        assert!(
+            surviving(&engine, &commits).is_empty(),
+            "a path only referenced in diff text is not a changed file"
+        );
+    }
+
+    #[test]
+    fn test_file_filter_falls_back_on_a_legacy_index() {
+        // No `Files:` line — an index built before paths were recorded.
+        let commits = [commit("Alice", "2024-06-01", "+modified src/auth.rs")];
+        let engine = FilterEngine::new(SearchFilters {
+            files: vec!["src/auth.rs".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(
+            surviving(&engine, &commits).len(),
+            1,
+            "legacy indexes should keep the old substring behaviour"
+        );
+    }
+
+    fn renamed(from: &str, to: &str) -> CommitInfo {
+        CommitInfo {
+            files: vec![FileChange {
+                old_path: Some(from.to_string()),
+                ..FileChange::new(to, ChangeStatus::Renamed)
+            }],
+            ..commit("Mover", "2024-05-01", "")
+        }
+    }
+
+    #[test]
+    fn test_file_filter_follows_a_file_across_its_rename() {
+        let commits = [
+            commit("Early", "2024-01-01", "Files: src/login.rs\n+fn login() {}"),
+            renamed("src/login.rs", "src/auth.rs"),
+            commit("Late", "2024-07-01", "Files: src/auth.rs\n+fn token() {}"),
+            commit("Other", "2024-07-02", "Files: src/main.rs\n+fn main() {}"),
+        ];
+        let mut engine = FilterEngine::new(SearchFilters {
+            files: vec!["src/auth.rs".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        engine.follow_renames(&commits);
+
+        assert_eq!(surviving(&engine, &commits), vec!["Early", "Mover", "Late"]);
+    }
+
+    #[test]
+    fn test_file_filter_follows_a_chain_of_renames() {
+        let commits = [
+            commit("First", "2024-01-01", "Files: a.rs\n+x"),
+            renamed("a.rs", "b.rs"),
+            renamed("b.rs", "c.rs"),
+        ];
+        let mut engine = FilterEngine::new(SearchFilters {
+            files: vec!["c.rs".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        engine.follow_renames(&commits);
+
+        assert!(
+            engine.matches(&commits[0]),
+            "a file moved twice must still be found under its first name"
+        );
+    }
+
+    #[test]
+    fn test_follow_renames_seeds_only_on_whole_path_components() {
+        let commits = [
+            commit("Data", "2024-01-01", "Files: lib/data.rs\n+x"),
+            commit("Tool", "2024-01-01", "Files: tools/gen.rs\n+x"),
+            renamed("lib/data.rs", "src/data.rs"),
+            renamed("tools/gen.rs", "src/gen.rs"),
+            commit("Auth", "2024-01-01", "Files: lib/auth.rs\n+x"),
+            renamed("lib/auth.rs", "src/auth/mod.rs"),
+        ];
+
+        // `a.rs` is inside `data.rs` but names no file that was renamed.
+        let mut engine = FilterEngine::new(SearchFilters {
+            files: vec!["a.rs".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        engine.follow_renames(&commits);
+        assert!(engine.file_aliases.is_empty());
+
+        // A directory follows the files renamed into it, and only those.
+        let mut engine = FilterEngine::new(SearchFilters {
+            files: vec!["src/auth/".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        engine.follow_renames(&commits);
+        assert_eq!(engine.file_aliases, ["lib/auth.rs"]);
+        assert!(engine.matches(&commits[4]));
+        assert!(!engine.matches(&commits[0]));
+    }
+
+    #[test]
+    fn test_follow_renames_without_a_file_filter_changes_nothing() {
+        let commits = [renamed("a.rs", "b.rs")];
+        let mut engine = FilterEngine::new(no_filters()).unwrap();
+        engine.follow_renames(&commits);
+        assert!(!engine.is_active());
+    }
+
+    #[test]
+    fn test_added_keeps_only_commits_that_created_a_file() {
+        let commits = [
+            changed("Creator", &[("src/new.rs", ChangeStatus::Added, 10, 0)]),
+            changed("Editor", &[("src/old.rs", ChangeStatus::Modified, 3, 1)]),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            added: true,
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(surviving(&engine, &commits), vec!["Creator"]);
+    }
+
+    #[test]
+    fn test_added_with_file_requires_that_file_to_be_the_added_one() {
+        let commits = [
+            changed(
+                "Both",
+                &[
+                    ("src/auth.rs", ChangeStatus::Modified, 1, 1),
+                    ("src/new.rs", ChangeStatus::Added, 5, 0),
+                ],
+            ),
+            changed("Introducer", &[("src/auth.rs", ChangeStatus::Added, 40, 0)]),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            files: vec!["src/auth.rs".to_string()],
+            added: true,
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(
+            surviving(&engine, &commits),
+            vec!["Introducer"],
+            "--file --added asks when that file was created"
+        );
+    }
+
+    #[test]
+    fn test_deleted_keeps_only_commits_that_removed_a_file() {
+        let commits = [
+            changed("Remover", &[("src/dead.rs", ChangeStatus::Deleted, 0, 12)]),
+            changed("Creator", &[("src/new.rs", ChangeStatus::Added, 10, 0)]),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            deleted: true,
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(surviving(&engine, &commits), vec!["Remover"]);
+    }
+
+    #[test]
+    fn test_min_lines_sums_insertions_and_deletions() {
+        let commits = [
+            changed(
+                "Big",
+                &[
+                    ("a.rs", ChangeStatus::Modified, 30, 10),
+                    ("b.rs", ChangeStatus::Modified, 5, 5),
+                ],
+            ),
+            changed("Small", &[("a.rs", ChangeStatus::Modified, 2, 1)]),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            min_lines: Some(50),
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(surviving(&engine, &commits), vec!["Big"]);
+    }
+
+    #[test]
+    fn test_min_lines_with_file_counts_only_that_file() {
+        let commits = [changed(
+            "Mixed",
+            &[
+                ("src/auth.rs", ChangeStatus::Modified, 2, 1),
+                ("Cargo.lock", ChangeStatus::Modified, 400, 300),
+            ],
+        )];
+        let engine = FilterEngine::new(SearchFilters {
+            files: vec!["src/auth.rs".to_string()],
+            min_lines: Some(50),
+            ..no_filters()
+        })
+        .unwrap();
+        assert!(
+            surviving(&engine, &commits).is_empty(),
+            "a lockfile churn must not make a tiny auth.rs change look large"
+        );
+    }
+
+    #[test]
+    fn test_change_filters_reject_commits_with_unknown_statuses() {
+        // Migrated from an index that never recorded what happened to a path.
+        let commits = [commit("Legacy", "2024-06-01", "Files: src/auth.rs\n+x")];
+        for filters in [
+            SearchFilters {
+                added: true,
+                ..no_filters()
+            },
+            SearchFilters {
+                min_lines: Some(1),
+                ..no_filters()
+            },
+        ] {
+            let engine = FilterEngine::new(filters).unwrap();
+            assert!(surviving(&engine, &commits).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_symbol_filter_matches_exact_names() {
+        let with_symbols = |author: &str, symbols: &[&str]| CommitInfo {
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+            ..commit(author, "2024-06-01", "+x")
+        };
+        let commits = [
+            with_symbols("Exact", &["SearchEngine", "encode_text"]),
+            with_symbols("Longer", &["encode_text_batch"]),
+            with_symbols("None", &[]),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            symbol: Some("encode_text".to_string()),
+            ..no_filters()
+        })
+        .unwrap();
+
+        assert_eq!(surviving(&engine, &commits), vec!["Exact"]);
+    }
+
+    #[test]
+    fn test_combined_filters() {
+        let commits = [
+            commit("Alice", "2024-06-01", "Files: src/auth.rs"),
+            commit("Alice", "2024-01-01", "Files: src/auth.rs"),
+            commit("Bob", "2024-06-01", "Files: src/auth.rs"),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            authors: vec!["alice".to_string()],
+            after: Some("2024-03-01".to_string()),
+            files: vec!["src/auth.rs".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+
+        let kept: Vec<&CommitInfo> = commits.iter().filter(|c| engine.matches(c)).collect();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].author, "Alice");
+        assert_eq!(kept[0].date.format("%Y-%m-%d").to_string(), "2024-06-01");
+    }
+
+    /// A clock reading with its offset, both pinned.
+    fn at(instant: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(instant).unwrap()
+    }
+
+    /// A Wednesday, so weeks, months and quarters all have a past and a rest,
+    /// on a clock that runs on UTC.
+    fn clock() -> DateTime<FixedOffset> {
+        at("2024-05-15T10:30:00+00:00")
+    }
+
+    fn bounds_at(raw: &str, now: DateTime<FixedOffset>) -> (String, String) {
+        let edge = |edge| parse_date(raw, edge, &now).unwrap().to_rfc3339();
+        (edge(DayEdge::Start), edge(DayEdge::End))
+    }
+
+    fn bounds(raw: &str) -> (String, String) {
+        bounds_at(raw, clock())
+    }
+
+    #[test]
+    fn test_named_days_cover_the_whole_day() {
+        let day = |date: &str| {
+            (
+                format!("{date}T00:00:00+00:00"),
+                format!("{date}T23:59:59+00:00"),
+            )
+        };
+        assert_eq!(bounds("today"), day("2024-05-15"));
+        assert_eq!(bounds("Yesterday"), day("2024-05-14"));
+        assert_eq!(bounds("2024-01-15"), day("2024-01-15"));
+    }
+
+    #[test]
+    fn test_relative_dates_count_back_from_now() {
+        let instant = |raw: &str| bounds(raw).0;
+        assert_eq!(instant("now"), "2024-05-15T10:30:00+00:00");
+        assert_eq!(instant("2 weeks ago"), "2024-05-01T10:30:00+00:00");
+        assert_eq!(instant("2.weeks.ago"), "2024-05-01T10:30:00+00:00");
+        assert_eq!(instant("an hour ago"), "2024-05-15T09:30:00+00:00");
+        assert_eq!(instant("90 minutes ago"), "2024-05-15T09:00:00+00:00");
+        assert_eq!(instant("1 day ago"), "2024-05-14T10:30:00+00:00");
+        assert_eq!(instant("3 months ago"), "2024-02-15T10:30:00+00:00");
+        assert_eq!(instant("1 year ago"), "2023-05-15T10:30:00+00:00");
+        assert_eq!(bounds("2 weeks ago").0, bounds("2 weeks ago").1);
+    }
+
+    #[test]
+    fn test_months_ago_clamp_to_the_end_of_a_shorter_month() {
+        let now = at("2024-03-31T12:00:00+00:00");
+        let month_ago = parse_date("1 month ago", DayEdge::Start, &now).unwrap();
+        assert_eq!(month_ago.to_rfc3339(), "2024-02-29T12:00:00+00:00");
+    }
+
+    #[test]
+    fn test_named_periods_span_the_calendar() {
+        let span = |first: &str, last: &str| {
+            (
+                format!("{first}T00:00:00+00:00"),
+                format!("{last}T23:59:59+00:00"),
+            )
+        };
+        assert_eq!(bounds("this week"), span("2024-05-13", "2024-05-19"));
+        assert_eq!(bounds("last week"), span("2024-05-06", "2024-05-12"));
+        assert_eq!(bounds("last month"), span("2024-04-01", "2024-04-30"));
+        assert_eq!(bounds("this quarter"), span("2024-04-01", "2024-06-30"));
+        assert_eq!(bounds("last quarter"), span("2024-01-01", "2024-03-31"));
+        assert_eq!(bounds("last year"), span("2023-01-01", "2023-12-31"));
+    }
+
+    #[test]
+    fn test_last_quarter_reaches_into_the_previous_year() {
+        let now = at("2024-01-10T08:00:00+00:00");
+        let first = parse_date("last quarter", DayEdge::Start, &now).unwrap();
+        let last = parse_date("last quarter", DayEdge::End, &now).unwrap();
+        assert_eq!(first.to_rfc3339(), "2023-10-01T00:00:00+00:00");
+        assert_eq!(last.to_rfc3339(), "2023-12-31T23:59:59+00:00");
+    }
+
+    #[test]
+    fn test_timestamps_are_exact_instants() {
+        assert_eq!(
+            bounds("2024-01-15T09:30:00+02:00").1,
+            "2024-01-15T07:30:00+00:00"
+        );
+        assert_eq!(bounds("2024-01-15 09:30").1, "2024-01-15T09:30:00+00:00");
+        assert_eq!(bounds("2024-01-15 09:30:15").0, "2024-01-15T09:30:15+00:00");
+    }
+
+    #[test]
+    fn test_days_and_naive_times_are_read_in_the_local_zone() {
+        // Late on the 15th in UTC-7, already the 16th in UTC.
+        let now = at("2024-05-15T23:30:00-07:00");
+        assert_eq!(
+            bounds_at("today", now),
+            (
+                "2024-05-15T07:00:00+00:00".to_string(),
+                "2024-05-16T06:59:59+00:00".to_string()
+            )
+        );
+        assert_eq!(bounds_at("2024-01-15", now).0, "2024-01-15T07:00:00+00:00");
+        assert_eq!(bounds_at("this week", now).0, "2024-05-13T07:00:00+00:00");
+        assert_eq!(
+            bounds_at("2024-01-15 09:30", now).0,
+            "2024-01-15T16:30:00+00:00"
+        );
+        assert_eq!(
+            bounds_at("2024-01-15T09:30:00Z", now).0,
+            "2024-01-15T09:30:00+00:00",
+            "an explicit offset wins"
+        );
+        assert_eq!(bounds_at("now", now).0, "2024-05-16T06:30:00+00:00");
+    }
+
+    #[test]
+    fn test_unreadable_dates_are_rejected() {
+        for raw in [
+            "soon",
+            "next week",
+            "2 fortnights ago",
+            "lots of days ago",
+            "",
+        ] {
+            let err = parse_date(raw, DayEdge::Start, &clock()).unwrap_err();
+            assert_eq!(err.code(), "E4001", "{raw}");
+        }
+    }
+
+    #[test]
+    fn test_relative_bounds_filter_against_the_clock() {
+        let commits = [
+            commit("Old", "2024-03-20", ""),
+            commit("Last month", "2024-04-20", ""),
+            commit("Recent", "2024-05-10", ""),
+        ];
+        let engine = FilterEngine::at(
+            SearchFilters {
+                after: Some("last month".to_string()),
+                before: Some("1 week ago".to_string()),
+                ..no_filters()
+            },
+            clock(),
+        )
+        .unwrap();
+        assert_eq!(surviving(&engine, &commits), vec!["Last month"]);
+    }
+
+    #[test]
+    fn test_revisions_keep_only_the_commits_they_reach() {
+        let on = |hash: &str, author: &str| CommitInfo {
+            hash: hash.to_string(),
+            ..commit(author, "2024-06-01", "")
+        };
+        let commits = [on("aaa", "Release fix"), on("bbb", "Main only")];
+        let mut engine = FilterEngine::new(SearchFilters {
+            revisions: vec!["release/2.3".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        assert!(engine.has_revisions() && engine.is_active());
+
+        engine.reachable = Some(HashSet::from(["aaa".to_string()]));
+        assert_eq!(surviving(&engine, &commits), vec!["Release fix"]);
+    }
+
+    #[test]
+    fn test_invalid_date_format_fails_at_compile_time() {
+        let result = FilterEngine::new(SearchFilters {
+            after: Some("not-a-date".to_string()),
+            ..no_filters()
+        });
+        assert!(
+            result.is_err(),
+            "a bad date should fail before any embedding work"
+        );
+    }
+
+    #[test]
+    fn test_invalid_before_date_is_also_rejected() {
         let result = FilterEngine::new(SearchFilters {
             before: Some("2024-13-45".to_string()),
             ..no_filters()
@@ -349,7 +1598,7 @@ mod tests {
     fn test_filter_returns_empty_when_nothing_matches() {
         let commits = [commit("Alice", "2024-06-01", "")];
         let engine = FilterEngine::new(SearchFilters {
-            author: Some("nonexistent".to_string()),
+            authors: vec!["nonexistent".to_string()],
             ..no_filters()
         })
         .unwrap();
@@ -359,7 +1608,7 @@ mod tests {
     #[test]
     fn test_matches_is_case_insensitive_on_author() {
         let engine = FilterEngine::new(SearchFilters {
-            author: Some("alice".to_string()),
+            authors: vec!["alice".to_string()],
             ..no_filters()
         })
         .unwrap();
@@ -374,7 +1623,7 @@ mod tests {
 
         for filters in [
             SearchFilters {
-                author: Some("a".into()),
+                authors: vec!["a".into()],
                 ..no_filters()
             },
             SearchFilters {
@@ -386,7 +1635,91 @@ mod tests {
                 ..no_filters()
             },
             SearchFilters {
-                file: Some("f".into()),
+                files: vec!["f".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                added: true,
+                ..no_filters()
+            },
+            SearchFilters {
+                deleted: true,
+                ..no_filters()
+            },
+            SearchFilters {
+                min_lines: Some(1),
+                ..no_filters()
+            },
+            SearchFilters {
+                symbol: Some("f".into()),
+                ..no_filters()
+            },
+            SearchFilters {
+                emails: vec!["e".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                committers: vec!["c".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                revisions: vec!["main".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                exclude_authors: vec!["a".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                exclude_emails: vec!["e".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                exclude_committers: vec!["c".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                exclude_files: vec!["f".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                messages: vec!["m".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                exclude_messages: vec!["m".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                exclude_types: vec!["chore".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                trailer: vec!["fixes".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                types: vec!["fix".into()],
+                ..no_filters()
+            },
+            SearchFilters {
+                scope: Some("auth".into()),
+                ..no_filters()
+            },
+            SearchFilters {
+                breaking: true,
+                ..no_filters()
+            },
+            SearchFilters {
+                issue: Some("#1".into()),
+                ..no_filters()
+            },
+            SearchFilters {
+                label: Some("bug".into()),
+                ..no_filters()
+            },
+            SearchFilters {
+                pr: Some("1".into()),
                 ..no_filters()
             },
         ] {
@@ -395,14 +1728,212 @@ mod tests {
     }
 
     #[test]
-    fn test_date_bounds_are_inclusive() {
+    fn test_trailer_filter_matches_key_and_value() {
+        let reviewed = CommitInfo {
+            trailers: vec![
+                Trailer {
+                    key: "Reviewed-by".to_string(),
+                    value: "Bob Smith <bob@example.com>".to_string(),
+                },
+                Trailer {
+                    key: "Fixes".to_string(),
+                    value: "#123".to_string(),
+                },
+            ],
+            ..commit("Alice", "2024-06-01", "")
+        };
+        let commits = [reviewed, commit("Carol", "2024-06-01", "")];
+
+        for wanted in [
+            vec!["reviewed-by"],
+            vec!["Reviewed-By=BOB"],
+            vec!["reviewed-by=bob", "fixes=#123"],
+        ] {
+            let engine = FilterEngine::new(SearchFilters {
+                trailer: wanted.iter().map(|t| t.to_string()).collect(),
+                ..no_filters()
+            })
+            .unwrap();
+            assert_eq!(surviving(&engine, &commits), vec!["Alice"], "{wanted:?}");
+        }
+
+        let engine = FilterEngine::new(SearchFilters {
+            trailer: vec!["reviewed-by=carol".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+        assert!(surviving(&engine, &commits).is_empty());
+    }
+
+    #[test]
+    fn test_trailer_filter_needs_a_key() {
+        for raw in ["", "=bob", "reviewed by=bob"] {
+            let err = FilterEngine::new(SearchFilters {
+                trailer: vec![raw.to_string()],
+                ..no_filters()
+            })
+            .err()
+            .unwrap();
+            assert_eq!(err.code(), "E4004", "{raw:?}");
+        }
+    }
+
+    fn conventional(author: &str, subject: &str) -> CommitInfo {
+        CommitInfo {
+            conventional: crate::git::conventional::parse(subject),
+            message: subject.to_string(),
+            ..commit(author, "2024-06-01", "")
+        }
+    }
+
+    #[test]
+    fn test_type_filter_accepts_any_listed_type() {
+        let commits = [
+            conventional("Alice", "fix(auth): expired tokens"),
+            conventional("Bob", "perf: faster scan"),
+            conventional("Carol", "feat: new command"),
+            conventional("Dave", "update readme"),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            types: vec!["fix".to_string(), "PERF".to_string()],
+            ..no_filters()
+        })
+        .unwrap();
+
+        assert_eq!(surviving(&engine, &commits), vec!["Alice", "Bob"]);
+    }
+
+    #[test]
+    fn test_scope_filter_matches_whole_scopes() {
+        let commits = [
+            conventional("Alice", "fix(auth): expired tokens"),
+            conventional("Bob", "fix(oauth): callback url"),
+            conventional("Carol", "feat(api,Auth): login endpoint"),
+            conventional("Dave", "fix: no scope"),
+        ];
         let engine = FilterEngine::new(SearchFilters {
-            after: Some("2024-06-15".to_string()),
-            before: Some("2024-06-15".to_string()),
+            scope: Some("auth".to_string()),
             ..no_filters()
         })
         .unwrap();
 
+        assert_eq!(surviving(&engine, &commits), vec!["Alice", "Carol"]);
+    }
+
+    #[test]
+    fn test_breaking_filter() {
+        let commits = [
+            conventional("Alice", "feat(api)!: drop v1"),
+            conventional("Bob", "feat(api): add v2"),
+            conventional("Carol", "Remove v1 (breaking)"),
+        ];
+        let engine = FilterEngine::new(SearchFilters {
+            breaking: true,
+            ..no_filters()
+        })
+        .unwrap();
+
+        assert_eq!(surviving(&engine, &commits), vec!["Alice"]);
+    }
+
+    #[test]
+    fn test_issue_filter_accepts_any_spelling() {
+        let mention = |author: &str, issues: &[&str]| CommitInfo {
+            issues: issues.iter().map(|i| i.to_string()).collect(),
+            ..commit(author, "2024-06-01", "")
+        };
+        let commits = [
+            mention("Alice", &["#12"]),
+            mention("Bob", &["acme/api#12"]),
+            mention("Carol", &["#123", "JIRA-456"]),
+        ];
+
+        for (wanted, expected) in [
+            ("GH-12", vec!["Alice", "Bob"]),
+            ("12", vec!["Alice", "Bob"]),
+            ("acme/api#12", vec!["Bob"]),
+            ("jira-456", vec!["Carol"]),
+        ] {
+            let engine = FilterEngine::new(SearchFilters {
+                issue: Some(wanted.to_string()),
+                ..no_filters()
+            })
+            .unwrap();
+            assert_eq!(surviving(&engine, &commits), expected, "{wanted}");
+        }
+    }
+
+    #[test]
+    fn test_label_and_pr_filters_use_the_imported_pull_request() {
+        let landed = |author: &str, number: u64, labels: &[&str]| CommitInfo {
+            pull_request: Some(PullRequest {
+                number,
+                labels: labels.iter().map(|l| l.to_string()).collect(),
+                ..Default::default()
+            }),
+            ..commit(author, "2024-06-01", "")
+        };
+        let commits = [
+            landed("Alice", 12, &["Security", "bug"]),
+            landed("Bob", 13, &["docs"]),
+            commit("Carol", "2024-06-01", ""),
+        ];
+
+        let label = FilterEngine::new(SearchFilters {
+            label: Some("security".to_string()),
+            ..no_filters()
+        })
+        .unwrap();
+        assert_eq!(surviving(&label, &commits), vec!["Alice"]);
+
+        for raw in ["13", "#13", "!13"] {
+            let pr = FilterEngine::new(SearchFilters {
+                pr: Some(raw.to_string()),
+                ..no_filters()
+            })
+            .unwrap();
+            assert_eq!(surviving(&pr, &commits), vec!["Bob"], "{raw}");
+        }
+
+        let err = FilterEngine::new(SearchFilters {
+            pr: Some("twelve".to_string()),
+            ..no_filters()
+        })
+        .err()
+        .unwrap();
+        assert_eq!(err.code(), "E4005");
+    }
+
+    #[test]
+    fn test_date_field_picks_the_committer_date() {
+        // Written in January, rebased onto main in June.
+        let mut rebased = commit("Alice", "2024-01-15", "");
+        rebased.committer_date = commit("Alice", "2024-06-15", "").date;
+        let bounds = |date_field| SearchFilters {
+            after: Some("2024-06-01".to_string()),
+            date_field,
+            ..no_filters()
+        };
+
+        let by_author = FilterEngine::new(bounds(DateField::Author)).unwrap();
+        let by_committer = FilterEngine::new(bounds(DateField::Committer)).unwrap();
+        assert!(!by_author.matches(&rebased));
+        assert!(by_committer.matches(&rebased));
+    }
+
+    #[test]
+    fn test_date_bounds_are_inclusive() {
+        // On the clock's own zone, so the day is the same wherever this runs.
+        let engine = FilterEngine::at(
+            SearchFilters {
+                after: Some("2024-06-15".to_string()),
+                before: Some("2024-06-15".to_string()),
+                ..no_filters()
+            },
+            clock(),
+        )
+        .unwrap();
+
         assert!(
             engine.matches(&commit("Alice", "2024-06-15", "")),
             "a commit on the boundary day must be included"
@@ -1,13 +1,17 @@
 mod engine;
 mod error;
+mod federated;
 mod filter;
 mod fusion;
 mod mmr;
+mod query;
 
 pub use engine::{RetrievalMode, SearchEngine, SearchOptions, SearchOutcome, SearchStrategy};
 pub use error::SearchError;
+pub use federated::{FederatedOutcome, WORKSPACE_KEY, Workspace, WorkspaceRepo, merge};
 pub use fusion::{RRF_K, Ranking, reciprocal_rank_fusion};
 pub use mmr::{Candidate, DEFAULT_LAMBDA, rerank};
+pub use query::InlineQuery;
 
 use crate::git::CommitInfo;
 use serde::{Deserialize, Serialize};
@@ -0,0 +1,409 @@
+//! Filters written inline in the query string.
+//!
+//! `author:alice after:2024-01-01 file:src/auth/ -path:tests "token refresh"`
+//! is the same search as `"token refresh"` with `--author alice --after
+//! 2024-01-01 --file src/auth/ --exclude-file tests`. Tools that can only
+//! hand over one string get the full filter set, and typing a search stays
+//! one line.
+//!
+//! Repeating an operator asks for any of its values, as repeating the flag
+//! does, and a `-` in front excludes instead. Only known keys are operators,
+//! so `error: connection refused` or a URL in the query is still plain text.
+//! Quoting a whole term — `"author:alice"` — keeps it as text too.
+
+use crate::cli::SearchFilters;
+
+use super::SearchError;
+
+/// A query split into the words to rank by and the filters it asked for.
+#[derive(Debug)]
+pub struct InlineQuery {
+    /// The free text, with operators removed and quotes dropped.
+    pub text: String,
+    /// The filters passed in, plus every operator from the query.
+    pub filters: SearchFilters,
+}
+
+impl InlineQuery {
+    /// Split `raw`, folding its operators into `filters`.
+    ///
+    /// An operator that sets a single-valued filter already set — by a flag or
+    /// earlier in the query — is an error rather than a silent override.
+    /// Operators for filters that take several values add to them.
+    pub fn parse(raw: &str, filters: SearchFilters) -> Result<Self, SearchError> {
+        let mut query = Self {
+            text: String::new(),
+            filters,
+        };
+        let mut words = Vec::new();
+
+        for term in tokenize(raw)? {
+            match term.operator() {
+                Some((negated, key, value)) => query.apply(&term.raw, negated, key, value)?,
+                None => words.push(term.text),
+            }
+        }
+
+        query.text = words.join(" ");
+        if query.text.is_empty() {
+            return Err(SearchError::EmptyQuery);
+        }
+        Ok(query)
+    }
+
+    fn apply(
+        &mut self,
+        raw: &str,
+        negated: bool,
+        key: &str,
+        value: String,
+    ) -> Result<(), SearchError> {
+        let invalid = |reason: &str| SearchError::InvalidQuery {
+            term: raw.to_string(),
+            reason: reason.to_string(),
+        };
+        let filters = &mut self.filters;
+
+        // Filters that take several values collect them: repeating one means
+        // any of its values may match, and `-` in front excludes instead.
+        let list = match (key, negated) {
+            ("author", false) => Some(&mut filters.authors),
+            ("author", true) => Some(&mut filters.exclude_authors),
+            ("email", false) => Some(&mut filters.emails),
+            ("email", true) => Some(&mut filters.exclude_emails),
+            ("committer", false) => Some(&mut filters.committers),
+            ("committer", true) => Some(&mut filters.exclude_committers),
+            ("file" | "path", false) => Some(&mut filters.files),
+            ("file" | "path", true) => Some(&mut filters.exclude_files),
+            ("message", false) => Some(&mut filters.messages),
+            ("message", true) => Some(&mut filters.exclude_messages),
+            ("trailer", false) => Some(&mut filters.trailer),
+            ("in", false) => Some(&mut filters.revisions),
+            _ => None,
+        };
+        if let Some(list) = list {
+            list.push(value);
+            return Ok(());
+        }
+        if (key, negated) == ("in", true) {
+            filters.revisions.push(format!("^{value}"));
+            return Ok(());
+        }
+        if key == "type" {
+            let types = match negated {
+                false => &mut filters.types,
+                true => &mut filters.exclude_types,
+            };
+            types.extend(value.split(',').map(str::to_string));
+            return Ok(());
+        }
+        if negated {
+            return Err(invalid(
+                "only author:, email:, committer:, file:, path:, message:, type: and in: can be negated",
+            ));
+        }
+
+        let slot = match key {
+            "after" | "since" => &mut filters.after,
+            "before" | "until" => &mut filters.before,
+            "symbol" => &mut filters.symbol,
+            "scope" => &mut filters.scope,
+            "issue" => &mut filters.issue,
+            "label" => &mut filters.label,
+            "pr" => &mut filters.pr,
+            "lines" => {
+                if filters.min_lines.is_some() {
+                    return Err(invalid("this filter is already set"));
+                }
+                let lines = value
+                    .parse()
+                    .map_err(|_| invalid("expected a number of lines, e.g. lines:50"))?;
+                filters.min_lines = Some(lines);
+                return Ok(());
+            }
+            "is" => {
+                let flag = match value.to_lowercase().as_str() {
+                    "added" => &mut filters.added,
+                    "deleted" => &mut filters.deleted,
+                    "breaking" => &mut filters.breaking,
+                    _ => return Err(invalid("expected is:added, is:deleted or is:breaking")),
+                };
+                *flag = true;
+                return Ok(());
+            }
+            _ => unreachable!("operator() only returns known keys"),
+        };
+
+        if slot.is_some() {
+            return Err(invalid("this filter is already set"));
+        }
+        *slot = Some(value);
+        Ok(())
+    }
+}
+
+/// Keys recognized as operators. Anything else before a colon is text.
+const OPERATORS: &[&str] = &[
+    "author",
+    "email",
+    "committer",
+    "after",
+    "since",
+    "before",
+    "until",
+    "file",
+    "path",
+    "message",
+    "lines",
+    "symbol",
+    "trailer",
+    "type",
+    "scope",
+    "is",
+    "issue",
+    "label",
+    "pr",
+    "in",
+];
+
+/// One whitespace-separated term of the query.
+struct Term {
+    /// As written, for error messages.
+    raw: String,
+    /// With quotes removed.
+    text: String,
+    /// Byte offset in `text` of the first colon outside quotes, when it comes
+    /// before any quoted part.
+    colon: Option<usize>,
+}
+
+impl Term {
+    /// `(negated, key, value)` when the term is an operator. A key with
+    /// nothing after its colon — `type: mismatch` — is prose, not a filter.
+    fn operator(&self) -> Option<(bool, &'static str, String)> {
+        let colon = self.colon?;
+        let (key, value) = (&self.text[..colon], &self.text[colon + 1..]);
+        if value.is_empty() {
+            return None;
+        }
+        let (negated, key) = match key.strip_prefix('-') {
+            Some(key) => (true, key),
+            None => (false, key),
+        };
+        let key = OPERATORS
+            .iter()
+            .find(|known| known.eq_ignore_ascii_case(key))
+            .copied()?;
+        Some((negated, key, value.to_string()))
+    }
+}
+
+/// Split on whitespace outside double quotes.
+fn tokenize(raw: &str) -> Result<Vec<Term>, SearchError> {
+    let mut terms = Vec::new();
+    let mut chars = raw.char_indices().peekable();
+
+    while let Some(&(start, _)) = chars.peek() {
+        if chars.next_if(|(_, c)| c.is_whitespace()).is_some() {
+            continue;
+        }
+
+        let mut text = String::new();
+        let mut colon = None;
+        let mut quoted_yet = false;
+        let mut in_quotes = false;
+        let mut end = raw.len();
+
+        while let Some(&(at, c)) = chars.peek() {
+            if c.is_whitespace() && !in_quotes {
+                end = at;
+                break;
+            }
+            chars.next();
+            match c {
+                '"' => {
+                    in_quotes = !in_quotes;
+                    quoted_yet = true;
+                }
+                ':' if !quoted_yet && colon.is_none() => {
+                    colon = Some(text.len());
+                    text.push(c);
+                }
+                _ => text.push(c),
+            }
+        }
+
+        let raw = raw[start..end].to_string();
+        if in_quotes {
+            return Err(SearchError::InvalidQuery {
+                term: raw,
+                reason: "the closing quote is missing".to_string(),
+            });
+        }
+        terms.push(Term { raw, text, colon });
+    }
+
+    Ok(terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> InlineQuery {
+        InlineQuery::parse(raw, SearchFilters::default()).unwrap()
+    }
+
+    fn reason(raw: &str) -> String {
+        match InlineQuery::parse(raw, SearchFilters::default()).unwrap_err() {
+            SearchError::InvalidQuery { reason, .. } => reason,
+            other => panic!("expected an invalid query, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn operators_become_filters_and_the_rest_is_the_query() {
+        let query =
+            parse(r#"author:alice after:2024-01-01 file:src/auth/ -path:tests "token refresh""#);
+        assert_eq!(query.text, "token refresh");
+        assert_eq!(query.filters.authors, ["alice"]);
+        assert_eq!(query.filters.after.as_deref(), Some("2024-01-01"));
+        assert_eq!(query.filters.files, ["src/auth/"]);
+        assert_eq!(query.filters.exclude_files, ["tests"]);
+    }
+
+    #[test]
+    fn words_around_operators_keep_their_order() {
+        let query = parse("fix the type:fix flaky scope:net timeout");
+        assert_eq!(query.text, "fix the flaky timeout");
+        assert_eq!(query.filters.types, ["fix"]);
+        assert_eq!(query.filters.scope.as_deref(), Some("net"));
+    }
+
+    #[test]
+    fn unknown_keys_and_quoted_terms_stay_text() {
+        let query = parse(r#"error: connection refused https://example.com "author:alice""#);
+        assert_eq!(
+            query.text,
+            "error: connection refused https://example.com author:alice"
+        );
+        assert!(query.filters.authors.is_empty());
+    }
+
+    #[test]
+    fn quoted_values_keep_their_spaces() {
+        let query = parse(r#"Author:"Alice Chen" trailer:reviewed-by=bob leak"#);
+        assert_eq!(query.filters.authors, ["Alice Chen"]);
+        assert_eq!(query.filters.trailer, ["reviewed-by=bob"]);
+        assert_eq!(query.text, "leak");
+    }
+
+    #[test]
+    fn flags_and_numbers_parse() {
+        let query = parse("is:breaking IS:added lines:50 type:fix,perf pr:#12 api");
+        assert!(query.filters.breaking && query.filters.added && !query.filters.deleted);
+        assert_eq!(query.filters.min_lines, Some(50));
+        assert_eq!(query.filters.types, ["fix", "perf"]);
+        assert_eq!(query.filters.pr.as_deref(), Some("#12"));
+    }
+
+    #[test]
+    fn since_and_until_name_the_date_bounds() {
+        let query = parse(r#"since:2.weeks.ago until:"last month" crash"#);
+        assert_eq!(query.filters.after.as_deref(), Some("2.weeks.ago"));
+        assert_eq!(query.filters.before.as_deref(), Some("last month"));
+        assert_eq!(
+            reason("after:today since:yesterday crash"),
+            "this filter is already set"
+        );
+    }
+
+    #[test]
+    fn inline_operators_add_to_the_flags() {
+        let flags = SearchFilters {
+            authors: vec!["alice".to_string()],
+            types: vec!["fix".to_string()],
+            ..SearchFilters::default()
+        };
+        let query = InlineQuery::parse("author:bob type:perf cache", flags).unwrap();
+        assert_eq!(query.filters.authors, ["alice", "bob"]);
+        assert_eq!(query.filters.types, ["fix", "perf"]);
+
+        let flags = SearchFilters {
+            scope: Some("auth".to_string()),
+            ..SearchFilters::default()
+        };
+        let err = InlineQuery::parse("scope:net cache", flags).unwrap_err();
+        assert_eq!(err.code(), "E4007");
+    }
+
+    #[test]
+    fn repeats_collect_and_a_minus_excludes() {
+        let query = parse(
+            r#"author:alice author:bob -author:release-bot -email:@bots.example file:src/**/*.rs -path:tests message:"^fix(\(auth\))?:" -message:revert -type:chore,docs login"#,
+        );
+        let filters = &query.filters;
+        assert_eq!(filters.authors, ["alice", "bob"]);
+        assert_eq!(filters.exclude_authors, ["release-bot"]);
+        assert_eq!(filters.exclude_emails, ["@bots.example"]);
+        assert_eq!(filters.files, ["src/**/*.rs"]);
+        assert_eq!(filters.exclude_files, ["tests"]);
+        assert_eq!(filters.messages, [r"^fix(\(auth\))?:"]);
+        assert_eq!(filters.exclude_messages, ["revert"]);
+        assert_eq!(filters.exclude_types, ["chore", "docs"]);
+        assert_eq!(query.text, "login");
+    }
+
+    #[test]
+    fn in_names_revisions_and_a_minus_leaves_one_out() {
+        let query = parse("in:release/2.3 in:main..feature -in:main tls timeout");
+        assert_eq!(
+            query.filters.revisions,
+            ["release/2.3", "main..feature", "^main"]
+        );
+        assert_eq!(query.text, "tls timeout");
+    }
+
+    #[test]
+    fn malformed_operators_are_reported() {
+        assert_eq!(
+            reason("lines:lots leak"),
+            "expected a number of lines, e.g. lines:50"
+        );
+        assert_eq!(
+            reason("is:merged leak"),
+            "expected is:added, is:deleted or is:breaking"
+        );
+        assert_eq!(
+            reason("-scope:ci leak"),
+            "only author:, email:, committer:, file:, path:, message:, type: and in: can be negated"
+        );
+        assert_eq!(reason("scope:a scope:b leak"), "this filter is already set");
+        assert_eq!(
+            reason(r#"leak "token refresh"#),
+            "the closing quote is missing"
+        );
+    }
+
+    #[test]
+    fn a_key_with_no_value_is_plain_text() {
+        for raw in [
+            "type: mismatch",
+            "path: not found",
+            "is: null check",
+            "-in: place",
+        ] {
+            let query = parse(raw);
+            assert_eq!(query.text, raw);
+            assert!(!query.filters.breaking && query.filters.types.is_empty());
+            assert!(query.filters.files.is_empty() && query.filters.revisions.is_empty());
+        }
+    }
+
+    #[test]
+    fn a_query_of_only_filters_has_nothing_to_rank() {
+        let err = InlineQuery::parse("author:alice", SearchFilters::default()).unwrap_err();
+        assert!(matches!(err, SearchError::EmptyQuery));
+        assert_eq!(err.code(), "E4008");
+    }
+}
@@ -96,6 +96,10 @@ impl Bm25Index {
         doc
     }
 
+    pub fn params(&self) -> Bm25Params {
+        self.params
+    }
+
     pub fn len(&self) -> usize {
         self.doc_lengths.len()
     }
@@ -57,6 +57,7 @@ fn index_with(count: usize) -> SemanticIndex {
                     .with_timezone(&chrono::Utc),
                 message: format!("commit {i}"),
                 diff_summary: format!("+src/module{}.rs", i % 7),
+                ..Default::default()
             },
             embedding: embedding(i),
         });
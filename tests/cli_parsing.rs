@@ -35,9 +35,112 @@ fn test_search_help() {
     assert!(stdout.contains("--after"));
     assert!(stdout.contains("--before"));
     assert!(stdout.contains("--file"));
+    assert!(stdout.contains("--email"));
+    assert!(stdout.contains("--committer"));
+    assert!(stdout.contains("--trailer"));
+    assert!(stdout.contains("--breaking"));
+    assert!(stdout.contains("--issue"));
+    assert!(stdout.contains("--label"));
+    assert!(stdout.contains("--date-field"));
+    assert!(stdout.contains("--pr"));
+    assert!(stdout.contains("--workspace"));
+    assert!(stdout.contains("--exclude-author"));
+    assert!(stdout.contains("--exclude-file"));
+    assert!(stdout.contains("--message"));
+    assert!(stdout.contains("--since"));
+    assert!(stdout.contains("--until"));
+    assert!(stdout.contains("--in <REV>"));
+    assert!(stdout.contains("[REVISION]..."));
     assert!(stdout.contains("-n"));
 }
 
+#[test]
+fn test_extra_query_words_are_not_read_as_revisions() {
+    let output = git_semantic_bin()
+        .args(["search", "fix", "login", "bug"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("unexpected argument 'login'"),
+        "stderr: {stderr}"
+    );
+    assert!(!stderr.contains("E2010"), "stderr: {stderr}");
+}
+
+#[test]
+fn test_search_workspace_needs_repositories_listed() {
+    let home = tempfile::TempDir::new().unwrap();
+    let output = git_semantic_bin()
+        .args(["search", "--workspace", "retry logic"])
+        .current_dir(home.path())
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path())
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("E4006"), "stderr: {stderr}");
+}
+
+#[test]
+fn test_search_workspace_and_path_conflict() {
+    let output = git_semantic_bin()
+        .args(["search", "--workspace", "--path", ".", "retry logic"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_issue_help() {
+    let output = git_semantic_bin()
+        .args(["issue", "--help"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("<REFERENCE>"));
+    assert!(stdout.contains("[QUERY]"));
+    assert!(stdout.contains("--json"));
+}
+
+#[test]
+fn test_import_prs_help() {
+    let output = git_semantic_bin()
+        .args(["import-prs", "--help"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("<FILE>"));
+    assert!(stdout.contains("gh pr list"));
+}
+
+#[test]
+fn test_export_and_import_help() {
+    let output = git_semantic_bin()
+        .args(["export", "--help"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("--format"));
+    assert!(stdout.contains("jsonl"));
+    assert!(stdout.contains("--output"));
+
+    let output = git_semantic_bin()
+        .args(["import", "--help"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("<FILE>"));
+    assert!(stdout.contains("--force"));
+}
+
 #[test]
 fn test_index_help() {
     let output = git_semantic_bin()
@@ -51,6 +154,20 @@ fn test_index_help() {
     assert!(stdout.contains("--force"));
 }
 
+#[test]
+fn test_index_push_and_fetch_help() {
+    for action in ["push", "fetch"] {
+        let output = git_semantic_bin()
+            .args(["index", action, "--help"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("[REMOTE]"));
+        assert!(stdout.contains("refs/semantic/index"));
+    }
+}
+
 #[test]
 fn test_unknown_subcommand_fails() {
     let output = git_semantic_bin().arg("foobar").output().unwrap();
@@ -83,6 +200,103 @@ fn test_stats_help() {
     assert!(output.status.success());
 }
 
+#[test]
+fn test_cache_prune_help() {
+    let output = git_semantic_bin()
+        .args(["cache", "prune", "--help"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("--max-size"));
+}
+
+#[test]
+fn test_cache_prune_rejects_a_bad_size() {
+    let output = git_semantic_bin()
+        .args(["cache", "prune", "--max-size", "lots"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("E3011"), "stderr: {stderr}");
+}
+
+/// Run `git-semantic` in `repo` with an empty home, so the global git config
+/// is the one the test writes.
+fn git_semantic_in(repo: &tempfile::TempDir, home: &tempfile::TempDir) -> Command {
+    let mut command = git_semantic_bin();
+    command
+        .current_dir(repo.path())
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path())
+        .env("GIT_CONFIG_NOSYSTEM", "1");
+    command
+}
+
+#[test]
+fn test_config_shows_where_each_setting_comes_from() {
+    let repo = empty_repo();
+    let home = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        home.path().join(".gitconfig"),
+        "[semantic]\n\tresults = 5\n\tlambda = 0.5\n",
+    )
+    .unwrap();
+    std::fs::write(
+        repo.path().join(".git-semantic.toml"),
+        "[semantic]\nresults = 20\nmode = \"lexical\"\n",
+    )
+    .unwrap();
+    git2::Repository::open(repo.path())
+        .unwrap()
+        .config()
+        .unwrap()
+        .set_str("semantic.mode", "semantic")
+        .unwrap();
+
+    let output = git_semantic_in(&repo, &home)
+        .arg("config")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = |key: &str| {
+        stdout
+            .lines()
+            .find(|line| line.split_whitespace().next() == Some(key))
+            .unwrap_or_else(|| panic!("no {key} in: {stdout}"))
+            .split_whitespace()
+            .skip(1)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    assert_eq!(line("semantic.mode"), "semantic local git config");
+    assert_eq!(line("semantic.results"), "20 .git-semantic.toml");
+    assert_eq!(line("semantic.lambda"), "0.5 global git config");
+    assert_eq!(line("semantic.hnswM"), "16 default");
+}
+
+#[test]
+fn test_an_invalid_setting_is_reported_with_its_code() {
+    let repo = empty_repo();
+    let home = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        repo.path().join(".git-semantic.toml"),
+        "[semantic]\nmode = \"fuzzy\"\n",
+    )
+    .unwrap();
+
+    let output = git_semantic_in(&repo, &home)
+        .args(["search", "retry logic"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("semantic.mode"), "stderr: {stderr}");
+    assert!(stderr.contains("E5001"), "stderr: {stderr}");
+}
+
 #[test]
 fn test_update_help() {
     let output = git_semantic_bin()
@@ -140,7 +354,18 @@ fn test_completions_cover_every_subcommand() {
         .unwrap();
     let stdout = String::from_utf8_lossy(&output.stdout);
 
-    for subcommand in ["init", "index", "update", "search", "stats", "completions"] {
+    for subcommand in [
+        "init",
+        "index",
+        "update",
+        "search",
+        "stats",
+        "config",
+        "export",
+        "import",
+        "cache",
+        "completions",
+    ] {
         assert!(
             stdout.contains(subcommand),
             "completion script should know about `{subcommand}`"
@@ -5,7 +5,7 @@
 //! the feature exists for.
 
 use git_semantic::cli::JsonOutput;
-use git_semantic::git::CommitInfo;
+use git_semantic::git::{ChangeStatus, CommitInfo, FileChange};
 use git_semantic::index::{IndexEntry, SemanticIndex};
 use git_semantic::search::{
     Candidate, DEFAULT_LAMBDA, RetrievalMode, SearchOutcome, SearchResult, SearchStrategy, rerank,
@@ -45,8 +45,12 @@ fn entry(idx: usize, message: &str, family: usize) -> IndexEntry {
                 .unwrap()
                 .with_timezone(&chrono::Utc),
             message: message.to_string(),
-            // Sorted, matching what the extractor's BTreeSet actually emits.
-            diff_summary: "Files: Cargo.lock, Cargo.toml\n+version".to_string(),
+            diff_summary: "+version".to_string(),
+            files: vec![
+                FileChange::new("Cargo.lock", ChangeStatus::Modified),
+                FileChange::new("Cargo.toml", ChangeStatus::Modified),
+            ],
+            ..Default::default()
         },
         embedding: embedding(family, idx),
     }
@@ -135,3 +135,284 @@ fn test_commit_date_is_set() {
     let diff = now - commits[0].date;
     assert!(diff.num_seconds() < 60, "commit date should be recent");
 }
+
+#[test]
+fn test_author_committer_and_co_authors_are_recorded() {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+
+    let written = git2::Time::new(1_700_000_000, 0);
+    let landed = git2::Time::new(1_700_086_400, 0);
+    let author = Signature::new("Alice Chen", "alice@example.com", &written).unwrap();
+    let committer = Signature::new("GitHub", "noreply@github.com", &landed).unwrap();
+
+    let tree_id = repo.index().unwrap().write_tree().unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &author,
+        &committer,
+        "feat: pair on parser\n\nCo-authored-by: Bob Smith <bob@example.com>\n",
+        &tree,
+        &[],
+    )
+    .unwrap();
+
+    let parser = git_semantic::git::RepositoryParser::new(dir.path()).unwrap();
+    let commit = &parser.parse_commits(false).unwrap()[0];
+
+    assert_eq!(commit.author, "Alice Chen");
+    assert_eq!(commit.author_email, "alice@example.com");
+    assert_eq!(commit.date.timestamp(), 1_700_000_000, "author date");
+    assert_eq!(commit.committer, "GitHub");
+    assert_eq!(commit.committer_email, "noreply@github.com");
+    assert_eq!(commit.committer_date.timestamp(), 1_700_086_400);
+    assert_eq!(commit.co_authors.len(), 1);
+    assert_eq!(commit.co_authors[0].name, "Bob Smith");
+    assert_eq!(commit.co_authors[0].email, "bob@example.com");
+    assert_eq!(commit.trailers.len(), 1);
+    assert_eq!(commit.trailers[0].key, "Co-authored-by");
+    let header = commit.conventional.as_ref().unwrap();
+    assert_eq!((header.kind.as_str(), header.breaking), ("feat", false));
+}
+
+#[test]
+fn test_time_zone_offsets_are_preserved() {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+
+    // 04:17 in New York, applied at 17:17 in Kolkata the same day.
+    let written = git2::Time::new(1_718_439_420, -4 * 60);
+    let landed = git2::Time::new(1_718_452_020, 330);
+    let author = Signature::new("Alice Chen", "alice@example.com", &written).unwrap();
+    let committer = Signature::new("Bob Smith", "bob@example.com", &landed).unwrap();
+
+    let tree_id = repo.index().unwrap().write_tree().unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &author,
+        &committer,
+        "fix: night shift",
+        &tree,
+        &[],
+    )
+    .unwrap();
+
+    let parser = git_semantic::git::RepositoryParser::new(dir.path()).unwrap();
+    let commit = &parser.parse_commits(false).unwrap()[0];
+
+    assert_eq!(
+        commit.local_date().to_rfc3339(),
+        "2024-06-15T04:17:00-04:00"
+    );
+    assert_eq!(
+        commit
+            .local_date_for(git_semantic::git::DateField::Committer)
+            .to_rfc3339(),
+        "2024-06-15T17:17:00+05:30"
+    );
+}
+
+#[test]
+fn test_legacy_encodings_and_non_utf8_diffs_are_decoded() {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+
+    // A Latin-1 source file, committed with a Latin-1 message. `git2` has no
+    // way to set the `encoding` header, so the commit object is written raw.
+    fs::write(dir.path().join("greet.c"), b"puts(\"Jos\xe9\");\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("greet.c")).unwrap();
+    index.write().unwrap();
+    let tree_id = index.write_tree().unwrap();
+
+    let mut raw = format!(
+        "tree {tree_id}\n\
+         author Alice <alice@example.com> 1700000000 +0100\n\
+         committer Alice <alice@example.com> 1700000000 +0100\n\
+         encoding ISO-8859-1\n\n"
+    )
+    .into_bytes();
+    raw.extend_from_slice(b"feat: saluer Jos\xe9 en fran\xe7ais\n");
+    let oid = repo
+        .odb()
+        .unwrap()
+        .write(git2::ObjectType::Commit, &raw)
+        .unwrap();
+    repo.reference("refs/heads/master", oid, true, "raw commit")
+        .unwrap();
+    repo.set_head("refs/heads/master").unwrap();
+
+    let parser = git_semantic::git::RepositoryParser::new(dir.path()).unwrap();
+    let commit = &parser.parse_commits(true).unwrap()[0];
+
+    assert_eq!(commit.message, "feat: saluer José en français\n");
+    assert!(commit.diff_summary.contains("+puts(\"Jos\u{fffd}\");"));
+    assert!(commit.non_utf8);
+}
+
+#[test]
+fn test_mailmap_resolves_canonical_identities() {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    fs::write(
+        dir.path().join(".mailmap"),
+        "Yan Xue <yan@example.com> <yan@work.example>\n",
+    )
+    .unwrap();
+
+    let author = Signature::now("yan", "yan@work.example").unwrap();
+    let tree_id = repo.index().unwrap().write_tree().unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &author,
+        &author,
+        "fix: typo\n\nCo-authored-by: Yan <yan@work.example>\n",
+        &tree,
+        &[],
+    )
+    .unwrap();
+
+    let parser = git_semantic::git::RepositoryParser::new(dir.path()).unwrap();
+    let commit = &parser.parse_commits(false).unwrap()[0];
+
+    assert_eq!(commit.author, "yan", "the raw name is kept");
+    assert_eq!(commit.author_email, "yan@work.example");
+    assert_eq!(commit.canonical_author.name, "Yan Xue");
+    assert_eq!(commit.canonical_author.email, "yan@example.com");
+    assert_eq!(commit.canonical_committer.name, "Yan Xue");
+    assert_eq!(commit.co_authors[0].name, "Yan Xue");
+}
+
+#[test]
+fn test_issue_patterns_come_from_git_config() {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    repo.config()
+        .unwrap()
+        .set_multivar("semantic.issuePattern", "^$", "SR([0-9]{4})")
+        .unwrap();
+
+    let sig = Signature::now("Test Author", "test@example.com").unwrap();
+    let tree_id = repo.index().unwrap().write_tree().unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &sig,
+        &sig,
+        "fix: retry uploads (#42)\n\nReported in SR1234, tracked as OPS-7.",
+        &tree,
+        &[],
+    )
+    .unwrap();
+
+    let parser = git_semantic::git::RepositoryParser::new(dir.path()).unwrap();
+    let commit = &parser.parse_commits(false).unwrap()[0];
+
+    assert_eq!(commit.issues, vec!["#42", "OPS-7", "SR1234"]);
+}
+
+#[test]
+fn test_notes_are_read_from_configured_refs() {
+    let dir = create_test_repo_with_commits(2, false);
+    let repo = Repository::open(dir.path()).unwrap();
+    let sig = Signature::now("Reviewer", "review@example.com").unwrap();
+    let head = repo.head().unwrap().target().unwrap();
+    repo.note(
+        &sig,
+        &sig,
+        Some("refs/notes/review"),
+        head,
+        "Approved",
+        false,
+    )
+    .unwrap();
+    repo.note(&sig, &sig, None, head, "Default-ref note", false)
+        .unwrap();
+
+    let parser = git_semantic::git::RepositoryParser::new(dir.path()).unwrap();
+    assert!(
+        parser.parse_commits(false).unwrap()[0].notes.is_empty(),
+        "notes stay out until a ref is configured"
+    );
+
+    repo.config()
+        .unwrap()
+        .set_multivar("semantic.notesRef", "^$", "review")
+        .unwrap();
+    let parser = git_semantic::git::RepositoryParser::new(dir.path()).unwrap();
+    let commits = parser.parse_commits(false).unwrap();
+
+    assert_eq!(commits[0].notes.len(), 1);
+    assert_eq!(commits[0].notes[0].notes_ref, "refs/notes/review");
+    assert_eq!(commits[0].notes[0].message, "Approved");
+    assert!(commits[1].notes.is_empty());
+}
+
+#[test]
+fn test_a_note_added_after_indexing_moves_its_ref_and_is_read_again() {
+    let dir = create_test_repo_with_commits(2, false);
+    let repo = Repository::open(dir.path()).unwrap();
+    repo.config()
+        .unwrap()
+        .set_multivar("semantic.notesRef", "^$", "review")
+        .unwrap();
+    let parser = git_semantic::git::RepositoryParser::new(dir.path()).unwrap();
+    let indexed = parser.parse_commits(false).unwrap();
+    let tips = parser.notes_tips().unwrap();
+    assert!(tips.is_empty(), "the ref does not exist yet");
+
+    let sig = Signature::now("CI", "ci@example.com").unwrap();
+    let head = repo.head().unwrap().target().unwrap();
+    repo.note(
+        &sig,
+        &sig,
+        Some("refs/notes/review"),
+        head,
+        "Deployed",
+        false,
+    )
+    .unwrap();
+
+    let moved = parser.notes_tips().unwrap();
+    assert_ne!(moved, tips);
+    assert_eq!(
+        moved["refs/notes/review"],
+        repo.refname_to_id("refs/notes/review").unwrap().to_string()
+    );
+    let notes = parser.notes_on(&indexed[0].hash).unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].message, "Deployed");
+    assert!(parser.notes_on(&indexed[1].hash).unwrap().is_empty());
+    assert!(parser.notes_on("not-a-hash").unwrap().is_empty());
+}
+
+#[test]
+fn test_annotated_tags_become_documents_pointing_at_their_commit() {
+    let dir = create_test_repo_with_commits(2, false);
+    let repo = Repository::open(dir.path()).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let tagger = Signature::now("Release Bot", "release@example.com").unwrap();
+    repo.tag(
+        "v1.0.0",
+        head.as_object(),
+        &tagger,
+        "Release 1.0.0\n\nFirst stable release (#7).",
+        false,
+    )
+    .unwrap();
+    repo.tag_lightweight("nightly", head.as_object(), false)
+        .unwrap();
+
+    let parser = git_semantic::git::RepositoryParser::new(dir.path()).unwrap();
+    let tags = parser.parse_tags().unwrap();
+
+    assert_eq!(tags.len(), 1, "lightweight tags have no text to index");
+    assert_eq!(tags[0].tag.as_deref(), Some("v1.0.0"));
+    assert_eq!(tags[0].hash, head.id().to_string());
+    assert_eq!(tags[0].author, "Release Bot");
+    assert!(tags[0].message.starts_with("Release 1.0.0"));
+    assert_eq!(tags[0].issues, vec!["#7"]);
+}
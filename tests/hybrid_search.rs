@@ -9,7 +9,7 @@
 //! which is exactly what a real 384-dimensional embedding does when it cannot
 //! distinguish `CVE-2024-1234` from surrounding prose.
 
-use git_semantic::git::CommitInfo;
+use git_semantic::git::{ChangeStatus, CommitInfo, FileChange};
 use git_semantic::index::{IndexEntry, IndexStorage, SemanticIndex};
 use git_semantic::search::{RRF_K, Ranking, reciprocal_rank_fusion};
 use git_semantic::text::Bm25Params;
@@ -42,7 +42,12 @@ fn entry(idx: usize, message: &str, files: &str, author: &str) -> IndexEntry {
                 .unwrap()
                 .with_timezone(&chrono::Utc),
             message: message.to_string(),
-            diff_summary: format!("Files: {files}\n+some change"),
+            diff_summary: "+some change".to_string(),
+            files: files
+                .split(", ")
+                .map(|path| FileChange::new(path, ChangeStatus::Modified))
+                .collect(),
+            ..Default::default()
         },
         embedding: embedding(idx),
     }
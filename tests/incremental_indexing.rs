@@ -70,6 +70,7 @@ fn make_entry(hash: &str, message: &str, diff: &str) -> IndexEntry {
                 .with_timezone(&chrono::Utc),
             message: message.to_string(),
             diff_summary: diff.to_string(),
+            ..Default::default()
         },
         embedding: vec![0.1; 384],
     }
@@ -701,3 +702,218 @@ fn test_force_flag_rebuilds_entire_index() {
     );
     assert_eq!(final_loaded.entries[0].commit.message, "feat: new1");
 }
+
+// ---------------------------------------------------------------------------
+// Tests: worktrees on other branches sharing one index
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_unindexed_commits_on_a_diverged_branch_are_found_without_a_rebuild() {
+    // main: c0 - c1 - c2, all indexed. side: c0 - c1 - s0, checked out in a
+    // linked worktree whose HEAD does not descend from main's.
+    let dir = create_test_repo_with_commits(3);
+    let repo = Repository::open(dir.path()).unwrap();
+    let parser = git_semantic::git::RepositoryParser::new(dir.path()).unwrap();
+    let main: Vec<String> = parser
+        .parse_commits(false)
+        .unwrap()
+        .into_iter()
+        .map(|c| c.hash)
+        .collect();
+
+    let fork = repo
+        .find_commit(git2::Oid::from_str(&main[1]).unwrap())
+        .unwrap();
+    repo.branch("side", &fork, false).unwrap();
+    let worktree = dir.path().join("side-wt");
+    let mut opts = git2::WorktreeAddOptions::new();
+    let side_ref = repo.find_reference("refs/heads/side").unwrap();
+    opts.reference(Some(&side_ref));
+    repo.worktree("side", &worktree, Some(&opts)).unwrap();
+
+    let side_repo = Repository::open(&worktree).unwrap();
+    let sig = Signature::now("Test Author", "test@example.com").unwrap();
+    let fork = side_repo.find_commit(fork.id()).unwrap();
+    let tree = fork.tree().unwrap();
+    let side_commit = side_repo
+        .commit(Some("HEAD"), &sig, &sig, "feat: side work", &tree, &[&fork])
+        .unwrap();
+
+    let indexed: std::collections::HashSet<&str> = main.iter().map(String::as_str).collect();
+    let side_parser = git_semantic::git::RepositoryParser::new(&worktree).unwrap();
+    assert!(
+        side_parser.parse_commits_since(&main[0], false).is_err(),
+        "main's HEAD is not an ancestor of side's"
+    );
+
+    let missing = side_parser
+        .parse_unindexed_commits(&indexed, &[main[0].as_str()], false)
+        .unwrap();
+    let hashes: Vec<&str> = missing.iter().map(|c| c.hash.as_str()).collect();
+    assert_eq!(hashes, vec![side_commit.to_string()]);
+
+    assert_eq!(
+        IndexStorage::new(&worktree).unwrap().index_path(),
+        IndexStorage::new(dir.path()).unwrap().index_path(),
+        "both worktrees use the same index"
+    );
+    assert!(
+        !parser.head_descends_from(&side_commit.to_string()).unwrap(),
+        "a worktree on another branch is not behind this HEAD"
+    );
+    assert!(
+        parser
+            .referenced_commits()
+            .unwrap()
+            .contains(&side_commit.to_string()),
+        "the other worktree's commits are still referenced"
+    );
+}
+
+#[test]
+fn test_commits_left_behind_by_a_rewrite_are_no_longer_referenced() {
+    // main: c0 - c1 - c2, then c2 is amended into c2'.
+    let dir = create_test_repo_with_commits(3);
+    let repo = Repository::open(dir.path()).unwrap();
+    let parser = git_semantic::git::RepositoryParser::new(dir.path()).unwrap();
+    let before: Vec<String> = parser
+        .parse_commits(false)
+        .unwrap()
+        .into_iter()
+        .map(|c| c.hash)
+        .collect();
+    assert!(parser.head_descends_from(&before[1]).unwrap());
+
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let amended = head
+        .amend(
+            Some("HEAD"),
+            None,
+            None,
+            None,
+            Some("feat: add feature 2, again"),
+            None,
+        )
+        .unwrap()
+        .to_string();
+
+    assert!(!parser.head_descends_from(&before[0]).unwrap());
+    assert!(!parser.head_descends_from("not-a-hash").unwrap());
+
+    let referenced = parser.referenced_commits().unwrap();
+    assert!(referenced.contains(&amended));
+    assert!(referenced.contains(&before[1]) && referenced.contains(&before[2]));
+    assert!(
+        !referenced.contains(&before[0]),
+        "the commit the amend replaced is reachable from nothing"
+    );
+}
+
+#[test]
+fn test_deleted_and_moved_tags_are_dropped() {
+    let dir = create_test_repo_with_commits(2);
+    let repo = Repository::open(dir.path()).unwrap();
+    let sig = Signature::now("Test Author", "test@example.com").unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let first = head.parent(0).unwrap();
+    repo.tag("v1.0", head.as_object(), &sig, "Release 1.0", false)
+        .unwrap();
+
+    // Indexed while v1.0 was on the first commit and v0.9 still existed.
+    let tag_entry = |hash: String, name: &str| {
+        let mut entry = make_entry(&hash, &format!("Release {name}"), "");
+        entry.commit.tag = Some(name.to_string());
+        entry
+    };
+    let (head, first) = (head.id().to_string(), first.id().to_string());
+    let index = make_index_with_entries(
+        vec![
+            make_entry(&head, "feat: add feature 1", ""),
+            make_entry(&first, "feat: add feature 0", ""),
+            tag_entry(head.clone(), "v1.0"),
+            tag_entry(first.clone(), "v1.0"),
+            tag_entry(first.clone(), "v0.9"),
+        ],
+        false,
+    );
+    let storage = IndexStorage::new(dir.path()).unwrap();
+    storage.save(&index).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_git-semantic"))
+        .args(["index", "--quick", "-p", dir.path().to_str().unwrap()])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "index failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("Dropping 2 tags"), "stdout: {stdout}");
+
+    let loaded = storage.load().unwrap();
+    let tags: Vec<_> = loaded
+        .entries
+        .iter()
+        .filter(|entry| entry.commit.is_tag())
+        .map(|entry| (entry.commit.hash.as_str(), entry.commit.tag.as_deref()))
+        .collect();
+    assert_eq!(tags, [(head.as_str(), Some("v1.0"))]);
+    assert_eq!(loaded.entries.len(), 3);
+    assert_eq!(loaded.metadata.total_commits, 3);
+}
+
+#[test]
+fn test_a_moved_notes_ref_is_recorded_without_re_embedding_unchanged_commits() {
+    let dir = create_test_repo_with_commits(1);
+    let repo = Repository::open(dir.path()).unwrap();
+    repo.config()
+        .unwrap()
+        .set_multivar("semantic.notesRef", "^$", "review")
+        .unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let index = make_index_with_entries(
+        vec![make_entry(
+            &head.id().to_string(),
+            "feat: add feature 0",
+            "",
+        )],
+        false,
+    );
+    let storage = IndexStorage::new(dir.path()).unwrap();
+    storage.save(&index).unwrap();
+
+    // The ref moves, but the note is on a tree, not on any indexed commit.
+    let sig = Signature::now("CI", "ci@example.com").unwrap();
+    repo.note(
+        &sig,
+        &sig,
+        Some("refs/notes/review"),
+        head.tree_id(),
+        "Scanned",
+        false,
+    )
+    .unwrap();
+
+    let index_quick = || {
+        std::process::Command::new(env!("CARGO_BIN_EXE_git-semantic"))
+            .args(["index", "--quick", "-p", dir.path().to_str().unwrap()])
+            .output()
+            .unwrap()
+    };
+    let output = index_quick();
+    assert!(
+        output.status.success(),
+        "index failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let loaded = storage.load().unwrap();
+    assert_eq!(
+        loaded.metadata.notes_tips["refs/notes/review"],
+        repo.refname_to_id("refs/notes/review").unwrap().to_string()
+    );
+    assert!(loaded.entries[0].commit.notes.is_empty());
+
+    let stdout = String::from_utf8_lossy(&index_quick().stdout).into_owned();
+    assert!(stdout.contains("already up to date"), "stdout: {stdout}");
+}
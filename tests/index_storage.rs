@@ -39,6 +39,7 @@ fn make_index(num_entries: usize, include_diffs: bool) -> SemanticIndex {
                 } else {
                     String::new()
                 },
+                ..Default::default()
             },
             embedding: vec![0.1; 384],
         });
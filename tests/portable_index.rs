@@ -0,0 +1,212 @@
+use git_semantic::git::CommitInfo;
+use git_semantic::index::{IndexEntry, IndexStorage, SemanticIndex};
+use git2::{Repository, Signature};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn git_semantic(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_git-semantic"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// A repository with two commits and an index of both.
+fn indexed_repo() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    let sig = Signature::now("Alice", "alice@example.com").unwrap();
+    let tree = repo
+        .find_tree(repo.index().unwrap().write_tree().unwrap())
+        .unwrap();
+    let first = repo
+        .commit(Some("HEAD"), &sig, &sig, "feat: first", &tree, &[])
+        .unwrap();
+    let first = repo.find_commit(first).unwrap();
+    let second = repo
+        .commit(Some("HEAD"), &sig, &sig, "fix: second", &tree, &[&first])
+        .unwrap();
+
+    let mut index = SemanticIndex::new("bge-small-en-v1.5".to_string(), second.to_string(), true);
+    for (hash, message) in [
+        (second.to_string(), "fix: second"),
+        (first.id().to_string(), "feat: first"),
+    ] {
+        index.entries.push(IndexEntry {
+            commit: CommitInfo {
+                hash: hash.to_string(),
+                author: "Alice".to_string(),
+                message: message.to_string(),
+                ..Default::default()
+            },
+            embedding: vec![0.5; 8],
+        });
+    }
+    index.metadata.total_commits = 2;
+    IndexStorage::new(dir.path()).unwrap().save(&index).unwrap();
+    dir
+}
+
+fn path_str(dir: &TempDir) -> &str {
+    dir.path().to_str().unwrap()
+}
+
+fn head_of(dir: &TempDir) -> String {
+    Repository::open(dir.path())
+        .unwrap()
+        .head()
+        .unwrap()
+        .target()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_export_to_stdout_is_one_json_object_per_line() {
+    let repo = indexed_repo();
+    let output = git_semantic(&["export", "--format", "jsonl", "-p", path_str(&repo)]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["model"], "bge-small-en-v1.5");
+    assert_eq!(lines[0]["mode"], "full");
+    assert_eq!(lines[1]["hash"], head_of(&repo));
+    assert_eq!(lines[1]["embedding"].as_array().unwrap().len(), 8);
+    // The summary stays off stdout so the records pipe cleanly.
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Exported 2 commits"));
+}
+
+#[test]
+fn test_an_exported_index_imports_into_another_repository() {
+    let source = indexed_repo();
+    let scratch = TempDir::new().unwrap();
+    let file = scratch.path().join("index.jsonl");
+    let file = file.to_str().unwrap();
+    assert!(
+        git_semantic(&["export", "-o", file, "-p", path_str(&source)])
+            .status
+            .success()
+    );
+
+    let target = TempDir::new().unwrap();
+    Repository::clone(path_str(&source), target.path()).unwrap();
+    let imported = git_semantic(&["import", file, "-p", path_str(&target)]);
+    assert!(
+        imported.status.success(),
+        "import failed: {}",
+        String::from_utf8_lossy(&imported.stderr)
+    );
+
+    let index = IndexStorage::new(target.path()).unwrap().load().unwrap();
+    assert_eq!(index.entries.len(), 2);
+    assert_eq!(index.entries[1].commit.message, "feat: first");
+    assert_eq!(index.last_commit, head_of(&source));
+}
+
+#[test]
+fn test_import_will_not_replace_an_index_without_force() {
+    let repo = indexed_repo();
+    let scratch = TempDir::new().unwrap();
+    let file = scratch.path().join("index.jsonl");
+    let file = file.to_str().unwrap();
+    assert!(
+        git_semantic(&["export", "-o", file, "-p", path_str(&repo)])
+            .status
+            .success()
+    );
+
+    let refused = git_semantic(&["import", file, "-p", path_str(&repo)]);
+    assert!(!refused.status.success());
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("E3017"));
+
+    assert!(
+        git_semantic(&["import", file, "--force", "-p", path_str(&repo)])
+            .status
+            .success()
+    );
+}
+
+#[test]
+fn test_abbreviated_hashes_are_imported_as_full_ids() {
+    let repo = indexed_repo();
+    let index = IndexStorage::new(repo.path()).unwrap().load().unwrap();
+    let hashes: Vec<&str> = index
+        .entries
+        .iter()
+        .map(|entry| entry.commit.hash.as_str())
+        .collect();
+
+    let scratch = TempDir::new().unwrap();
+    let file = scratch.path().join("index.jsonl");
+    let mut jsonl = format!(
+        "{{\"format\":\"git-semantic-index\",\"version\":1,\"model\":\"bge-small-en-v1.5\",\
+         \"mode\":\"quick\",\"last_commit\":\"{}\"}}\n",
+        &hashes[0][..7]
+    );
+    for hash in &hashes {
+        jsonl.push_str(&format!(
+            "{{\"hash\":\"{}\",\"embedding\":[0.5,0.5]}}\n",
+            &hash[..7]
+        ));
+    }
+    std::fs::write(&file, jsonl).unwrap();
+    let file = file.to_str().unwrap();
+
+    let imported = git_semantic(&["import", file, "--force", "-p", path_str(&repo)]);
+    assert!(
+        imported.status.success(),
+        "import failed: {}",
+        String::from_utf8_lossy(&imported.stderr)
+    );
+    let loaded = IndexStorage::new(repo.path()).unwrap().load().unwrap();
+    let loaded_hashes: Vec<&str> = loaded
+        .entries
+        .iter()
+        .map(|entry| entry.commit.hash.as_str())
+        .collect();
+    assert_eq!(loaded_hashes, hashes);
+    assert_eq!(loaded.last_commit, hashes[0]);
+
+    // Both commits are known by their full ids, so nothing needs embedding.
+    let indexed = git_semantic(&["index", "--quick", "-p", path_str(&repo)]);
+    let stdout = String::from_utf8_lossy(&indexed.stdout);
+    assert!(indexed.status.success(), "stdout: {stdout}");
+    assert!(stdout.contains("already up to date"), "stdout: {stdout}");
+    assert_eq!(
+        IndexStorage::new(repo.path())
+            .unwrap()
+            .load()
+            .unwrap()
+            .entries
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_a_hash_this_repository_lacks_is_rejected_with_its_line() {
+    let repo = TempDir::new().unwrap();
+    Repository::init(repo.path()).unwrap();
+    let scratch = TempDir::new().unwrap();
+    let file = scratch.path().join("index.jsonl");
+    std::fs::write(
+        &file,
+        "{\"format\":\"git-semantic-index\",\"version\":1,\"model\":\"bge-small-en-v1.5\",\"mode\":\"quick\"}\n\
+         {\"hash\":\"abc1234\",\"embedding\":[0.5]}\n",
+    )
+    .unwrap();
+
+    let refused = git_semantic(&["import", file.to_str().unwrap(), "-p", path_str(&repo)]);
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(!refused.status.success());
+    assert!(
+        stderr.contains("E3016") && stderr.contains("abc1234"),
+        "stderr: {stderr}"
+    );
+    assert!(IndexStorage::new(repo.path()).unwrap().load().is_err());
+}
@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+
+use git_semantic::git::{GitError, reachable};
+use git2::{Oid, Repository, Signature};
+use tempfile::TempDir;
+
+/// Commits `message` with an empty tree onto `branch`.
+fn commit(repo: &Repository, branch: &str, message: &str, parents: &[Oid]) -> Oid {
+    let sig = Signature::now("Test Author", "test@example.com").unwrap();
+    let tree_id = repo.treebuilder(None).unwrap().write().unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+    let parents: Vec<_> = parents
+        .iter()
+        .map(|id| repo.find_commit(*id).unwrap())
+        .collect();
+    let parents: Vec<_> = parents.iter().collect();
+    repo.commit(
+        Some(&format!("refs/heads/{branch}")),
+        &sig,
+        &sig,
+        message,
+        &tree,
+        &parents,
+    )
+    .unwrap()
+}
+
+/// `main` is A-B, `feature` branches from A with C-D and is checked out,
+/// and `v1` is an annotated tag on B.
+struct History {
+    _dir: TempDir,
+    repo: Repository,
+    a: Oid,
+    b: Oid,
+    c: Oid,
+    d: Oid,
+}
+
+fn history() -> History {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    let a = commit(&repo, "main", "A", &[]);
+    let b = commit(&repo, "main", "B", &[a]);
+    let c = commit(&repo, "feature", "C", &[a]);
+    let d = commit(&repo, "feature", "D", &[c]);
+    {
+        let sig = Signature::now("Test Author", "test@example.com").unwrap();
+        let target = repo.find_object(b, None).unwrap();
+        repo.tag("v1", &target, &sig, "release 1", false).unwrap();
+    }
+    repo.set_head("refs/heads/feature").unwrap();
+    History {
+        _dir: dir,
+        repo,
+        a,
+        b,
+        c,
+        d,
+    }
+}
+
+fn resolve(history: &History, specs: &[&str]) -> HashSet<String> {
+    let specs: Vec<String> = specs.iter().map(|spec| spec.to_string()).collect();
+    reachable(&history.repo, &specs).unwrap()
+}
+
+fn ids(ids: &[Oid]) -> HashSet<String> {
+    ids.iter().map(Oid::to_string).collect()
+}
+
+#[test]
+fn a_ref_reaches_its_whole_history() {
+    let h = history();
+    assert_eq!(resolve(&h, &["main"]), ids(&[h.a, h.b]));
+    assert_eq!(
+        resolve(&h, &["v1"]),
+        ids(&[h.a, h.b]),
+        "tags peel to commits"
+    );
+    assert_eq!(
+        resolve(&h, &["main", "feature"]),
+        ids(&[h.a, h.b, h.c, h.d])
+    );
+}
+
+#[test]
+fn ranges_read_as_in_git_log() {
+    let h = history();
+    assert_eq!(resolve(&h, &["main..feature"]), ids(&[h.c, h.d]));
+    assert_eq!(resolve(&h, &["main...feature"]), ids(&[h.b, h.c, h.d]));
+    assert_eq!(resolve(&h, &["feature", "^main"]), ids(&[h.c, h.d]));
+}
+
+#[test]
+fn a_range_missing_a_side_has_head_there() {
+    let h = history();
+    assert_eq!(resolve(&h, &["main.."]), ids(&[h.c, h.d]));
+    assert_eq!(resolve(&h, &["..main"]), ids(&[h.b]));
+    assert_eq!(resolve(&h, &["main..."]), ids(&[h.b, h.c, h.d]));
+    assert_eq!(resolve(&h, &["...main"]), ids(&[h.b, h.c, h.d]));
+}
+
+#[test]
+fn exclusions_alone_start_from_head() {
+    let h = history();
+    assert_eq!(resolve(&h, &["^main"]), ids(&[h.c, h.d]));
+}
+
+#[test]
+fn an_unknown_revision_is_named_in_the_error() {
+    let h = history();
+    for spec in ["nope", "^nope", "main..nope"] {
+        let err = reachable(&h.repo, &[spec.to_string()]).unwrap_err();
+        assert!(
+            matches!(&err, GitError::InvalidRevision(named, _) if named == spec),
+            "{spec}: {err:?}"
+        );
+        assert_eq!(err.code(), "E2010");
+    }
+}
@@ -0,0 +1,177 @@
+use git_semantic::git::CommitInfo;
+use git_semantic::index::{IndexEntry, IndexStorage, SemanticIndex};
+use git2::{Repository, Signature};
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn git_semantic(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_git-semantic"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// A repository with two commits and an index of both, embedded by `model`.
+fn indexed_repo(model: &str) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    let sig = Signature::now("Test Author", "test@example.com").unwrap();
+    let tree = repo
+        .find_tree(repo.index().unwrap().write_tree().unwrap())
+        .unwrap();
+    let first = repo
+        .commit(Some("HEAD"), &sig, &sig, "feat: first", &tree, &[])
+        .unwrap();
+    let first = repo.find_commit(first).unwrap();
+    let second = repo
+        .commit(Some("HEAD"), &sig, &sig, "fix: second", &tree, &[&first])
+        .unwrap();
+
+    let mut index = SemanticIndex::new(model.to_string(), second.to_string(), false);
+    for (hash, message) in [(second, "fix: second"), (first.id(), "feat: first")] {
+        index.entries.push(IndexEntry {
+            commit: CommitInfo {
+                hash: hash.to_string(),
+                message: message.to_string(),
+                ..Default::default()
+            },
+            embedding: vec![0.5; 8],
+        });
+    }
+    index.metadata.total_commits = 2;
+    IndexStorage::new(dir.path()).unwrap().save(&index).unwrap();
+    dir
+}
+
+fn bare_remote() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    Repository::init_bare(dir.path()).unwrap();
+    dir
+}
+
+fn clone_of(source: &Path) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    Repository::clone(source.to_str().unwrap(), dir.path()).unwrap();
+    dir
+}
+
+fn path_str(dir: &TempDir) -> &str {
+    dir.path().to_str().unwrap()
+}
+
+#[test]
+fn test_a_pushed_index_is_fetched_into_another_clone() {
+    let origin = indexed_repo("bge-small-en-v1.5");
+    let remote = bare_remote();
+
+    let pushed = git_semantic(&["index", "push", path_str(&remote), "-p", path_str(&origin)]);
+    assert!(
+        pushed.status.success(),
+        "push failed: {}",
+        String::from_utf8_lossy(&pushed.stderr)
+    );
+    assert!(
+        Repository::open(remote.path())
+            .unwrap()
+            .find_reference("refs/semantic/index")
+            .is_ok(),
+        "the remote should have the shared ref"
+    );
+
+    let clone = clone_of(origin.path());
+    let fetched = git_semantic(&["index", "fetch", path_str(&remote), "-p", path_str(&clone)]);
+    let stdout = String::from_utf8_lossy(&fetched.stdout);
+    assert!(
+        fetched.status.success(),
+        "fetch failed: {}",
+        String::from_utf8_lossy(&fetched.stderr)
+    );
+    // The clone has nothing the shared index lacks, so no model is needed.
+    assert!(stdout.contains("already up to date"), "stdout: {stdout}");
+
+    let local = IndexStorage::new(clone.path()).unwrap().load().unwrap();
+    assert_eq!(local.entries.len(), 2);
+    assert!(!local.metadata.include_diffs, "the shared mode is kept");
+}
+
+#[test]
+fn test_an_index_from_another_model_is_rejected() {
+    let origin = indexed_repo("some-other-model");
+    let remote = bare_remote();
+    assert!(
+        git_semantic(&["index", "push", path_str(&remote), "-p", path_str(&origin)])
+            .status
+            .success()
+    );
+
+    let clone = clone_of(origin.path());
+    let fetched = git_semantic(&["index", "fetch", path_str(&remote), "-p", path_str(&clone)]);
+    let stderr = String::from_utf8_lossy(&fetched.stderr);
+    assert!(!fetched.status.success());
+    assert!(stderr.contains("E3013"), "stderr: {stderr}");
+    assert!(
+        IndexStorage::new(clone.path()).unwrap().load().is_err(),
+        "a rejected index must not be adopted"
+    );
+}
+
+#[test]
+fn test_fetching_from_a_remote_with_no_shared_index_fails() {
+    let origin = indexed_repo("bge-small-en-v1.5");
+    let remote = bare_remote();
+
+    let fetched = git_semantic(&[
+        "index",
+        "fetch",
+        path_str(&remote),
+        "--force",
+        "-p",
+        path_str(&origin),
+    ]);
+    let stderr = String::from_utf8_lossy(&fetched.stderr);
+    assert!(!fetched.status.success());
+    assert!(stderr.contains("E3014"), "stderr: {stderr}");
+}
+
+#[test]
+fn test_an_existing_index_is_only_replaced_with_force() {
+    let origin = indexed_repo("bge-small-en-v1.5");
+    let remote = bare_remote();
+    assert!(
+        git_semantic(&["index", "push", path_str(&remote), "-p", path_str(&origin)])
+            .status
+            .success()
+    );
+
+    let clone = clone_of(origin.path());
+    let storage = IndexStorage::new(clone.path()).unwrap();
+    let local = SemanticIndex::new("bge-small-en-v1.5".to_string(), "local".to_string(), true);
+    storage.save(&local).unwrap();
+
+    let refused = git_semantic(&["index", "fetch", path_str(&remote), "-p", path_str(&clone)]);
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(!refused.status.success());
+    assert!(stderr.contains("E3017"), "stderr: {stderr}");
+    assert!(
+        storage.load().unwrap().metadata.include_diffs,
+        "the local index is left alone"
+    );
+
+    let forced = git_semantic(&[
+        "index",
+        "fetch",
+        path_str(&remote),
+        "--force",
+        "-p",
+        path_str(&clone),
+    ]);
+    assert!(
+        forced.status.success(),
+        "fetch --force failed: {}",
+        String::from_utf8_lossy(&forced.stderr)
+    );
+    let adopted = storage.load().unwrap();
+    assert_eq!(adopted.entries.len(), 2);
+    assert!(!adopted.metadata.include_diffs);
+}